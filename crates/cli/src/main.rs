@@ -0,0 +1,706 @@
+//! Lists and changes users, jobs and organizations from the shell.
+//!
+//! Results are written to stdout as a table, JSON or CSV; messages go to
+//! stderr. The exit code tells scripts what went wrong:
+//!
+//! | Code | Meaning                                                    |
+//! |------|------------------------------------------------------------|
+//! | 0    | Success                                                    |
+//! | 1    | The database could not be opened, read or written          |
+//! | 2    | Invalid arguments                                          |
+//! | 3    | Validation failed, or a job or organization does not exist |
+//! | 4    | The user, job or organization to change does not exist     |
+//! | 5    | Still in use, or changed by someone else meanwhile         |
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use thiserror::Error;
+
+use usermgmt_core::domain::services::{JobServiceError, OrganizationServiceError};
+use usermgmt_core::domain::{
+    Entity, Job, Organization, RepositoryError, Services, SortDirection, User, UserQuery,
+    UserServiceError, UserSortKey,
+};
+use usermgmt_core::infrastructure::{
+    get_database_path, org_chart, ChartCollapse, ChartFormat, Database, ExportCell, ExportTable,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Manage users, jobs and organizations from scripts")]
+struct Cli {
+    /// Database file to use instead of the default one
+    #[arg(long, global = true, env = "USERMGMT_DATABASE", value_name = "PATH")]
+    database: Option<PathBuf>,
+
+    /// How results are written to stdout
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List, add, update or delete users
+    #[command(subcommand)]
+    User(UserCommand),
+    /// List, add, update or delete jobs
+    #[command(subcommand)]
+    Job(NamedCommand),
+    /// List, add, update or delete organizations
+    #[command(subcommand, visible_alias = "organization")]
+    Org(NamedCommand),
+    /// Write an org chart as Graphviz DOT, or as SVG with --svg
+    ///
+    /// Users have no managers, so the only lines drawn are from each
+    /// organization to its users. Users whose organization no longer exists
+    /// are listed under "Unassigned".
+    Chart {
+        /// Show one node per job or per organization instead of every user
+        #[arg(long, value_enum)]
+        collapse: Option<Collapse>,
+        /// Draw the chart as SVG instead of writing DOT
+        #[arg(long)]
+        svg: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Collapse {
+    Job,
+    Org,
+}
+
+#[derive(Debug, Subcommand)]
+enum UserCommand {
+    /// List users, by name unless sorted otherwise
+    List {
+        /// Only users whose name contains this
+        #[arg(long)]
+        name: Option<String>,
+        /// Only users with this job, by id or name
+        #[arg(long)]
+        job: Option<String>,
+        /// Only users in this organization, by id or name
+        #[arg(long)]
+        org: Option<String>,
+        #[arg(long, value_enum, default_value_t = SortKey::Name)]
+        sort: SortKey,
+        /// Sort in descending order
+        #[arg(long)]
+        desc: bool,
+    },
+    /// Add a user and print it
+    Add {
+        #[arg(long)]
+        name: String,
+        /// Job id or name
+        #[arg(long)]
+        job: String,
+        /// Organization id or name
+        #[arg(long)]
+        org: String,
+        #[arg(long, default_value = "")]
+        email: String,
+        #[arg(long, default_value = "")]
+        phone: String,
+    },
+    /// Change the given fields of a user and print it
+    Update {
+        id: i64,
+        #[command(flatten)]
+        fields: UserFields,
+    },
+    /// Delete users; either all of them are deleted or none
+    Delete {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+}
+
+#[derive(Debug, Args)]
+struct UserFields {
+    #[arg(long)]
+    name: Option<String>,
+    /// Job id or name
+    #[arg(long)]
+    job: Option<String>,
+    /// Organization id or name
+    #[arg(long)]
+    org: Option<String>,
+    /// An empty value removes the email address
+    #[arg(long)]
+    email: Option<String>,
+    /// An empty value removes the phone number
+    #[arg(long)]
+    phone: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortKey {
+    Id,
+    Name,
+    Job,
+    Org,
+}
+
+/// The commands of jobs and organizations, which only have a name.
+#[derive(Debug, Subcommand)]
+enum NamedCommand {
+    /// List them by name
+    List {
+        /// Only those matching these words as prefixes
+        #[arg(long)]
+        search: Option<String>,
+    },
+    /// Add one and print it
+    Add { name: String },
+    /// Rename one and print it
+    Update {
+        id: i64,
+        #[arg(long)]
+        name: String,
+    },
+    /// Delete them; either all of them are deleted or none
+    Delete {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+}
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("Could not open {0}: {1}")]
+    Open(PathBuf, sqlx::Error),
+    #[error("{}", .0.join("\n"))]
+    Invalid(Vec<String>),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InUse(String),
+    #[error("{0}")]
+    Database(String),
+    #[error("Could not write the output: {0}")]
+    Output(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Open(..) | CliError::Database(_) | CliError::Output(_) => 1,
+            CliError::Invalid(_) => 3,
+            CliError::NotFound(_) => 4,
+            CliError::InUse(_) => 5,
+        }
+    }
+
+    fn invalid(message: impl Into<String>) -> Self {
+        CliError::Invalid(vec![message.into()])
+    }
+}
+
+impl From<RepositoryError> for CliError {
+    fn from(error: RepositoryError) -> Self {
+        match error {
+            RepositoryError::NotFound => CliError::NotFound(error.to_string()),
+            RepositoryError::ConstraintViolation(_) | RepositoryError::Conflict => {
+                CliError::InUse(error.to_string())
+            }
+            RepositoryError::DatabaseError(_) => CliError::Database(error.to_string()),
+        }
+    }
+}
+
+impl From<UserServiceError> for CliError {
+    fn from(error: UserServiceError) -> Self {
+        match error {
+            UserServiceError::RepositoryError(error) => error.into(),
+            UserServiceError::UserNotFound => CliError::NotFound(error.to_string()),
+            _ => CliError::invalid(error.to_string()),
+        }
+    }
+}
+
+impl From<JobServiceError> for CliError {
+    fn from(error: JobServiceError) -> Self {
+        match error {
+            JobServiceError::RepositoryError(error) => error.into(),
+            JobServiceError::ValidationError => CliError::invalid(error.to_string()),
+        }
+    }
+}
+
+impl From<OrganizationServiceError> for CliError {
+    fn from(error: OrganizationServiceError) -> Self {
+        match error {
+            OrganizationServiceError::RepositoryError(error) => error.into(),
+            OrganizationServiceError::ValidationError => CliError::invalid(error.to_string()),
+        }
+    }
+}
+
+impl From<csv::Error> for CliError {
+    fn from(error: csv::Error) -> Self {
+        CliError::Output(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(error: std::io::Error) -> Self {
+        CliError::Output(error.to_string())
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+    match smol::block_on(run(cli)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            for line in e.to_string().lines() {
+                eprintln!("error: {}", line);
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), CliError> {
+    let path = cli.database.unwrap_or_else(get_database_path);
+    let database = Database::open(&path.to_string_lossy(), false)
+        .await
+        .map_err(|e| CliError::Open(path.clone(), e))?;
+    let services = database.services();
+    match cli.command {
+        Command::User(command) => user(&services, command, cli.format).await,
+        Command::Job(command) => job(&services, command, cli.format).await,
+        Command::Org(command) => organization(&services, command, cli.format).await,
+        Command::Chart { collapse, svg } => {
+            let collapse = match collapse {
+                None => ChartCollapse::None,
+                Some(Collapse::Job) => ChartCollapse::Job,
+                Some(Collapse::Org) => ChartCollapse::Organization,
+            };
+            let chart = org_chart(&services, collapse)
+                .await
+                .map_err(|e| CliError::Database(e.to_string()))?;
+            let format = if svg {
+                ChartFormat::Svg
+            } else {
+                ChartFormat::Dot
+            };
+            std::io::stdout().write_all(chart.render(format).as_bytes())?;
+            Ok(())
+        }
+    }
+}
+
+async fn user(services: &Services, command: UserCommand, format: Format) -> Result<(), CliError> {
+    let jobs = services.job.get_all_jobs().await?;
+    let organizations = services.organization.get_all_organizations().await?;
+    match command {
+        UserCommand::List {
+            name,
+            job,
+            org,
+            sort,
+            desc,
+        } => {
+            let query = UserQuery {
+                name_contains: name,
+                job_id: job.map(|job| find(&jobs, &job, "job")).transpose()?,
+                organization_id: org
+                    .map(|org| find(&organizations, &org, "organization"))
+                    .transpose()?,
+                sort: match sort {
+                    SortKey::Id => UserSortKey::Id,
+                    SortKey::Name => UserSortKey::Name,
+                    SortKey::Job => UserSortKey::Job,
+                    SortKey::Org => UserSortKey::Organization,
+                },
+                direction: if desc {
+                    SortDirection::Descending
+                } else {
+                    SortDirection::Ascending
+                },
+                ..UserQuery::default()
+            };
+            let table = ExportTable::users(services, &query, &jobs, &organizations)
+                .await
+                .map_err(|e| CliError::Database(e.to_string()))?;
+            print(&table, format, false)
+        }
+        UserCommand::Add {
+            name,
+            job,
+            org,
+            email,
+            phone,
+        } => {
+            let mut user = User::new();
+            user.set_name(name);
+            user.set_job_id(find(&jobs, &job, "job")?);
+            user.set_organization_id(find(&organizations, &org, "organization")?);
+            user.set_email(email);
+            user.set_phone(phone);
+            validate(&mut user)?;
+            let user = services.user.create_user(user).await?;
+            print(
+                &ExportTable::user_list(&[user], &jobs, &organizations),
+                format,
+                true,
+            )
+        }
+        UserCommand::Update { id, fields } => {
+            let mut user = services
+                .user
+                .get_user_by_id(id)
+                .await?
+                .ok_or_else(|| CliError::NotFound(format!("No user with id {}", id)))?;
+            if let Some(name) = fields.name {
+                user.set_name(name);
+            }
+            if let Some(job) = fields.job {
+                user.set_job_id(find(&jobs, &job, "job")?);
+            }
+            if let Some(org) = fields.org {
+                user.set_organization_id(find(&organizations, &org, "organization")?);
+            }
+            if let Some(email) = fields.email {
+                user.set_email(email);
+            }
+            if let Some(phone) = fields.phone {
+                user.set_phone(phone);
+            }
+            validate(&mut user)?;
+            services.user.update_user(user).await?;
+            let user = services
+                .user
+                .get_user_by_id(id)
+                .await?
+                .ok_or_else(|| CliError::NotFound(format!("No user with id {}", id)))?;
+            print(
+                &ExportTable::user_list(&[user], &jobs, &organizations),
+                format,
+                true,
+            )
+        }
+        UserCommand::Delete { ids } => {
+            let (transaction_services, transaction) = services.begin().await?;
+            let result = async {
+                for &id in &ids {
+                    transaction_services
+                        .user
+                        .delete_user(id)
+                        .await
+                        .map_err(|e| match e {
+                            UserServiceError::UserNotFound => {
+                                CliError::NotFound(format!("No user with id {}", id))
+                            }
+                            e => e.into(),
+                        })?;
+                }
+                Ok::<_, CliError>(())
+            }
+            .await;
+            transaction.complete(result).await?;
+            eprintln!("Deleted {} user{}", ids.len(), plural(ids.len()));
+            Ok(())
+        }
+    }
+}
+
+async fn job(services: &Services, command: NamedCommand, format: Format) -> Result<(), CliError> {
+    match command {
+        NamedCommand::List { search } => {
+            let jobs = match search.filter(|search| !search.trim().is_empty()) {
+                Some(search) => services.job.search_jobs(&search).await?,
+                None => services.job.get_all_jobs().await?,
+            };
+            print(&ExportTable::jobs(&by_name(jobs)), format, false)
+        }
+        NamedCommand::Add { name } => {
+            let mut job = Job::new();
+            job.set_name(name);
+            validate(&mut job)?;
+            let job = services.job.create_job(job).await?;
+            print(&ExportTable::jobs(&[job]), format, true)
+        }
+        NamedCommand::Update { id, name } => {
+            let not_found = || CliError::NotFound(format!("No job with id {}", id));
+            let mut job = services
+                .job
+                .get_job_by_id(id)
+                .await?
+                .ok_or_else(not_found)?;
+            job.set_name(name);
+            validate(&mut job)?;
+            services.job.update_job(job).await?;
+            let job = services
+                .job
+                .get_job_by_id(id)
+                .await?
+                .ok_or_else(not_found)?;
+            print(&ExportTable::jobs(&[job]), format, true)
+        }
+        NamedCommand::Delete { ids } => {
+            let (transaction_services, transaction) = services.begin().await?;
+            let result = async {
+                for &id in &ids {
+                    transaction_services
+                        .job
+                        .delete_job(id)
+                        .await
+                        .map_err(|e| in_use(e.into(), "job", id))?;
+                }
+                Ok::<_, CliError>(())
+            }
+            .await;
+            transaction.complete(result).await?;
+            eprintln!("Deleted {} job{}", ids.len(), plural(ids.len()));
+            Ok(())
+        }
+    }
+}
+
+async fn organization(
+    services: &Services,
+    command: NamedCommand,
+    format: Format,
+) -> Result<(), CliError> {
+    match command {
+        NamedCommand::List { search } => {
+            let organizations = match search.filter(|search| !search.trim().is_empty()) {
+                Some(search) => services.organization.search_organizations(&search).await?,
+                None => services.organization.get_all_organizations().await?,
+            };
+            print(
+                &ExportTable::organizations(&by_name(organizations)),
+                format,
+                false,
+            )
+        }
+        NamedCommand::Add { name } => {
+            let mut organization = Organization::new();
+            organization.set_name(name);
+            validate(&mut organization)?;
+            let organization = services
+                .organization
+                .create_organization(organization)
+                .await?;
+            print(&ExportTable::organizations(&[organization]), format, true)
+        }
+        NamedCommand::Update { id, name } => {
+            let not_found = || CliError::NotFound(format!("No organization with id {}", id));
+            let mut organization = services
+                .organization
+                .get_organization_by_id(id)
+                .await?
+                .ok_or_else(not_found)?;
+            organization.set_name(name);
+            validate(&mut organization)?;
+            services
+                .organization
+                .update_organization(organization)
+                .await?;
+            let organization = services
+                .organization
+                .get_organization_by_id(id)
+                .await?
+                .ok_or_else(not_found)?;
+            print(&ExportTable::organizations(&[organization]), format, true)
+        }
+        NamedCommand::Delete { ids } => {
+            let (transaction_services, transaction) = services.begin().await?;
+            let result = async {
+                for &id in &ids {
+                    transaction_services
+                        .organization
+                        .delete_organization(id)
+                        .await
+                        .map_err(|e| in_use(e.into(), "organization", id))?;
+                }
+                Ok::<_, CliError>(())
+            }
+            .await;
+            transaction.complete(result).await?;
+            eprintln!("Deleted {} organization{}", ids.len(), plural(ids.len()));
+            Ok(())
+        }
+    }
+}
+
+/// Says which job or organization could not be deleted.
+fn in_use(error: CliError, entity: &str, id: i64) -> CliError {
+    match error {
+        CliError::NotFound(_) => CliError::NotFound(format!("No {} with id {}", entity, id)),
+        CliError::InUse(_) => {
+            CliError::InUse(format!("The {} with id {} still has users", entity, id))
+        }
+        error => error,
+    }
+}
+
+/// Finds a job or organization by id, or else by name ignoring case.
+fn find<T: Entity>(list: &[T], reference: &str, entity: &str) -> Result<i64, CliError> {
+    let reference = reference.trim();
+    if let Ok(id) = reference.parse::<i64>() {
+        if list.iter().any(|item| item.id() == id) {
+            return Ok(id);
+        }
+    }
+    let matches: Vec<&T> = list
+        .iter()
+        .filter(|item| item.name().eq_ignore_ascii_case(reference))
+        .collect();
+    match matches.as_slice() {
+        [item] => Ok(item.id()),
+        [] => Err(CliError::invalid(format!(
+            "No {} with id or name '{}'",
+            entity, reference
+        ))),
+        _ => Err(CliError::invalid(format!(
+            "More than one {} is named '{}', use its id",
+            entity, reference
+        ))),
+    }
+}
+
+/// Checks an entity before it is saved, reporting every invalid field.
+fn validate<T: Entity>(entity: &mut T) -> Result<(), CliError> {
+    entity.validate().map_err(|errors| {
+        let mut messages: Vec<String> = errors
+            .iter()
+            .map(|(field, message)| format!("{}: {}", field, message))
+            .collect();
+        messages.sort();
+        CliError::Invalid(messages)
+    })
+}
+
+fn by_name<T: Entity>(mut list: Vec<T>) -> Vec<T> {
+    list.sort_by(|a, b| a.name().cmp(b.name()).then(a.id().cmp(&b.id())));
+    list
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// Writes the table to stdout. A `single` record is written as a JSON
+/// object rather than an array.
+fn print(table: &ExportTable, format: Format, single: bool) -> Result<(), CliError> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    match format {
+        Format::Table => {
+            let text = |cell: &ExportCell| cell.to_string();
+            let mut widths: Vec<usize> = table.headers.iter().map(|h| h.chars().count()).collect();
+            for row in &table.rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(text(cell).chars().count());
+                }
+            }
+            let line = |cells: Vec<(String, bool)>| {
+                cells
+                    .into_iter()
+                    .zip(&widths)
+                    .map(|((text, number), &width)| {
+                        if number {
+                            format!("{:>width$}", text)
+                        } else {
+                            format!("{:<width$}", text)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+            writeln!(
+                out,
+                "{}",
+                line(
+                    table
+                        .headers
+                        .iter()
+                        .map(|h| (h.to_string(), false))
+                        .collect()
+                )
+            )?;
+            writeln!(
+                out,
+                "{}",
+                widths
+                    .iter()
+                    .map(|&width| "-".repeat(width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            )?;
+            for row in &table.rows {
+                writeln!(
+                    out,
+                    "{}",
+                    line(
+                        row.iter()
+                            .map(|cell| (text(cell), matches!(cell, ExportCell::Number(_))))
+                            .collect()
+                    )
+                )?;
+            }
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(&table.headers)?;
+            for row in &table.rows {
+                writer.write_record(row.iter().map(ExportCell::to_string))?;
+            }
+            writer.flush()?;
+        }
+        Format::Json => {
+            let records: Vec<serde_json::Value> = table
+                .rows
+                .iter()
+                .map(|row| {
+                    table
+                        .headers
+                        .iter()
+                        .zip(row)
+                        .map(|(header, cell)| {
+                            let value = match cell {
+                                ExportCell::Number(number) => serde_json::json!(number),
+                                ExportCell::Text(text) => serde_json::json!(text),
+                            };
+                            (header.to_lowercase(), value)
+                        })
+                        .collect::<serde_json::Map<_, _>>()
+                        .into()
+                })
+                .collect();
+            let value = match (single, records.as_slice()) {
+                (true, [record]) => record.clone(),
+                _ => records.into(),
+            };
+            serde_json::to_writer_pretty(&mut out, &value)
+                .map_err(|e| CliError::Output(e.to_string()))?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,177 @@
+//! Runs the `usermgmt` binary against a temporary database and checks its
+//! output and exit codes.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+struct TestCli {
+    // Kept so the directory lives as long as the database in it.
+    _dir: tempfile::TempDir,
+    database: PathBuf,
+}
+
+impl TestCli {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let database = dir.path().join("cli.db");
+        Self {
+            _dir: dir,
+            database,
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        run(&self.database, args)
+    }
+
+    /// Runs a command that must succeed and parses what it printed as JSON.
+    fn json(&self, args: &[&str]) -> Value {
+        let output = self.run(&[&["--format", "json"], args].concat());
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        serde_json::from_slice(&output.stdout).expect("JSON output")
+    }
+
+    /// Adds a record and returns its id as an argument.
+    fn add(&self, args: &[&str]) -> String {
+        self.json(args)["id"].as_i64().expect("an id").to_string()
+    }
+}
+
+fn run(database: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_usermgmt"))
+        .arg("--database")
+        .arg(database)
+        .args(args)
+        .env_remove("USERMGMT_DATABASE")
+        .output()
+        .expect("usermgmt runs")
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Checks the exit code and the first error line.
+fn assert_fails(output: &Output, code: i32, message: &str) {
+    assert_eq!(output.status.code(), Some(code), "{}", stderr(output));
+    assert!(output.stdout.is_empty());
+    assert!(
+        stderr(output).starts_with(&format!("error: {}", message)),
+        "{}",
+        stderr(output)
+    );
+}
+
+#[test]
+fn records_are_added_listed_and_deleted() {
+    let cli = TestCli::new();
+    let job = cli.add(&["job", "add", "Developer"]);
+    let organization = cli.add(&["org", "add", "Engineering"]);
+    let ada = cli.json(&[
+        "user",
+        "add",
+        "--name",
+        "Ada Lovelace",
+        "--job",
+        "developer",
+        "--org",
+        &organization,
+        "--email",
+        "ada@example.com",
+    ]);
+    assert_eq!(ada["name"], "Ada Lovelace");
+    assert_eq!(ada["job"], "Developer");
+    assert_eq!(ada["organization"], "Engineering");
+    assert_eq!(ada["email"], "ada@example.com");
+
+    let id = ada["id"].as_i64().expect("an id").to_string();
+    let updated = cli.json(&["user", "update", &id, "--phone", "+44 20 7946 0000"]);
+    assert_eq!(updated["phone"], "+44 20 7946 0000");
+    assert_eq!(updated["email"], "ada@example.com");
+    assert_eq!(cli.json(&["user", "list"]), Value::Array(vec![updated]));
+
+    let deleted = cli.run(&["user", "delete", &id]);
+    assert_eq!(deleted.status.code(), Some(0), "{}", stderr(&deleted));
+    assert_eq!(stderr(&deleted), "Deleted 1 user\n");
+    let deleted = cli.run(&["job", "delete", &job]);
+    assert_eq!(deleted.status.code(), Some(0), "{}", stderr(&deleted));
+    assert_eq!(cli.json(&["user", "list"]), Value::Array(Vec::new()));
+    assert_eq!(cli.json(&["job", "list"]), Value::Array(Vec::new()));
+}
+
+#[test]
+fn missing_records_are_not_found() {
+    let cli = TestCli::new();
+    let job = cli.add(&["job", "add", "Developer"]);
+
+    assert_fails(
+        &cli.run(&["user", "update", "42", "--name", "Nobody"]),
+        4,
+        "No user with id 42",
+    );
+    assert_fails(&cli.run(&["user", "delete", "42"]), 4, "No user with id 42");
+    assert_fails(
+        &cli.run(&["org", "update", "42", "--name", "Nowhere"]),
+        4,
+        "No organization with id 42",
+    );
+    // Either every job is deleted or none is.
+    assert_fails(
+        &cli.run(&["job", "delete", &job, "42"]),
+        4,
+        "No job with id 42",
+    );
+    assert_eq!(cli.json(&["job", "list"])[0]["name"], "Developer");
+
+    // A job or organization a user refers to is invalid input instead.
+    assert_fails(
+        &cli.run(&[
+            "user",
+            "add",
+            "--name",
+            "Ada",
+            "--job",
+            "Developer",
+            "--org",
+            "42",
+        ]),
+        3,
+        "No organization with id or name '42'",
+    );
+}
+
+#[test]
+fn records_in_use_are_not_deleted() {
+    let cli = TestCli::new();
+    let job = cli.add(&["job", "add", "Developer"]);
+    let unused = cli.add(&["job", "add", "Analyst"]);
+    let organization = cli.add(&["org", "add", "Engineering"]);
+    cli.add(&[
+        "user",
+        "add",
+        "--name",
+        "Ada Lovelace",
+        "--job",
+        &job,
+        "--org",
+        &organization,
+    ]);
+
+    assert_fails(
+        &cli.run(&["org", "delete", &organization]),
+        5,
+        &format!("The organization with id {} still has users", organization),
+    );
+    assert_fails(
+        &cli.run(&["job", "delete", &unused, &job]),
+        5,
+        &format!("The job with id {} still has users", job),
+    );
+    assert_eq!(cli.json(&["job", "list"]).as_array().map(Vec::len), Some(2));
+    assert_eq!(cli.json(&["org", "list"]).as_array().map(Vec::len), Some(1));
+    assert_eq!(
+        cli.json(&["user", "list"]).as_array().map(Vec::len),
+        Some(1)
+    );
+}
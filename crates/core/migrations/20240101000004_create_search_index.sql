@@ -0,0 +1,49 @@
+-- One full-text index over the names of users, jobs and organizations.
+-- The rowid is id * 3 plus a per-table offset, so triggers can address a
+-- record's row directly instead of scanning the index.
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    entity_type UNINDEXED,
+    entity_id UNINDEXED,
+    name,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO search_index (rowid, entity_type, entity_id, name)
+SELECT id * 3, 'user', id, name FROM users;
+INSERT INTO search_index (rowid, entity_type, entity_id, name)
+SELECT id * 3 + 1, 'job', id, name FROM jobs;
+INSERT INTO search_index (rowid, entity_type, entity_id, name)
+SELECT id * 3 + 2, 'organization', id, name FROM organizations;
+
+CREATE TRIGGER IF NOT EXISTS users_search_insert AFTER INSERT ON users BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, name)
+    VALUES (new.id * 3, 'user', new.id, new.name);
+END;
+CREATE TRIGGER IF NOT EXISTS users_search_update AFTER UPDATE OF name ON users BEGIN
+    UPDATE search_index SET name = new.name WHERE rowid = old.id * 3;
+END;
+CREATE TRIGGER IF NOT EXISTS users_search_delete AFTER DELETE ON users BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 3;
+END;
+
+CREATE TRIGGER IF NOT EXISTS jobs_search_insert AFTER INSERT ON jobs BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, name)
+    VALUES (new.id * 3 + 1, 'job', new.id, new.name);
+END;
+CREATE TRIGGER IF NOT EXISTS jobs_search_update AFTER UPDATE OF name ON jobs BEGIN
+    UPDATE search_index SET name = new.name WHERE rowid = old.id * 3 + 1;
+END;
+CREATE TRIGGER IF NOT EXISTS jobs_search_delete AFTER DELETE ON jobs BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 3 + 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS organizations_search_insert AFTER INSERT ON organizations BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, name)
+    VALUES (new.id * 3 + 2, 'organization', new.id, new.name);
+END;
+CREATE TRIGGER IF NOT EXISTS organizations_search_update AFTER UPDATE OF name ON organizations BEGIN
+    UPDATE search_index SET name = new.name WHERE rowid = old.id * 3 + 2;
+END;
+CREATE TRIGGER IF NOT EXISTS organizations_search_delete AFTER DELETE ON organizations BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 3 + 2;
+END;
@@ -0,0 +1,39 @@
+-- A revision per table, bumped by triggers on every write. Other processes
+-- writing to the same file bump it too, so an open app can poll it and
+-- reload only the lists that changed.
+CREATE TABLE IF NOT EXISTS change_counters (
+    entity_type TEXT PRIMARY KEY,
+    revision INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT OR IGNORE INTO change_counters (entity_type) VALUES ('user'), ('job'), ('organization');
+
+CREATE TRIGGER IF NOT EXISTS users_change_insert AFTER INSERT ON users BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'user';
+END;
+CREATE TRIGGER IF NOT EXISTS users_change_update AFTER UPDATE ON users BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'user';
+END;
+CREATE TRIGGER IF NOT EXISTS users_change_delete AFTER DELETE ON users BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'user';
+END;
+
+CREATE TRIGGER IF NOT EXISTS jobs_change_insert AFTER INSERT ON jobs BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'job';
+END;
+CREATE TRIGGER IF NOT EXISTS jobs_change_update AFTER UPDATE ON jobs BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'job';
+END;
+CREATE TRIGGER IF NOT EXISTS jobs_change_delete AFTER DELETE ON jobs BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'job';
+END;
+
+CREATE TRIGGER IF NOT EXISTS organizations_change_insert AFTER INSERT ON organizations BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'organization';
+END;
+CREATE TRIGGER IF NOT EXISTS organizations_change_update AFTER UPDATE ON organizations BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'organization';
+END;
+CREATE TRIGGER IF NOT EXISTS organizations_change_delete AFTER DELETE ON organizations BEGIN
+    UPDATE change_counters SET revision = revision + 1 WHERE entity_type = 'organization';
+END;
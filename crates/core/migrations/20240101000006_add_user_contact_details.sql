@@ -0,0 +1,3 @@
+-- Contact details, used by the contact exports. Both are optional.
+ALTER TABLE users ADD COLUMN email TEXT NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN phone TEXT NOT NULL DEFAULT '';
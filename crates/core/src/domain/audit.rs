@@ -0,0 +1,138 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+use super::DomainEntity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 3] = [
+        AuditAction::Create,
+        AuditAction::Update,
+        AuditAction::Delete,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "create" => Some(AuditAction::Create),
+            "update" => Some(AuditAction::Update),
+            "delete" => Some(AuditAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditAction::Create => write!(f, "Create"),
+            AuditAction::Update => write!(f, "Update"),
+            AuditAction::Delete => write!(f, "Delete"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub entity_type: DomainEntity,
+    pub entity_id: i64,
+    pub action: AuditAction,
+    pub timestamp: DateTime<Utc>,
+    pub operator: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        entity_type: DomainEntity,
+        entity_id: i64,
+        action: AuditAction,
+        operator: &str,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Self {
+        Self {
+            id: 0,
+            entity_type,
+            entity_id,
+            action,
+            timestamp: Utc::now(),
+            operator: operator.to_string(),
+            before,
+            after,
+        }
+    }
+
+    /// Lists the fields that differ between the before and after snapshots.
+    pub fn changes(&self) -> Vec<(String, Option<String>, Option<String>)> {
+        let before = parse_snapshot(self.before.as_deref());
+        let after = parse_snapshot(self.after.as_deref());
+
+        let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+        fields.sort();
+        fields.dedup();
+
+        fields
+            .into_iter()
+            .filter(|field| before.get(*field) != after.get(*field))
+            .map(|field| {
+                (
+                    field.clone(),
+                    before.get(field).cloned(),
+                    after.get(field).cloned(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn parse_snapshot(snapshot: Option<&str>) -> BTreeMap<String, String> {
+    let Some(snapshot) = snapshot else {
+        return BTreeMap::new();
+    };
+
+    match serde_json::from_str::<serde_json::Value>(snapshot) {
+        Ok(serde_json::Value::Object(fields)) => fields
+            .into_iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(s) => (key, s),
+                other => (key, other.to_string()),
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditFilter {
+    pub entity_type: Option<DomainEntity>,
+    pub entity_id: Option<i64>,
+    pub action: Option<AuditAction>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl AuditFilter {
+    pub fn for_record(entity_type: DomainEntity, entity_id: i64) -> Self {
+        Self {
+            entity_type: Some(entity_type),
+            entity_id: Some(entity_id),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use super::services::{JobServiceError, OrganizationServiceError, Services, UserServiceError};
+use super::{DomainEntity, Entity, Job, Organization, RepositoryError, User};
+
+/// A data-changing operation that can be replayed through the services.
+///
+/// Executing a command returns its inverse, so the same mechanism drives
+/// the original action, undo and redo. A batch runs in a single transaction
+/// and leaves nothing behind if any of its commands fails.
+#[derive(Debug, Clone)]
+pub enum Command {
+    CreateUser(User),
+    RestoreUser(User),
+    UpdateUser(User),
+    DeleteUser(i64),
+
+    CreateJob(Job),
+    RestoreJob(Job),
+    UpdateJob(Job),
+    DeleteJob(i64),
+
+    CreateOrganization(Organization),
+    RestoreOrganization(Organization),
+    UpdateOrganization(Organization),
+    DeleteOrganization(i64),
+
+    Batch(Vec<Command>),
+}
+
+impl Command {
+    pub async fn execute(&self, services: &Services) -> Result<Command, CommandError> {
+        if !matches!(self, Command::Batch(_)) {
+            return execute_one(self, services).await;
+        }
+
+        let commands = self.clone().flatten();
+        let (services, transaction) = services.begin().await?;
+        let result = async {
+            let mut inverses = Vec::with_capacity(commands.len());
+            for command in &commands {
+                inverses.push(execute_one(command, &services).await?);
+            }
+            Ok::<_, CommandError>(inverses)
+        }
+        .await;
+        let mut inverses = transaction.complete(result).await?;
+
+        inverses.reverse();
+        Ok(Command::Batch(inverses))
+    }
+
+    /// The entity lists that need reloading after this command runs.
+    pub fn affected(&self) -> Vec<DomainEntity> {
+        let mut affected: Vec<DomainEntity> = Vec::new();
+        for command in self.clone().flatten() {
+            let entity = match command {
+                Command::CreateUser(_)
+                | Command::RestoreUser(_)
+                | Command::UpdateUser(_)
+                | Command::DeleteUser(_) => DomainEntity::User,
+                Command::CreateJob(_)
+                | Command::RestoreJob(_)
+                | Command::UpdateJob(_)
+                | Command::DeleteJob(_) => DomainEntity::Job,
+                Command::CreateOrganization(_)
+                | Command::RestoreOrganization(_)
+                | Command::UpdateOrganization(_)
+                | Command::DeleteOrganization(_) => DomainEntity::Organization,
+                Command::Batch(_) => continue,
+            };
+            if !affected.contains(&entity) {
+                affected.push(entity);
+            }
+        }
+        affected
+    }
+
+    /// The record a single update command targets, used to resolve conflicts.
+    pub fn update_target(&self) -> Option<(DomainEntity, i64)> {
+        match self {
+            Command::UpdateUser(user) => Some((DomainEntity::User, user.id())),
+            Command::UpdateJob(job) => Some((DomainEntity::Job, job.id())),
+            Command::UpdateOrganization(organization) => {
+                Some((DomainEntity::Organization, organization.id()))
+            }
+            _ => None,
+        }
+    }
+
+    fn flatten(self) -> Vec<Command> {
+        match self {
+            Command::Batch(commands) => commands.into_iter().flat_map(Command::flatten).collect(),
+            command => vec![command],
+        }
+    }
+}
+
+async fn execute_one(command: &Command, services: &Services) -> Result<Command, CommandError> {
+    match command {
+        Command::CreateUser(user) => {
+            let saved_user = services.user.create_user(user.clone()).await?;
+            Ok(Command::DeleteUser(saved_user.id()))
+        }
+        Command::RestoreUser(user) => {
+            services.user.restore_user(user.clone()).await?;
+            Ok(Command::DeleteUser(user.id()))
+        }
+        Command::UpdateUser(user) => {
+            let mut before = services
+                .user
+                .get_user_by_id(user.id())
+                .await?
+                .ok_or(CommandError::NotFound(DomainEntity::User))?;
+            services.user.update_user(user.clone()).await?;
+            before.set_version(user.version() + 1);
+            Ok(Command::UpdateUser(before))
+        }
+        Command::DeleteUser(id) => {
+            let before = services
+                .user
+                .get_user_by_id(*id)
+                .await?
+                .ok_or(CommandError::NotFound(DomainEntity::User))?;
+            services.user.delete_user(*id).await?;
+            Ok(Command::RestoreUser(before))
+        }
+
+        Command::CreateJob(job) => {
+            let saved_job = services.job.create_job(job.clone()).await?;
+            Ok(Command::DeleteJob(saved_job.id()))
+        }
+        Command::RestoreJob(job) => {
+            services.job.restore_job(job.clone()).await?;
+            Ok(Command::DeleteJob(job.id()))
+        }
+        Command::UpdateJob(job) => {
+            let mut before = services
+                .job
+                .get_job_by_id(job.id())
+                .await?
+                .ok_or(CommandError::NotFound(DomainEntity::Job))?;
+            services.job.update_job(job.clone()).await?;
+            before.set_version(job.version() + 1);
+            Ok(Command::UpdateJob(before))
+        }
+        Command::DeleteJob(id) => {
+            let before = services
+                .job
+                .get_job_by_id(*id)
+                .await?
+                .ok_or(CommandError::NotFound(DomainEntity::Job))?;
+            services.job.delete_job(*id).await?;
+            Ok(Command::RestoreJob(before))
+        }
+
+        Command::CreateOrganization(organization) => {
+            let saved_organization = services
+                .organization
+                .create_organization(organization.clone())
+                .await?;
+            Ok(Command::DeleteOrganization(saved_organization.id()))
+        }
+        Command::RestoreOrganization(organization) => {
+            services
+                .organization
+                .restore_organization(organization.clone())
+                .await?;
+            Ok(Command::DeleteOrganization(organization.id()))
+        }
+        Command::UpdateOrganization(organization) => {
+            let mut before = services
+                .organization
+                .get_organization_by_id(organization.id())
+                .await?
+                .ok_or(CommandError::NotFound(DomainEntity::Organization))?;
+            services
+                .organization
+                .update_organization(organization.clone())
+                .await?;
+            before.set_version(organization.version() + 1);
+            Ok(Command::UpdateOrganization(before))
+        }
+        Command::DeleteOrganization(id) => {
+            let before = services
+                .organization
+                .get_organization_by_id(*id)
+                .await?
+                .ok_or(CommandError::NotFound(DomainEntity::Organization))?;
+            services.organization.delete_organization(*id).await?;
+            Ok(Command::RestoreOrganization(before))
+        }
+
+        Command::Batch(_) => unreachable!("batches are flattened before execution"),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("{0} not found")]
+    NotFound(DomainEntity),
+
+    #[error("Database error: {0}")]
+    RepositoryError(#[from] RepositoryError),
+
+    #[error(transparent)]
+    User(#[from] UserServiceError),
+
+    #[error(transparent)]
+    Job(#[from] JobServiceError),
+
+    #[error(transparent)]
+    Organization(#[from] OrganizationServiceError),
+}
+
+impl CommandError {
+    pub fn is_conflict(&self) -> bool {
+        matches!(
+            self,
+            CommandError::RepositoryError(RepositoryError::Conflict)
+                | CommandError::User(UserServiceError::RepositoryError(RepositoryError::Conflict))
+                | CommandError::Job(JobServiceError::RepositoryError(RepositoryError::Conflict))
+                | CommandError::Organization(OrganizationServiceError::RepositoryError(
+                    RepositoryError::Conflict
+                ))
+        )
+    }
+}
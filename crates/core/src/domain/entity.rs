@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+pub trait Entity: Clone + Default + std::fmt::Debug + serde::Serialize {
+    fn id(&self) -> i64;
+    fn set_id(&mut self, id: i64);
+    fn name(&self) -> &str;
+    fn set_name(&mut self, name: String);
+    fn version(&self) -> i64;
+    fn set_version(&mut self, version: i64);
+    fn errors(&self) -> &HashMap<&'static str, &'static str>;
+    fn validate(&mut self) -> Result<(), &HashMap<&'static str, &'static str>>;
+    fn validate_property(&mut self, propery: &str);
+    fn clear_errors(&mut self);
+
+    fn snapshot(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainEntity {
+    User,
+    Job,
+    Organization,
+    None,
+}
+
+impl DomainEntity {
+    pub const ALL: [DomainEntity; 3] = [
+        DomainEntity::User,
+        DomainEntity::Job,
+        DomainEntity::Organization,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainEntity::User => "user",
+            DomainEntity::Job => "job",
+            DomainEntity::Organization => "organization",
+            DomainEntity::None => "none",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "user" => DomainEntity::User,
+            "job" => DomainEntity::Job,
+            "organization" => DomainEntity::Organization,
+            _ => DomainEntity::None,
+        }
+    }
+}
+
+impl std::fmt::Display for DomainEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DomainEntity::User => write!(f, "User"),
+            DomainEntity::Job => write!(f, "Job"),
+            DomainEntity::Organization => write!(f, "Organization"),
+            DomainEntity::None => write!(f, "None"),
+        }
+    }
+}
@@ -1,11 +1,14 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::Entity;
 
-#[derive(Default, Clone, Debug, PartialEq)]
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Job {
     id: i64,
     name: String,
+    version: i64,
+    #[serde(skip)]
     errors: HashMap<&'static str, &'static str>,
 }
 
@@ -32,6 +35,14 @@ impl Entity for Job {
         self.name = name;
     }
 
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
     fn errors(&self) -> &HashMap<&'static str, &'static str> {
         &self.errors
     }
@@ -56,20 +67,17 @@ impl Entity for Job {
     }
 
     fn validate_property(&mut self, propery: &str) {
-        match propery {
-            "name" => {
-                self.errors.remove("name");
-                if self.name.trim().is_empty() {
-                    self.errors.insert("name", "Name is required");
-                } else if self.name.len() < 3 {
-                    self.errors
-                        .insert("name", "Name must be at least 3 characters");
-                } else if self.name.len() > 50 {
-                    self.errors
-                        .insert("name", "Name must be under 50 characters");
-                }
+        if propery == "name" {
+            self.errors.remove("name");
+            if self.name.trim().is_empty() {
+                self.errors.insert("name", "Name is required");
+            } else if self.name.len() < 3 {
+                self.errors
+                    .insert("name", "Name must be at least 3 characters");
+            } else if self.name.len() > 50 {
+                self.errors
+                    .insert("name", "Name must be under 50 characters");
             }
-            _ => {}
         }
     }
     fn clear_errors(&mut self) {
@@ -0,0 +1,28 @@
+mod audit;
+mod command;
+mod entity;
+mod job;
+mod organization;
+pub mod repositories;
+pub mod services;
+mod unit_of_work;
+mod user;
+mod user_query;
+
+pub use audit::{AuditAction, AuditEntry, AuditFilter};
+pub use command::{Command, CommandError};
+pub use entity::DomainEntity;
+pub use entity::Entity;
+pub use job::Job;
+pub use organization::Organization;
+pub use repositories::{
+    AuditRepository, JobRepository, OrganizationRepository, RepositoryError, UserRepository,
+};
+pub use services::{
+    AuditService, JobService, OrganizationService, Services, UserService, UserServiceError,
+};
+pub use unit_of_work::{Repositories, Transaction, UnitOfWork};
+pub use user::User;
+pub use user_query::{
+    SortDirection, UserCursor, UserPage, UserQuery, UserSortKey, DEFAULT_PAGE_SIZE,
+};
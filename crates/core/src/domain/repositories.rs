@@ -1,11 +1,20 @@
-use super::{Job, Organization, User};
+use super::{AuditEntry, AuditFilter, Job, Organization, User, UserPage, UserQuery};
 use async_trait::async_trait;
 
+/// The most records a repository `search` returns.
+pub const SEARCH_LIMIT: i64 = 50;
+
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn find_by_id(&self, id: i64) -> Result<Option<User>, RepositoryError>;
     async fn find_all(&self) -> Result<Vec<User>, RepositoryError>;
+    /// Records whose name contains every word of `query` as a word prefix,
+    /// best matches first.
+    async fn search(&self, query: &str) -> Result<Vec<User>, RepositoryError>;
+    /// The page of users the query asks for, with the count of all matches.
+    async fn find_page(&self, query: &UserQuery) -> Result<UserPage, RepositoryError>;
     async fn create(&self, user: &User) -> Result<User, RepositoryError>;
+    async fn restore(&self, user: &User) -> Result<(), RepositoryError>;
     async fn update(&self, user: &User) -> Result<(), RepositoryError>;
     async fn delete(&self, id: i64) -> Result<(), RepositoryError>;
 }
@@ -14,7 +23,11 @@ pub trait UserRepository: Send + Sync {
 pub trait JobRepository: Send + Sync {
     async fn find_by_id(&self, id: i64) -> Result<Option<Job>, RepositoryError>;
     async fn find_all(&self) -> Result<Vec<Job>, RepositoryError>;
+    /// Records whose name contains every word of `query` as a word prefix,
+    /// best matches first.
+    async fn search(&self, query: &str) -> Result<Vec<Job>, RepositoryError>;
     async fn create(&self, job: &Job) -> Result<Job, RepositoryError>;
+    async fn restore(&self, job: &Job) -> Result<(), RepositoryError>;
     async fn update(&self, job: &Job) -> Result<(), RepositoryError>;
     async fn delete(&self, id: i64) -> Result<(), RepositoryError>;
 }
@@ -23,11 +36,21 @@ pub trait JobRepository: Send + Sync {
 pub trait OrganizationRepository: Send + Sync {
     async fn find_by_id(&self, id: i64) -> Result<Option<Organization>, RepositoryError>;
     async fn find_all(&self) -> Result<Vec<Organization>, RepositoryError>;
+    /// Records whose name contains every word of `query` as a word prefix,
+    /// best matches first.
+    async fn search(&self, query: &str) -> Result<Vec<Organization>, RepositoryError>;
     async fn create(&self, org: &Organization) -> Result<Organization, RepositoryError>;
+    async fn restore(&self, org: &Organization) -> Result<(), RepositoryError>;
     async fn update(&self, org: &Organization) -> Result<(), RepositoryError>;
     async fn delete(&self, id: i64) -> Result<(), RepositoryError>;
 }
 
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn record(&self, entry: &AuditEntry) -> Result<AuditEntry, RepositoryError>;
+    async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, RepositoryError>;
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum RepositoryError {
     #[error("Entity not found")]
@@ -36,4 +59,6 @@ pub enum RepositoryError {
     DatabaseError(String),
     #[error("Constraint violation: {0}")]
     ConstraintViolation(String),
+    #[error("Entity was modified by someone else")]
+    Conflict,
 }
@@ -0,0 +1,58 @@
+use crate::domain::{
+    repositories::RepositoryError, AuditEntry, AuditFilter, DomainEntity, UnitOfWork,
+};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct AuditService {
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl std::fmt::Debug for AuditService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditService")
+            .field("unit_of_work", &"Arc<dyn UnitOfWork>")
+            .finish()
+    }
+}
+
+impl AuditService {
+    pub fn new(unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        Self { unit_of_work }
+    }
+
+    pub async fn get_entries(
+        &self,
+        filter: AuditFilter,
+    ) -> Result<Vec<AuditEntry>, AuditServiceError> {
+        if let (Some(from), Some(to)) = (filter.from, filter.to) {
+            if from > to {
+                return Err(AuditServiceError::InvalidDateRange);
+            }
+        }
+
+        Ok(self.unit_of_work.repositories().audit.find(&filter).await?)
+    }
+
+    pub async fn get_history(
+        &self,
+        entity_type: DomainEntity,
+        entity_id: i64,
+    ) -> Result<Vec<AuditEntry>, AuditServiceError> {
+        Ok(self
+            .unit_of_work
+            .repositories()
+            .audit
+            .find(&AuditFilter::for_record(entity_type, entity_id))
+            .await?)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditServiceError {
+    #[error("Invalid date range")]
+    InvalidDateRange,
+
+    #[error("Database error: {0}")]
+    RepositoryError(#[from] RepositoryError),
+}
@@ -0,0 +1,77 @@
+use crate::domain::{repositories::RepositoryError, Entity, Job, UnitOfWork};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct JobService {
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl JobService {
+    pub fn new(unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        Self { unit_of_work }
+    }
+
+    pub async fn create_job(&self, mut job: Job) -> Result<Job, JobServiceError> {
+        job.validate()
+            .map_err(|_| JobServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction.repositories().job.create(&job).await;
+        Ok(transaction.complete(result).await?)
+    }
+
+    /// Re-inserts a previously deleted job under its original id.
+    pub async fn restore_job(&self, mut job: Job) -> Result<(), JobServiceError> {
+        job.validate()
+            .map_err(|_| JobServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction.repositories().job.restore(&job).await;
+        Ok(transaction.complete(result).await?)
+    }
+
+    pub async fn update_job(&self, mut job: Job) -> Result<(), JobServiceError> {
+        job.validate()
+            .map_err(|_| JobServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction.repositories().job.update(&job).await;
+        Ok(transaction.complete(result).await?)
+    }
+
+    pub async fn get_all_jobs(&self) -> Result<Vec<Job>, JobServiceError> {
+        Ok(self.unit_of_work.repositories().job.find_all().await?)
+    }
+
+    /// Jobs matching the words of `query` as prefixes, best matches first.
+    pub async fn search_jobs(&self, query: &str) -> Result<Vec<Job>, JobServiceError> {
+        Ok(self.unit_of_work.repositories().job.search(query).await?)
+    }
+
+    pub async fn get_job_by_id(&self, id: i64) -> Result<Option<Job>, JobServiceError> {
+        Ok(self.unit_of_work.repositories().job.find_by_id(id).await?)
+    }
+
+    pub async fn delete_job(&self, id: i64) -> Result<(), JobServiceError> {
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction.repositories().job.delete(id).await;
+        Ok(transaction.complete(result).await?)
+    }
+}
+
+impl std::fmt::Debug for JobService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobService")
+            .field("unit_of_work", &"Arc<dyn UnitOfWork>")
+            .finish()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JobServiceError {
+    #[error("Job validation failed")]
+    ValidationError,
+
+    #[error("Database error: {0}")]
+    RepositoryError(#[from] RepositoryError),
+}
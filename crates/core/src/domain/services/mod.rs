@@ -0,0 +1,52 @@
+mod audit_service;
+mod job_service;
+mod organization_service;
+mod user_service;
+
+pub use audit_service::{AuditService, AuditServiceError};
+pub use job_service::{JobService, JobServiceError};
+pub use organization_service::{OrganizationService, OrganizationServiceError};
+pub use user_service::{UserService, UserServiceError};
+
+use super::{repositories::RepositoryError, Transaction, UnitOfWork};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct Services {
+    pub user: UserService,
+    pub job: JobService,
+    pub organization: OrganizationService,
+    pub audit: AuditService,
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl std::fmt::Debug for Services {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Services")
+            .field("user", &self.user)
+            .field("job", &self.job)
+            .field("organization", &self.organization)
+            .field("audit", &self.audit)
+            .finish()
+    }
+}
+
+impl Services {
+    pub fn new(unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        Self {
+            user: UserService::new(unit_of_work.clone()),
+            job: JobService::new(unit_of_work.clone()),
+            organization: OrganizationService::new(unit_of_work.clone()),
+            audit: AuditService::new(unit_of_work.clone()),
+            unit_of_work,
+        }
+    }
+
+    /// Starts a transaction and returns services that run inside it, for
+    /// operations spanning several service calls. Nothing they write is kept
+    /// unless the returned transaction is committed.
+    pub async fn begin(&self) -> Result<(Services, Arc<dyn Transaction>), RepositoryError> {
+        let transaction = self.unit_of_work.begin().await?;
+        Ok((Services::new(transaction.clone()), transaction))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::domain::{repositories::RepositoryError, Entity, Organization, UnitOfWork};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct OrganizationService {
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl std::fmt::Debug for OrganizationService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrganizationService")
+            .field("unit_of_work", &"Arc<dyn UnitOfWork>")
+            .finish()
+    }
+}
+
+impl OrganizationService {
+    pub fn new(unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        Self { unit_of_work }
+    }
+
+    pub async fn create_organization(
+        &self,
+        mut organization: Organization,
+    ) -> Result<Organization, OrganizationServiceError> {
+        organization
+            .validate()
+            .map_err(|_| OrganizationServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction
+            .repositories()
+            .organization
+            .create(&organization)
+            .await;
+        Ok(transaction.complete(result).await?)
+    }
+
+    /// Re-inserts a previously deleted organization under its original id.
+    pub async fn restore_organization(
+        &self,
+        mut organization: Organization,
+    ) -> Result<(), OrganizationServiceError> {
+        organization
+            .validate()
+            .map_err(|_| OrganizationServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction
+            .repositories()
+            .organization
+            .restore(&organization)
+            .await;
+        Ok(transaction.complete(result).await?)
+    }
+
+    pub async fn update_organization(
+        &self,
+        mut organization: Organization,
+    ) -> Result<(), OrganizationServiceError> {
+        organization
+            .validate()
+            .map_err(|_| OrganizationServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction
+            .repositories()
+            .organization
+            .update(&organization)
+            .await;
+        Ok(transaction.complete(result).await?)
+    }
+
+    pub async fn get_all_organizations(
+        &self,
+    ) -> Result<Vec<Organization>, OrganizationServiceError> {
+        Ok(self
+            .unit_of_work
+            .repositories()
+            .organization
+            .find_all()
+            .await?)
+    }
+
+    /// Organizations matching the words of `query` as prefixes, best matches first.
+    pub async fn search_organizations(
+        &self,
+        query: &str,
+    ) -> Result<Vec<Organization>, OrganizationServiceError> {
+        Ok(self
+            .unit_of_work
+            .repositories()
+            .organization
+            .search(query)
+            .await?)
+    }
+
+    pub async fn get_organization_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<Organization>, OrganizationServiceError> {
+        Ok(self
+            .unit_of_work
+            .repositories()
+            .organization
+            .find_by_id(id)
+            .await?)
+    }
+
+    pub async fn delete_organization(&self, id: i64) -> Result<(), OrganizationServiceError> {
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction.repositories().organization.delete(id).await;
+        Ok(transaction.complete(result).await?)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrganizationServiceError {
+    #[error("Organization validation failed")]
+    ValidationError,
+
+    #[error("Database error: {0}")]
+    RepositoryError(#[from] RepositoryError),
+}
@@ -0,0 +1,145 @@
+use crate::domain::{
+    repositories::RepositoryError, unit_of_work::Repositories, Entity, UnitOfWork, User, UserPage,
+    UserQuery,
+};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct UserService {
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl std::fmt::Debug for UserService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserService")
+            .field("unit_of_work", &"Arc<dyn UnitOfWork>")
+            .finish()
+    }
+}
+
+impl UserService {
+    pub fn new(unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        Self { unit_of_work }
+    }
+
+    pub async fn create_user(&self, mut user: User) -> Result<User, UserServiceError> {
+        user.validate()
+            .map_err(|_| UserServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let repositories = transaction.repositories();
+        let result = async {
+            check_references(&repositories, &user).await?;
+            Ok(repositories.user.create(&user).await?)
+        }
+        .await;
+        transaction.complete(result).await
+    }
+
+    /// Re-inserts a previously deleted user under its original id.
+    pub async fn restore_user(&self, mut user: User) -> Result<(), UserServiceError> {
+        user.validate()
+            .map_err(|_| UserServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let repositories = transaction.repositories();
+        let result = async {
+            check_references(&repositories, &user).await?;
+            Ok(repositories.user.restore(&user).await?)
+        }
+        .await;
+        transaction.complete(result).await
+    }
+
+    pub async fn update_user(&self, mut user: User) -> Result<(), UserServiceError> {
+        user.validate()
+            .map_err(|_| UserServiceError::ValidationError)?;
+
+        let transaction = self.unit_of_work.begin().await?;
+        let repositories = transaction.repositories();
+        let result = async {
+            check_references(&repositories, &user).await?;
+            repositories.user.update(&user).await.map_err(|e| match e {
+                RepositoryError::NotFound => UserServiceError::UserNotFound,
+                e => e.into(),
+            })
+        }
+        .await;
+        transaction.complete(result).await
+    }
+
+    pub async fn get_all_users(&self) -> Result<Vec<User>, UserServiceError> {
+        Ok(self.unit_of_work.repositories().user.find_all().await?)
+    }
+
+    /// Users matching the words of `query` as prefixes, best matches first.
+    pub async fn search_users(&self, query: &str) -> Result<Vec<User>, UserServiceError> {
+        Ok(self.unit_of_work.repositories().user.search(query).await?)
+    }
+
+    pub async fn get_user_page(&self, query: &UserQuery) -> Result<UserPage, UserServiceError> {
+        Ok(self
+            .unit_of_work
+            .repositories()
+            .user
+            .find_page(query)
+            .await?)
+    }
+
+    pub async fn get_user_by_id(&self, id: i64) -> Result<Option<User>, UserServiceError> {
+        Ok(self.unit_of_work.repositories().user.find_by_id(id).await?)
+    }
+
+    pub async fn delete_user(&self, id: i64) -> Result<(), UserServiceError> {
+        let transaction = self.unit_of_work.begin().await?;
+        let result = transaction
+            .repositories()
+            .user
+            .delete(id)
+            .await
+            .map_err(|e| match e {
+                RepositoryError::NotFound => UserServiceError::UserNotFound,
+                e => e.into(),
+            });
+        transaction.complete(result).await
+    }
+}
+
+/// Makes sure the user's job and organization exist within the same
+/// transaction that writes the user.
+async fn check_references(
+    repositories: &Repositories,
+    user: &User,
+) -> Result<(), UserServiceError> {
+    repositories
+        .job
+        .find_by_id(user.job_id())
+        .await?
+        .ok_or(UserServiceError::JobNotFound)?;
+
+    repositories
+        .organization
+        .find_by_id(user.organization_id())
+        .await?
+        .ok_or(UserServiceError::OrganizationNotFound)?;
+
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UserServiceError {
+    #[error("User validation failed")]
+    ValidationError,
+
+    #[error("Job not found")]
+    JobNotFound,
+
+    #[error("Organization not found")]
+    OrganizationNotFound,
+
+    #[error("User not found")]
+    UserNotFound,
+
+    #[error("Database error: {0}")]
+    RepositoryError(#[from] RepositoryError),
+}
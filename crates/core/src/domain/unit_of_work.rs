@@ -0,0 +1,59 @@
+use super::repositories::{
+    AuditRepository, JobRepository, OrganizationRepository, RepositoryError, UserRepository,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// One set of repositories that share a connection or transaction.
+#[derive(Clone)]
+pub struct Repositories {
+    pub user: Arc<dyn UserRepository>,
+    pub job: Arc<dyn JobRepository>,
+    pub organization: Arc<dyn OrganizationRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+}
+
+/// Hands out repositories and groups their writes into transactions.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    /// Repositories whose statements each take effect immediately.
+    fn repositories(&self) -> Repositories;
+
+    /// Starts a transaction. Beginning again on an open transaction joins it
+    /// instead of nesting, so only the outermost commit takes effect.
+    async fn begin(&self) -> Result<Arc<dyn Transaction>, RepositoryError>;
+}
+
+/// A unit of work whose repositories see and write uncommitted changes.
+///
+/// Dropping a transaction without committing it rolls it back, but only once
+/// its connection is next used, so error paths roll back explicitly.
+#[async_trait]
+pub trait Transaction: UnitOfWork {
+    async fn commit(&self) -> Result<(), RepositoryError>;
+
+    /// Discards the transaction's writes and releases its locks. Does nothing
+    /// on a handle that joined an outer transaction.
+    async fn rollback(&self) -> Result<(), RepositoryError>;
+}
+
+impl dyn Transaction {
+    /// Commits if `result` is a success and rolls back otherwise, returning
+    /// `result` or the commit error.
+    pub async fn complete<T, E>(&self, result: Result<T, E>) -> Result<T, E>
+    where
+        E: From<RepositoryError>,
+    {
+        match result {
+            Ok(value) => {
+                self.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                // The error that caused the rollback is the one to report.
+                let _ = self.rollback().await;
+                Err(e)
+            }
+        }
+    }
+}
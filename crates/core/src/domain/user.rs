@@ -1,13 +1,20 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::Entity;
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct User {
     id: i64,
     name: String,
+    version: i64,
     job_id: i64,
     organization_id: i64,
+    #[serde(default)]
+    email: String,
+    #[serde(default)]
+    phone: String,
+    #[serde(skip)]
     errors: HashMap<&'static str, &'static str>,
 }
 
@@ -31,6 +38,65 @@ impl User {
     pub fn organization_id(&self) -> i64 {
         self.organization_id
     }
+
+    pub fn set_email(&mut self, email: String) {
+        self.email = email;
+    }
+
+    pub fn set_phone(&mut self, phone: String) {
+        self.phone = phone;
+    }
+
+    /// Empty when the user has no email address.
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    /// Empty when the user has no phone number.
+    pub fn phone(&self) -> &str {
+        &self.phone
+    }
+
+    fn email_error(&self) -> Option<&'static str> {
+        let email = self.email.trim();
+        if email.is_empty() {
+            return None;
+        }
+        let valid = match email.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && !domain.contains('@')
+                    && !email.contains(char::is_whitespace)
+            }
+            None => false,
+        };
+        if !valid {
+            Some("Email must look like name@example.com")
+        } else if email.len() > 254 {
+            Some("Email must be under 254 characters")
+        } else {
+            None
+        }
+    }
+
+    fn phone_error(&self) -> Option<&'static str> {
+        let phone = self.phone.trim();
+        if phone.is_empty() {
+            None
+        } else if !phone
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '+' | '-' | '(' | ')' | '.'))
+        {
+            Some("Phone may only contain digits, spaces and + - ( ) .")
+        } else if phone.len() > 32 {
+            Some("Phone must be under 32 characters")
+        } else {
+            None
+        }
+    }
 }
 
 impl Entity for User {
@@ -50,6 +116,14 @@ impl Entity for User {
         self.name = name;
     }
 
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
     fn errors(&self) -> &HashMap<&'static str, &'static str> {
         &self.errors
     }
@@ -75,6 +149,14 @@ impl Entity for User {
                 .insert("organization_id", "Organization selection is required");
         }
 
+        if let Some(error) = self.email_error() {
+            self.errors.insert("email", error);
+        }
+
+        if let Some(error) = self.phone_error() {
+            self.errors.insert("phone", error);
+        }
+
         if self.errors.is_empty() {
             Ok(())
         } else {
@@ -97,16 +179,30 @@ impl Entity for User {
                 }
             }
             "job_id" => {
+                self.errors.remove("job_id");
                 if self.job_id == 0 {
                     self.errors.insert("job_id", "Job selection is required");
                 }
             }
             "organization_id" => {
+                self.errors.remove("organization_id");
                 if self.organization_id == 0 {
                     self.errors
                         .insert("organization_id", "Organization selection is required");
                 }
             }
+            "email" => {
+                self.errors.remove("email");
+                if let Some(error) = self.email_error() {
+                    self.errors.insert("email", error);
+                }
+            }
+            "phone" => {
+                self.errors.remove("phone");
+                if let Some(error) = self.phone_error() {
+                    self.errors.insert("phone", error);
+                }
+            }
             _ => {}
         }
     }
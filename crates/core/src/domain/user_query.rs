@@ -0,0 +1,97 @@
+use super::User;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The number of users a page holds unless the query asks otherwise.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UserSortKey {
+    Id,
+    #[default]
+    Name,
+    Job,
+    Organization,
+}
+
+impl Display for UserSortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            UserSortKey::Id => "ID",
+            UserSortKey::Name => "Name",
+            UserSortKey::Job => "Job",
+            UserSortKey::Organization => "Organization",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        }
+    }
+}
+
+/// The position of the last user on a page. The next page starts right
+/// after it, which stays correct while rows are added or removed elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserCursor {
+    /// The sort key of the last user; empty when sorting by id.
+    pub sort_value: String,
+    pub id: i64,
+}
+
+/// Which users to fetch and in what order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserQuery {
+    pub organization_id: Option<i64>,
+    pub job_id: Option<i64>,
+    pub name_contains: Option<String>,
+    pub sort: UserSortKey,
+    pub direction: SortDirection,
+    pub after: Option<UserCursor>,
+    pub limit: i64,
+}
+
+impl Default for UserQuery {
+    fn default() -> Self {
+        Self {
+            organization_id: None,
+            job_id: None,
+            name_contains: None,
+            sort: UserSortKey::default(),
+            direction: SortDirection::default(),
+            after: None,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl UserQuery {
+    /// The name filter with surrounding whitespace removed, if there is one.
+    pub fn name_filter(&self) -> Option<&str> {
+        self.name_contains
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// One page of users and the number of users matching the query overall.
+#[derive(Debug, Clone, Default)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    pub total: i64,
+    /// Where the following page starts, or `None` on the last page.
+    pub next: Option<UserCursor>,
+}
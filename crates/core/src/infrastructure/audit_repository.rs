@@ -0,0 +1,109 @@
+use super::map_sqlx_error;
+use super::unit_of_work::SqliteExecutor;
+use crate::domain::{
+    repositories::{AuditRepository, RepositoryError},
+    AuditAction, AuditEntry, AuditFilter, DomainEntity,
+};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::{QueryBuilder, Row, Sqlite};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct AuditSqliteRepository {
+    executor: SqliteExecutor,
+}
+
+impl AuditSqliteRepository {
+    pub fn new(executor: SqliteExecutor) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for AuditSqliteRepository {
+    async fn record(&self, entry: &AuditEntry) -> Result<AuditEntry, RepositoryError> {
+        let entity_type = entry.entity_type.as_str();
+        let action = entry.action.as_str();
+        let timestamp = entry.timestamp.format(TIMESTAMP_FORMAT).to_string();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO audit_log (entity_type, entity_id, action, timestamp, operator, before, after)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            entity_type,
+            entry.entity_id,
+            action,
+            timestamp,
+            entry.operator,
+            entry.before,
+            entry.after,
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        let mut saved_entry = entry.clone();
+        saved_entry.id = result.last_insert_rowid();
+        Ok(saved_entry)
+    }
+
+    async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT id, entity_type, entity_id, action, timestamp, operator, before, after \
+             FROM audit_log WHERE 1 = 1",
+        );
+
+        if let Some(entity_type) = filter.entity_type {
+            query
+                .push(" AND entity_type = ")
+                .push_bind(entity_type.as_str());
+        }
+        if let Some(entity_id) = filter.entity_id {
+            query.push(" AND entity_id = ").push_bind(entity_id);
+        }
+        if let Some(action) = filter.action {
+            query.push(" AND action = ").push_bind(action.as_str());
+        }
+        if let Some(from) = filter.from {
+            query
+                .push(" AND timestamp >= ")
+                .push_bind(from.format("%Y-%m-%d 00:00:00").to_string());
+        }
+        if let Some(to) = filter.to {
+            query
+                .push(" AND timestamp <= ")
+                .push_bind(to.format("%Y-%m-%d 23:59:59").to_string());
+        }
+        query.push(" ORDER BY timestamp DESC, id DESC");
+
+        let rows = query
+            .build()
+            .fetch_all(&mut *self.executor.acquire().await?)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(|r| {
+                let action: String = r.get("action");
+                let timestamp: String = r.get("timestamp");
+                let timestamp = NaiveDateTime::parse_from_str(&timestamp, TIMESTAMP_FORMAT)
+                    .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+                Ok(AuditEntry {
+                    id: r.get("id"),
+                    entity_type: DomainEntity::parse(r.get("entity_type")),
+                    entity_id: r.get("entity_id"),
+                    action: AuditAction::parse(&action).ok_or_else(|| {
+                        RepositoryError::DatabaseError(format!("Unknown audit action: {}", action))
+                    })?,
+                    timestamp: Utc.from_utc_datetime(&timestamp),
+                    operator: r.get("operator"),
+                    before: r.get("before"),
+                    after: r.get("after"),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,369 @@
+use crate::domain::{
+    repositories::{
+        AuditRepository, JobRepository, OrganizationRepository, RepositoryError, UserRepository,
+    },
+    AuditAction, AuditEntry, DomainEntity, Entity, Job, Organization, Repositories, Services,
+    Transaction, UnitOfWork, User, UserPage, UserQuery,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Writes audit entries on behalf of the audited repository wrappers below.
+#[derive(Clone)]
+pub struct AuditRecorder {
+    audit_repo: Arc<dyn AuditRepository>,
+    operator: String,
+}
+
+impl AuditRecorder {
+    pub fn new(audit_repo: Arc<dyn AuditRepository>, operator: String) -> Self {
+        Self {
+            audit_repo,
+            operator,
+        }
+    }
+
+    async fn record<T: Entity>(
+        &self,
+        entity_type: DomainEntity,
+        entity_id: i64,
+        action: AuditAction,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Result<(), RepositoryError> {
+        let entry = AuditEntry::new(
+            entity_type,
+            entity_id,
+            action,
+            &self.operator,
+            before.map(Entity::snapshot),
+            after.map(Entity::snapshot),
+        );
+        self.audit_repo.record(&entry).await?;
+        Ok(())
+    }
+}
+
+pub struct AuditedUserRepository {
+    inner: Arc<dyn UserRepository>,
+    recorder: AuditRecorder,
+}
+
+impl AuditedUserRepository {
+    pub fn new(inner: Arc<dyn UserRepository>, recorder: AuditRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl UserRepository for AuditedUserRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<User>, RepositoryError> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>, RepositoryError> {
+        self.inner.find_all().await
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<User>, RepositoryError> {
+        self.inner.search(query).await
+    }
+
+    async fn find_page(&self, query: &UserQuery) -> Result<UserPage, RepositoryError> {
+        self.inner.find_page(query).await
+    }
+
+    async fn create(&self, user: &User) -> Result<User, RepositoryError> {
+        let saved_user = self.inner.create(user).await?;
+        self.recorder
+            .record(
+                DomainEntity::User,
+                saved_user.id(),
+                AuditAction::Create,
+                None,
+                Some(&saved_user),
+            )
+            .await?;
+        Ok(saved_user)
+    }
+
+    async fn restore(&self, user: &User) -> Result<(), RepositoryError> {
+        self.inner.restore(user).await?;
+        self.recorder
+            .record(
+                DomainEntity::User,
+                user.id(),
+                AuditAction::Create,
+                None,
+                Some(user),
+            )
+            .await
+    }
+
+    async fn update(&self, user: &User) -> Result<(), RepositoryError> {
+        let before = self.inner.find_by_id(user.id()).await?;
+        self.inner.update(user).await?;
+        let mut after = user.clone();
+        after.set_version(user.version() + 1);
+        self.recorder
+            .record(
+                DomainEntity::User,
+                user.id(),
+                AuditAction::Update,
+                before.as_ref(),
+                Some(&after),
+            )
+            .await
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let before = self.inner.find_by_id(id).await?;
+        self.inner.delete(id).await?;
+        self.recorder
+            .record::<User>(
+                DomainEntity::User,
+                id,
+                AuditAction::Delete,
+                before.as_ref(),
+                None,
+            )
+            .await
+    }
+}
+
+pub struct AuditedJobRepository {
+    inner: Arc<dyn JobRepository>,
+    recorder: AuditRecorder,
+}
+
+impl AuditedJobRepository {
+    pub fn new(inner: Arc<dyn JobRepository>, recorder: AuditRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl JobRepository for AuditedJobRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Job>, RepositoryError> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Job>, RepositoryError> {
+        self.inner.find_all().await
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Job>, RepositoryError> {
+        self.inner.search(query).await
+    }
+
+    async fn create(&self, job: &Job) -> Result<Job, RepositoryError> {
+        let saved_job = self.inner.create(job).await?;
+        self.recorder
+            .record(
+                DomainEntity::Job,
+                saved_job.id(),
+                AuditAction::Create,
+                None,
+                Some(&saved_job),
+            )
+            .await?;
+        Ok(saved_job)
+    }
+
+    async fn restore(&self, job: &Job) -> Result<(), RepositoryError> {
+        self.inner.restore(job).await?;
+        self.recorder
+            .record(
+                DomainEntity::Job,
+                job.id(),
+                AuditAction::Create,
+                None,
+                Some(job),
+            )
+            .await
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), RepositoryError> {
+        let before = self.inner.find_by_id(job.id()).await?;
+        self.inner.update(job).await?;
+        let mut after = job.clone();
+        after.set_version(job.version() + 1);
+        self.recorder
+            .record(
+                DomainEntity::Job,
+                job.id(),
+                AuditAction::Update,
+                before.as_ref(),
+                Some(&after),
+            )
+            .await
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let before = self.inner.find_by_id(id).await?;
+        self.inner.delete(id).await?;
+        self.recorder
+            .record::<Job>(
+                DomainEntity::Job,
+                id,
+                AuditAction::Delete,
+                before.as_ref(),
+                None,
+            )
+            .await
+    }
+}
+
+pub struct AuditedOrganizationRepository {
+    inner: Arc<dyn OrganizationRepository>,
+    recorder: AuditRecorder,
+}
+
+impl AuditedOrganizationRepository {
+    pub fn new(inner: Arc<dyn OrganizationRepository>, recorder: AuditRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl OrganizationRepository for AuditedOrganizationRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Organization>, RepositoryError> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Organization>, RepositoryError> {
+        self.inner.find_all().await
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Organization>, RepositoryError> {
+        self.inner.search(query).await
+    }
+
+    async fn create(&self, org: &Organization) -> Result<Organization, RepositoryError> {
+        let saved_org = self.inner.create(org).await?;
+        self.recorder
+            .record(
+                DomainEntity::Organization,
+                saved_org.id(),
+                AuditAction::Create,
+                None,
+                Some(&saved_org),
+            )
+            .await?;
+        Ok(saved_org)
+    }
+
+    async fn restore(&self, org: &Organization) -> Result<(), RepositoryError> {
+        self.inner.restore(org).await?;
+        self.recorder
+            .record(
+                DomainEntity::Organization,
+                org.id(),
+                AuditAction::Create,
+                None,
+                Some(org),
+            )
+            .await
+    }
+
+    async fn update(&self, org: &Organization) -> Result<(), RepositoryError> {
+        let before = self.inner.find_by_id(org.id()).await?;
+        self.inner.update(org).await?;
+        let mut after = org.clone();
+        after.set_version(org.version() + 1);
+        self.recorder
+            .record(
+                DomainEntity::Organization,
+                org.id(),
+                AuditAction::Update,
+                before.as_ref(),
+                Some(&after),
+            )
+            .await
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let before = self.inner.find_by_id(id).await?;
+        self.inner.delete(id).await?;
+        self.recorder
+            .record::<Organization>(
+                DomainEntity::Organization,
+                id,
+                AuditAction::Delete,
+                before.as_ref(),
+                None,
+            )
+            .await
+    }
+}
+
+/// A unit of work whose repositories audit every write, recording the
+/// entries in the same transaction as the change itself.
+pub struct AuditedUnitOfWork<U: ?Sized> {
+    inner: Arc<U>,
+    operator: String,
+}
+
+impl<U: ?Sized> AuditedUnitOfWork<U> {
+    pub fn new(inner: Arc<U>, operator: String) -> Self {
+        Self { inner, operator }
+    }
+}
+
+#[async_trait]
+impl<U: UnitOfWork + ?Sized> UnitOfWork for AuditedUnitOfWork<U> {
+    fn repositories(&self) -> Repositories {
+        let repositories = self.inner.repositories();
+        let recorder = AuditRecorder::new(repositories.audit.clone(), self.operator.clone());
+
+        Repositories {
+            user: Arc::new(AuditedUserRepository::new(
+                repositories.user,
+                recorder.clone(),
+            )),
+            job: Arc::new(AuditedJobRepository::new(
+                repositories.job,
+                recorder.clone(),
+            )),
+            organization: Arc::new(AuditedOrganizationRepository::new(
+                repositories.organization,
+                recorder,
+            )),
+            audit: repositories.audit,
+        }
+    }
+
+    async fn begin(&self) -> Result<Arc<dyn Transaction>, RepositoryError> {
+        let transaction = self.inner.begin().await?;
+        Ok(Arc::new(AuditedUnitOfWork::new(
+            transaction,
+            self.operator.clone(),
+        )))
+    }
+}
+
+#[async_trait]
+impl Transaction for AuditedUnitOfWork<dyn Transaction> {
+    async fn commit(&self) -> Result<(), RepositoryError> {
+        self.inner.commit().await
+    }
+
+    async fn rollback(&self) -> Result<(), RepositoryError> {
+        self.inner.rollback().await
+    }
+}
+
+/// Builds the domain services on top of the given unit of work, auditing
+/// every write as the current operator.
+pub fn audited_services(unit_of_work: Arc<dyn UnitOfWork>) -> Services {
+    Services::new(Arc::new(AuditedUnitOfWork::new(
+        unit_of_work,
+        current_operator(),
+    )))
+}
+
+pub fn current_operator() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
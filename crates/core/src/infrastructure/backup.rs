@@ -0,0 +1,196 @@
+use sqlx::{
+    migrate::MigrateError, sqlite::SqliteConnectOptions, Connection, Executor, Row,
+    SqliteConnection, SqlitePool,
+};
+use std::path::{Path, PathBuf};
+
+use super::database::MIGRATOR;
+use super::Database;
+
+/// Tables copied during a restore, in foreign key order.
+const RESTORED_TABLES: [&str; 4] = ["organizations", "jobs", "users", "audit_log"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("File error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Migration error: {0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("Incompatible backup: {0}")]
+    IncompatibleSchema(String),
+
+    #[error("Backup is corrupt: {0}")]
+    Integrity(String),
+}
+
+impl Database {
+    /// Writes a consistent copy of the database with `VACUUM INTO`.
+    ///
+    /// The copy is written beside `path` and then renamed over it, so a
+    /// failed backup leaves an earlier one there untouched.
+    pub async fn backup_to(&self, path: &Path) -> Result<(), BackupError> {
+        let partial = partial_path(path);
+        // Left over from an interrupted backup; VACUUM INTO refuses to
+        // overwrite it.
+        let _ = std::fs::remove_file(&partial);
+
+        let result = sqlx::query("VACUUM INTO ?")
+            .bind(partial.to_string_lossy().to_string())
+            .execute(self.pool())
+            .await
+            .map_err(BackupError::from)
+            .and_then(|_| Ok(std::fs::rename(&partial, path)?));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        result
+    }
+
+    /// Replaces all data with the contents of a backup file.
+    ///
+    /// The backup is copied aside, checked against the known migrations and
+    /// upgraded if it is older, then copied into the open database in a
+    /// single transaction.
+    pub async fn restore_from(&self, path: &Path) -> Result<(), BackupError> {
+        let staging = staging_path(path);
+        std::fs::copy(path, &staging)?;
+
+        let result = self.restore_from_staging(&staging).await;
+        let _ = std::fs::remove_file(&staging);
+        result
+    }
+
+    async fn restore_from_staging(&self, staging: &Path) -> Result<(), BackupError> {
+        validate_backup(staging).await?;
+
+        let mut conn = self.pool().acquire().await?;
+        sqlx::query("ATTACH DATABASE ? AS backup")
+            .bind(staging.to_string_lossy().to_string())
+            .execute(&mut *conn)
+            .await?;
+
+        // Backups taken before foreign keys were enforced may hold orphaned
+        // users. Restore them as they are and leave them to the integrity check.
+        let result = match conn.execute("PRAGMA foreign_keys = OFF").await {
+            Ok(_) => copy_tables(&mut conn).await,
+            Err(e) => Err(e.into()),
+        };
+
+        // The pragma and the attachment stay with the connection when it goes
+        // back to the pool, so undo both whatever happened above.
+        let reset = async {
+            conn.execute("PRAGMA foreign_keys = ON").await?;
+            conn.execute("DETACH DATABASE backup").await?;
+            Ok::<_, sqlx::Error>(())
+        }
+        .await;
+        if reset.is_err() {
+            // Keep a connection that could not be reset out of the pool.
+            let _ = conn.close().await;
+        }
+        result?;
+        Ok(reset?)
+    }
+}
+
+async fn validate_backup(path: &Path) -> Result<(), BackupError> {
+    let options = SqliteConnectOptions::new().filename(path);
+    let pool = SqlitePool::connect_with(options).await?;
+
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&pool)
+        .await?;
+    if integrity != "ok" {
+        return Err(BackupError::Integrity(integrity));
+    }
+
+    let applied = sqlx::query("SELECT version, checksum FROM _sqlx_migrations WHERE success = 1")
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| {
+            BackupError::IncompatibleSchema(
+                "the file was not created by this application".to_string(),
+            )
+        })?;
+
+    for row in applied {
+        let version: i64 = row.get("version");
+        let checksum: Vec<u8> = row.get("checksum");
+        match MIGRATOR.iter().find(|m| m.version == version) {
+            Some(migration) if *migration.checksum == checksum[..] => {}
+            Some(_) => {
+                return Err(BackupError::IncompatibleSchema(format!(
+                    "migration {} does not match this version of the application",
+                    version
+                )))
+            }
+            None => {
+                return Err(BackupError::IncompatibleSchema(format!(
+                    "migration {} is from a newer version of the application",
+                    version
+                )))
+            }
+        }
+    }
+
+    MIGRATOR.run(&pool).await?;
+    pool.close().await;
+    Ok(())
+}
+
+async fn copy_tables(conn: &mut SqliteConnection) -> Result<(), BackupError> {
+    let mut tx = conn.begin().await?;
+
+    for table in RESTORED_TABLES.iter().rev() {
+        sqlx::query(&format!("DELETE FROM main.{}", table))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    for table in RESTORED_TABLES {
+        let columns: Vec<String> = sqlx::query(&format!("PRAGMA main.table_info({})", table))
+            .fetch_all(&mut *tx)
+            .await?
+            .iter()
+            .map(|row| row.get::<String, _>("name"))
+            .collect();
+        let columns = columns.join(", ");
+
+        sqlx::query(&format!(
+            "INSERT INTO main.{table} ({columns}) SELECT {columns} FROM backup.{table}"
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// In the same directory as `path`, so renaming it over `path` cannot fail
+/// for crossing file systems.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_else(|| "backup.db".into());
+    name.push(".partial");
+    path.with_file_name(name)
+}
+
+fn staging_path(path: &Path) -> PathBuf {
+    let mut staging = std::env::temp_dir();
+    staging.push(format!(
+        "iced-user-management-restore-{}-{}",
+        std::process::id(),
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "backup.db".to_string())
+    ));
+    staging
+}
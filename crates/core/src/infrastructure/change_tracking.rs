@@ -0,0 +1,63 @@
+use sqlx::Row;
+
+use crate::domain::DomainEntity;
+
+use super::Database;
+
+/// How often each table has been written to, by this app or anyone else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounters {
+    pub user: i64,
+    pub job: i64,
+    pub organization: i64,
+}
+
+impl ChangeCounters {
+    /// The entities whose tables were written to since `earlier`.
+    pub fn changed_since(&self, earlier: &ChangeCounters) -> Vec<DomainEntity> {
+        DomainEntity::ALL
+            .into_iter()
+            .filter(|entity| self.revision(*entity) != earlier.revision(*entity))
+            .collect()
+    }
+
+    fn revision(&self, entity: DomainEntity) -> i64 {
+        match entity {
+            DomainEntity::User => self.user,
+            DomainEntity::Job => self.job,
+            DomainEntity::Organization => self.organization,
+            DomainEntity::None => 0,
+        }
+    }
+}
+
+impl Database {
+    /// The current counters, or `None` when the database has no
+    /// `change_counters` table. Read-only databases are not migrated, so
+    /// ones created before change tracking never get it.
+    pub async fn change_counters(&self) -> Result<Option<ChangeCounters>, sqlx::Error> {
+        let tracked = sqlx::query(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'change_counters'",
+        )
+        .fetch_optional(self.pool())
+        .await?
+        .is_some();
+        if !tracked {
+            return Ok(None);
+        }
+        let mut counters = ChangeCounters::default();
+        for row in sqlx::query("SELECT entity_type, revision FROM change_counters")
+            .fetch_all(self.pool())
+            .await?
+        {
+            let revision: i64 = row.try_get("revision")?;
+            match row.try_get::<String, _>("entity_type")?.as_str() {
+                "user" => counters.user = revision,
+                "job" => counters.job = revision,
+                "organization" => counters.organization = revision,
+                _ => {}
+            }
+        }
+        Ok(Some(counters))
+    }
+}
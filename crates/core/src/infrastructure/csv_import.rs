@@ -0,0 +1,490 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::domain::{CommandError, Entity, Job, Organization, Services, User};
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Could not read {0}: {1}")]
+    Read(PathBuf, csv::Error),
+    #[error("{0} has no header row")]
+    NoHeader(PathBuf),
+    #[error("Could not write {0}: {1}")]
+    Write(PathBuf, csv::Error),
+}
+
+/// A CSV file read into memory, header row first.
+#[derive(Debug, Clone)]
+pub struct CsvFile {
+    pub path: PathBuf,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvFile {
+    /// Reads a whole file. Rows may have fewer or more fields than the
+    /// header; missing fields read as empty.
+    pub fn read(path: &Path) -> Result<Self, ImportError> {
+        let error = |e| ImportError::Read(path.to_path_buf(), e);
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(error)?;
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(error)?
+            .iter()
+            .map(str::to_string)
+            .collect();
+        if headers.iter().all(String::is_empty) {
+            return Err(ImportError::NoHeader(path.to_path_buf()));
+        }
+        let rows = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+            .collect::<Result<Vec<Vec<String>>, csv::Error>>()
+            .map_err(error)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            headers,
+            rows,
+        })
+    }
+
+    pub fn columns(&self) -> Vec<CsvColumn> {
+        self.headers
+            .iter()
+            .enumerate()
+            .map(|(index, name)| CsvColumn {
+                index,
+                name: name.clone(),
+            })
+            .collect()
+    }
+}
+
+/// A column of the CSV file, as offered in the mapping pick lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvColumn {
+    pub index: usize,
+    pub name: String,
+}
+
+impl std::fmt::Display for CsvColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "Column {}", self.index + 1)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// The user fields a CSV column can be mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportField {
+    Name,
+    Job,
+    Organization,
+    Email,
+    Phone,
+}
+
+impl ImportField {
+    pub const ALL: [ImportField; 5] = [
+        ImportField::Name,
+        ImportField::Job,
+        ImportField::Organization,
+        ImportField::Email,
+        ImportField::Phone,
+    ];
+
+    /// Header names that are mapped to the field without asking.
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            ImportField::Name => &["name", "user", "full name", "fullname", "display name"],
+            ImportField::Job => &["job", "title", "job title", "position", "role"],
+            ImportField::Organization => &[
+                "organization",
+                "organisation",
+                "org",
+                "company",
+                "department",
+            ],
+            ImportField::Email => &["email", "e-mail", "mail", "email address"],
+            ImportField::Phone => &["phone", "telephone", "phone number", "mobile"],
+        }
+    }
+}
+
+impl std::fmt::Display for ImportField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            ImportField::Name => "Name",
+            ImportField::Job => "Job",
+            ImportField::Organization => "Organization",
+            ImportField::Email => "Email",
+            ImportField::Phone => "Phone",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Which CSV column feeds each user field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub name: Option<usize>,
+    pub job: Option<usize>,
+    pub organization: Option<usize>,
+    pub email: Option<usize>,
+    pub phone: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Maps every field whose name, or a common alias of it, appears as a
+    /// header.
+    pub fn guess(headers: &[String]) -> Self {
+        let mut mapping = Self::default();
+        for field in ImportField::ALL {
+            let column = headers.iter().position(|header| {
+                field
+                    .aliases()
+                    .contains(&header.trim().to_lowercase().as_str())
+            });
+            mapping.set(field, column);
+        }
+        mapping
+    }
+
+    pub fn get(&self, field: ImportField) -> Option<usize> {
+        match field {
+            ImportField::Name => self.name,
+            ImportField::Job => self.job,
+            ImportField::Organization => self.organization,
+            ImportField::Email => self.email,
+            ImportField::Phone => self.phone,
+        }
+    }
+
+    pub fn set(&mut self, field: ImportField, column: Option<usize>) {
+        match field {
+            ImportField::Name => self.name = column,
+            ImportField::Job => self.job = column,
+            ImportField::Organization => self.organization = column,
+            ImportField::Email => self.email = column,
+            ImportField::Phone => self.phone = column,
+        }
+    }
+}
+
+/// A job or organization as named in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    Existing(i64),
+    /// Not in the database yet; created by the import.
+    New(String),
+}
+
+/// One data row of the file and what importing it would do.
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    /// The line in the file, counting the header as line 1.
+    pub line: usize,
+    pub values: Vec<String>,
+    pub name: String,
+    pub job_name: String,
+    pub organization_name: String,
+    pub email: String,
+    pub phone: String,
+    pub job: Option<Reference>,
+    pub organization: Option<Reference>,
+    pub errors: Vec<String>,
+}
+
+impl ImportRow {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Every row of the file, checked against the database but not written.
+#[derive(Debug, Clone, Default)]
+pub struct ImportPreview {
+    pub rows: Vec<ImportRow>,
+    pub new_jobs: BTreeSet<String>,
+    pub new_organizations: BTreeSet<String>,
+}
+
+impl ImportPreview {
+    /// Resolves job and organization names, case-insensitively, and runs
+    /// every row through `User::validate`. With `create_missing`, unknown
+    /// names become new records instead of errors.
+    pub fn new(
+        file: &CsvFile,
+        mapping: ColumnMapping,
+        jobs: &[Job],
+        organizations: &[Organization],
+        create_missing: bool,
+    ) -> Self {
+        let job_ids = ids_by_name(jobs);
+        let organization_ids = ids_by_name(organizations);
+        // The first spelling of each new name, so "acme" and "ACME" on
+        // different rows create one organization.
+        let mut new_job_names = HashMap::new();
+        let mut new_organization_names = HashMap::new();
+        let mut preview = Self::default();
+
+        for (index, values) in file.rows.iter().enumerate() {
+            let field = |column: Option<usize>| {
+                column
+                    .and_then(|column| values.get(column))
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let mut row = ImportRow {
+                line: index + 2,
+                values: values.clone(),
+                name: field(mapping.name),
+                job_name: field(mapping.job),
+                organization_name: field(mapping.organization),
+                email: field(mapping.email),
+                phone: field(mapping.phone),
+                job: None,
+                organization: None,
+                errors: Vec::new(),
+            };
+
+            row.job = resolve::<Job>(
+                "Job",
+                &row.job_name,
+                &job_ids,
+                create_missing,
+                &mut row.errors,
+            )
+            .map(|reference| canonical(reference, &mut new_job_names));
+            row.organization = resolve::<Organization>(
+                "Organization",
+                &row.organization_name,
+                &organization_ids,
+                create_missing,
+                &mut row.errors,
+            )
+            .map(|reference| canonical(reference, &mut new_organization_names));
+
+            // New references have no id yet; any non-zero id stands in for
+            // them so validation only reports what is really missing.
+            let mut user = User::new();
+            user.set_name(row.name.clone());
+            user.set_job_id(reference_id(&row.job));
+            user.set_organization_id(reference_id(&row.organization));
+            user.set_email(row.email.clone());
+            user.set_phone(row.phone.clone());
+            if let Err(errors) = user.validate() {
+                // A name that was given but not found is already reported.
+                let mut messages: Vec<&str> = errors
+                    .iter()
+                    .filter(|(field, _)| match **field {
+                        "job_id" => row.job_name.is_empty(),
+                        "organization_id" => row.organization_name.is_empty(),
+                        _ => true,
+                    })
+                    .map(|(_, message)| *message)
+                    .collect();
+                messages.sort();
+                row.errors.extend(messages.into_iter().map(str::to_string));
+            }
+
+            if row.is_valid() {
+                if let Some(Reference::New(name)) = &row.job {
+                    preview.new_jobs.insert(name.clone());
+                }
+                if let Some(Reference::New(name)) = &row.organization {
+                    preview.new_organizations.insert(name.clone());
+                }
+            }
+            preview.rows.push(row);
+        }
+        preview
+    }
+
+    pub fn valid_count(&self) -> usize {
+        self.rows.iter().filter(|row| row.is_valid()).count()
+    }
+
+    pub fn rejected(&self) -> impl Iterator<Item = &ImportRow> {
+        self.rows.iter().filter(|row| !row.is_valid())
+    }
+}
+
+/// What an import added.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub users: usize,
+    pub jobs: usize,
+    pub organizations: usize,
+    pub rejected: usize,
+}
+
+impl std::fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Imported {} users, created {} jobs and {} organizations, rejected {} rows",
+            self.users, self.jobs, self.organizations, self.rejected
+        )
+    }
+}
+
+/// Creates the new jobs and organizations and every valid user in one
+/// transaction, so a failure part way leaves the database untouched.
+pub async fn import_users(
+    services: &Services,
+    preview: &ImportPreview,
+) -> Result<ImportSummary, CommandError> {
+    let (services, transaction) = services.begin().await?;
+    let result = import_rows(&services, preview).await;
+    transaction.complete(result).await
+}
+
+async fn import_rows(
+    services: &Services,
+    preview: &ImportPreview,
+) -> Result<ImportSummary, CommandError> {
+    let mut summary = ImportSummary {
+        rejected: preview.rejected().count(),
+        ..ImportSummary::default()
+    };
+
+    let mut job_ids = HashMap::new();
+    for name in &preview.new_jobs {
+        let mut job = Job::new();
+        job.set_name(name.clone());
+        let job = services.job.create_job(job).await?;
+        job_ids.insert(name.clone(), job.id());
+        summary.jobs += 1;
+    }
+    let mut organization_ids = HashMap::new();
+    for name in &preview.new_organizations {
+        let mut organization = Organization::new();
+        organization.set_name(name.clone());
+        let organization = services
+            .organization
+            .create_organization(organization)
+            .await?;
+        organization_ids.insert(name.clone(), organization.id());
+        summary.organizations += 1;
+    }
+
+    for row in preview.rows.iter().filter(|row| row.is_valid()) {
+        let id = |reference: &Option<Reference>, created: &HashMap<String, i64>| match reference {
+            Some(Reference::Existing(id)) => *id,
+            Some(Reference::New(name)) => created.get(name).copied().unwrap_or_default(),
+            None => 0,
+        };
+        let mut user = User::new();
+        user.set_name(row.name.clone());
+        user.set_job_id(id(&row.job, &job_ids));
+        user.set_organization_id(id(&row.organization, &organization_ids));
+        user.set_email(row.email.clone());
+        user.set_phone(row.phone.clone());
+        services.user.create_user(user).await?;
+        summary.users += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Writes the rejected rows as they appeared in the file, with an extra
+/// column explaining why each was rejected.
+pub fn write_rejected(
+    path: &Path,
+    file: &CsvFile,
+    preview: &ImportPreview,
+) -> Result<usize, ImportError> {
+    let error = |e| ImportError::Write(path.to_path_buf(), e);
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(error)?;
+
+    let mut header = file.headers.clone();
+    header.push("line".to_string());
+    header.push("errors".to_string());
+    writer.write_record(&header).map_err(error)?;
+
+    let mut count = 0;
+    for row in preview.rejected() {
+        let mut record = row.values.clone();
+        record.resize(file.headers.len(), String::new());
+        record.push(row.line.to_string());
+        record.push(row.errors.join("; "));
+        writer.write_record(&record).map_err(error)?;
+        count += 1;
+    }
+    writer.flush().map_err(|e| error(csv::Error::from(e)))?;
+    Ok(count)
+}
+
+pub(super) fn ids_by_name<T: Entity>(records: &[T]) -> HashMap<String, i64> {
+    records
+        .iter()
+        .map(|record| (record.name().trim().to_lowercase(), record.id()))
+        .collect()
+}
+
+pub(super) fn resolve<T: Entity>(
+    label: &str,
+    name: &str,
+    ids: &HashMap<String, i64>,
+    create_missing: bool,
+    errors: &mut Vec<String>,
+) -> Option<Reference> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    if let Some(id) = ids.get(&name.to_lowercase()) {
+        return Some(Reference::Existing(*id));
+    }
+    if !create_missing {
+        errors.push(format!("Unknown {} '{}'", label.to_lowercase(), name));
+        return None;
+    }
+
+    let mut record = T::default();
+    record.set_name(name.to_string());
+    match record.validate() {
+        Ok(()) => Some(Reference::New(name.to_string())),
+        Err(record_errors) => {
+            errors.extend(
+                record_errors
+                    .values()
+                    .map(|message| format!("{} name: {}", label, message)),
+            );
+            None
+        }
+    }
+}
+
+pub(super) fn canonical(
+    reference: Reference,
+    spellings: &mut HashMap<String, String>,
+) -> Reference {
+    match reference {
+        Reference::New(name) => {
+            Reference::New(spellings.entry(name.to_lowercase()).or_insert(name).clone())
+        }
+        existing => existing,
+    }
+}
+
+pub(super) fn reference_id(reference: &Option<Reference>) -> i64 {
+    match reference {
+        Some(Reference::Existing(id)) => *id,
+        Some(Reference::New(_)) => -1,
+        None => 0,
+    }
+}
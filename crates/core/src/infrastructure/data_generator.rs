@@ -0,0 +1,286 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashSet;
+
+use crate::domain::{CommandError, DomainEntity, Entity, Job, Organization, Services, User};
+
+/// Users are created in transactions of this many, so a large run neither
+/// commits row by row nor holds one huge transaction open.
+const USERS_PER_TRANSACTION: usize = 1_000;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Ana", "Andrei", "Aiko", "Beatriz", "Bruno", "Chen", "Chloe", "Dalia",
+    "Daniel", "Elena", "Emeka", "Farah", "Felix", "Grace", "Hana", "Hugo", "Ines", "Ivan", "Jamal",
+    "Julia", "Kai", "Kofi", "Lars", "Leila", "Lucas", "Maya", "Mateo", "Nadia", "Niko", "Olga",
+    "Omar", "Priya", "Rafael", "Rosa", "Sami", "Sofia", "Tariq", "Theo", "Uma", "Victor", "Wen",
+    "Yara", "Yusuf", "Zoe", "Zora",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Abara",
+    "Bianchi",
+    "Castillo",
+    "Dubois",
+    "Eriksen",
+    "Fischer",
+    "Garcia",
+    "Haddad",
+    "Ivanova",
+    "Jensen",
+    "Kowalski",
+    "Lindqvist",
+    "Moreau",
+    "Nakamura",
+    "Okafor",
+    "Petrov",
+    "Quinn",
+    "Rossi",
+    "Schmidt",
+    "Tanaka",
+    "Urquhart",
+    "Varga",
+    "Wang",
+    "Xu",
+    "Yilmaz",
+    "Zhang",
+    "Novak",
+    "Silva",
+    "Murphy",
+    "Kim",
+    "Singh",
+    "Costa",
+];
+
+const SENIORITIES: &[&str] = &["Junior", "Associate", "", "Senior", "Lead", "Principal"];
+
+const ROLES: &[&str] = &[
+    "Software Engineer",
+    "Data Analyst",
+    "Product Manager",
+    "Designer",
+    "Accountant",
+    "Recruiter",
+    "Support Specialist",
+    "Sales Representative",
+    "QA Engineer",
+    "Marketing Manager",
+    "Operations Manager",
+    "Legal Counsel",
+    "Technical Writer",
+    "Security Engineer",
+    "Office Manager",
+];
+
+const COMPANY_PREFIXES: &[&str] = &[
+    "Acme",
+    "Blue",
+    "Bright",
+    "Cedar",
+    "Copper",
+    "Delta",
+    "Evergreen",
+    "Granite",
+    "Harbor",
+    "Iron",
+    "Lumen",
+    "Maple",
+    "North",
+    "Orbit",
+    "Pioneer",
+    "Quartz",
+    "River",
+    "Summit",
+];
+
+const COMPANY_NOUNS: &[&str] = &[
+    "Analytics",
+    "Labs",
+    "Logistics",
+    "Media",
+    "Robotics",
+    "Systems",
+    "Foods",
+    "Health",
+    "Energy",
+    "Finance",
+    "Studios",
+    "Works",
+];
+
+const COMPANY_SUFFIXES: &[&str] = &["Inc", "Ltd", "GmbH", "Group", "Co"];
+
+/// How much sample data to create. The same seed always yields the same
+/// names and assignments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    pub organizations: usize,
+    pub jobs: usize,
+    pub users: usize,
+    pub seed: u64,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            organizations: 10,
+            jobs: 25,
+            users: 1_000,
+            seed: 42,
+        }
+    }
+}
+
+/// The number of records a generator run created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GeneratedData {
+    pub organizations: usize,
+    pub jobs: usize,
+    pub users: usize,
+}
+
+impl std::fmt::Display for GeneratedData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} organizations, {} jobs and {} users",
+            self.organizations, self.jobs, self.users
+        )
+    }
+}
+
+/// Fills the database with sample organizations, jobs and users through
+/// the services, so the usual validation and audit logging apply.
+///
+/// Names already taken are skipped. Users are spread over every job and
+/// organization in the database, including ones that existed before.
+pub async fn generate_data(
+    services: &Services,
+    options: GeneratorOptions,
+) -> Result<GeneratedData, CommandError> {
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let mut generated = GeneratedData::default();
+
+    let (transaction_services, transaction) = services.begin().await?;
+    let ids = async {
+        let mut taken: HashSet<String> = transaction_services
+            .organization
+            .get_all_organizations()
+            .await?
+            .into_iter()
+            .map(|organization| organization.name().to_string())
+            .collect();
+        for _ in 0..options.organizations {
+            let mut organization = Organization::new();
+            organization.set_name(unique_name(&mut taken, || {
+                format!(
+                    "{} {} {}",
+                    pick(&mut rng, COMPANY_PREFIXES),
+                    pick(&mut rng, COMPANY_NOUNS),
+                    pick(&mut rng, COMPANY_SUFFIXES)
+                )
+            }));
+            transaction_services
+                .organization
+                .create_organization(organization)
+                .await?;
+            generated.organizations += 1;
+        }
+
+        let mut taken: HashSet<String> = transaction_services
+            .job
+            .get_all_jobs()
+            .await?
+            .into_iter()
+            .map(|job| job.name().to_string())
+            .collect();
+        for _ in 0..options.jobs {
+            let mut job = Job::new();
+            job.set_name(unique_name(&mut taken, || {
+                format!("{} {}", pick(&mut rng, SENIORITIES), pick(&mut rng, ROLES))
+                    .trim()
+                    .to_string()
+            }));
+            transaction_services.job.create_job(job).await?;
+            generated.jobs += 1;
+        }
+
+        // Checked before committing, so a run that cannot add its users leaves
+        // nothing behind.
+        let job_ids: Vec<i64> = transaction_services
+            .job
+            .get_all_jobs()
+            .await?
+            .iter()
+            .map(Entity::id)
+            .collect();
+        let organization_ids: Vec<i64> = transaction_services
+            .organization
+            .get_all_organizations()
+            .await?
+            .iter()
+            .map(Entity::id)
+            .collect();
+        if options.users > 0 && job_ids.is_empty() {
+            return Err(CommandError::NotFound(DomainEntity::Job));
+        }
+        if options.users > 0 && organization_ids.is_empty() {
+            return Err(CommandError::NotFound(DomainEntity::Organization));
+        }
+        Ok((job_ids, organization_ids))
+    }
+    .await;
+    let (job_ids, organization_ids) = transaction.complete(ids).await?;
+
+    let mut remaining = options.users;
+    while remaining > 0 {
+        let batch = remaining.min(USERS_PER_TRANSACTION);
+        let (transaction_services, transaction) = services.begin().await?;
+        let result = async {
+            for _ in 0..batch {
+                let mut user = User::new();
+                user.set_name(format!(
+                    "{} {}",
+                    pick(&mut rng, FIRST_NAMES),
+                    pick(&mut rng, LAST_NAMES)
+                ));
+                user.set_job_id(job_ids[rng.gen_range(0..job_ids.len())]);
+                user.set_organization_id(
+                    organization_ids[rng.gen_range(0..organization_ids.len())],
+                );
+                transaction_services.user.create_user(user).await?;
+            }
+            Ok::<_, CommandError>(())
+        }
+        .await;
+        transaction.complete(result).await?;
+        generated.users += batch;
+        remaining -= batch;
+    }
+
+    Ok(generated)
+}
+
+fn pick<'a>(rng: &mut ChaCha8Rng, words: &[&'a str]) -> &'a str {
+    words.choose(rng).copied().unwrap_or_default()
+}
+
+/// Draws names until one is free, numbering it once the draws keep
+/// colliding so large runs still terminate.
+fn unique_name(taken: &mut HashSet<String>, mut draw: impl FnMut() -> String) -> String {
+    let mut name = draw();
+    let mut attempts = 1;
+    while taken.contains(&name) {
+        name = draw();
+        attempts += 1;
+        if attempts > 10 {
+            let base = name;
+            name = (2..)
+                .map(|number| format!("{} {}", base, number))
+                .find(|candidate| !taken.contains(candidate))
+                .unwrap_or(base);
+        }
+    }
+    taken.insert(name.clone());
+    name
+}
@@ -0,0 +1,103 @@
+use sqlx::{migrate::Migrator, sqlite::SqliteConnectOptions, SqlitePool};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::domain::{RepositoryError, Services};
+
+use super::audited_repository::audited_services;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Clone)]
+pub struct Database {
+    pub pool: SqlitePool,
+    pub path: PathBuf,
+    pub read_only: bool,
+}
+
+impl Database {
+    pub async fn new(database_path: &str) -> Result<Self, sqlx::Error> {
+        Self::open(database_path, false).await
+    }
+
+    /// Opens a database file. A read-only database must already exist and is
+    /// not migrated, so a fixture is never modified.
+    pub async fn open(database_path: &str, read_only: bool) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::new()
+            .filename(database_path)
+            .foreign_keys(true);
+
+        let pool = if read_only {
+            SqlitePool::connect_with(options.read_only(true)).await?
+        } else {
+            if let Some(parent) = PathBuf::from(database_path).parent() {
+                std::fs::create_dir_all(parent).map_err(sqlx::Error::Io)?;
+            }
+            let pool = SqlitePool::connect_with(options.create_if_missing(true)).await?;
+            MIGRATOR.run(&pool).await?;
+            pool
+        };
+        log::info!(
+            "Opened {}{}",
+            database_path,
+            if read_only { " read-only" } else { "" }
+        );
+
+        Ok(Self {
+            pool,
+            path: PathBuf::from(database_path),
+            read_only,
+        })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Builds the domain services on top of audited SQLite repositories.
+    pub fn services(&self) -> Services {
+        audited_services(Arc::new(self.clone()))
+    }
+}
+
+pub fn get_database_path() -> PathBuf {
+    #[cfg(debug_assertions)]
+    {
+        PathBuf::from("./data/app.db")
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("iced-user-management")
+            .join("app.db")
+    }
+}
+
+/// SQLite reports `ON DELETE RESTRICT` failures as SQLITE_CONSTRAINT_TRIGGER
+/// rather than as foreign key violations.
+const SQLITE_CONSTRAINT_TRIGGER: &str = "1811";
+
+pub fn map_sqlx_error(error: sqlx::Error) -> RepositoryError {
+    match error.as_database_error() {
+        Some(db_error)
+            if db_error.is_unique_violation()
+                || db_error.is_foreign_key_violation()
+                || db_error.code().as_deref() == Some(SQLITE_CONSTRAINT_TRIGGER) =>
+        {
+            RepositoryError::ConstraintViolation(db_error.message().to_string())
+        }
+        _ => RepositoryError::DatabaseError(error.to_string()),
+    }
+}
+
+/// Turns free text into an FTS5 query that matches every word as a prefix,
+/// or `None` when there is nothing to search for.
+pub fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
@@ -0,0 +1,201 @@
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::domain::{CommandError, Entity, Job, Organization, Services, User, UserQuery};
+
+/// Users are read from the database in pages of this many while exporting.
+const EXPORT_PAGE_SIZE: i64 = 5_000;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Could not write {0}: {1}")]
+    Csv(PathBuf, csv::Error),
+    #[error("Could not write {0}: {1}")]
+    Xlsx(PathBuf, XlsxError),
+    #[error("Could not write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error(transparent)]
+    Command(#[from] CommandError),
+}
+
+/// The file types a list can be exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    /// Picks the format from the file extension, defaulting to CSV.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("xlsx") => ExportFormat::Xlsx,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+/// A value in an exported table. Ids stay numbers so spreadsheets can sort
+/// and filter on them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportCell {
+    Number(i64),
+    Text(String),
+}
+
+impl std::fmt::Display for ExportCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportCell::Number(number) => write!(f, "{}", number),
+            ExportCell::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// The rows of a list view, in the order shown, ready to be written out.
+#[derive(Debug, Clone, Default)]
+pub struct ExportTable {
+    pub name: &'static str,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<ExportCell>>,
+}
+
+impl ExportTable {
+    pub fn jobs(jobs: &[Job]) -> Self {
+        Self::named_list("Jobs", jobs)
+    }
+
+    pub fn organizations(organizations: &[Organization]) -> Self {
+        Self::named_list("Organizations", organizations)
+    }
+
+    fn named_list<T: Entity>(name: &'static str, list: &[T]) -> Self {
+        Self {
+            name,
+            headers: vec!["ID", "Name"],
+            rows: list
+                .iter()
+                .map(|entity| {
+                    vec![
+                        ExportCell::Number(entity.id()),
+                        ExportCell::Text(entity.name().to_string()),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// Every user matching the filters and sort order of `query`, across all
+    /// pages, with job and organization names in place of their ids.
+    pub async fn users(
+        services: &Services,
+        query: &UserQuery,
+        jobs: &[Job],
+        organizations: &[Organization],
+    ) -> Result<Self, CommandError> {
+        let mut query = UserQuery {
+            after: None,
+            limit: EXPORT_PAGE_SIZE,
+            ..query.clone()
+        };
+        let mut table = Self::user_list(&[], jobs, organizations);
+
+        loop {
+            let page = services.user.get_user_page(&query).await?;
+            table
+                .rows
+                .extend(Self::user_list(&page.users, jobs, organizations).rows);
+            match page.next {
+                Some(next) => query.after = Some(next),
+                None => break,
+            }
+        }
+        Ok(table)
+    }
+
+    /// The given users, in order, with job and organization names in place
+    /// of their ids.
+    pub fn user_list(users: &[User], jobs: &[Job], organizations: &[Organization]) -> Self {
+        let job_names = names_by_id(jobs);
+        let organization_names = names_by_id(organizations);
+        let name = |names: &HashMap<i64, &str>, id: i64| {
+            ExportCell::Text(names.get(&id).copied().unwrap_or_default().to_string())
+        };
+        Self {
+            name: "Users",
+            headers: vec!["ID", "Name", "Job", "Organization", "Email", "Phone"],
+            rows: users
+                .iter()
+                .map(|user| {
+                    vec![
+                        ExportCell::Number(user.id()),
+                        ExportCell::Text(user.name().to_string()),
+                        name(&job_names, user.job_id()),
+                        name(&organization_names, user.organization_id()),
+                        ExportCell::Text(user.email().to_string()),
+                        ExportCell::Text(user.phone().to_string()),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the table in the format matching the extension of `path` and
+    /// returns the number of rows written.
+    pub fn write(&self, path: &Path) -> Result<usize, ExportError> {
+        match ExportFormat::from_path(path) {
+            ExportFormat::Csv => self.write_csv(path),
+            ExportFormat::Xlsx => self.write_xlsx(path),
+        }
+    }
+
+    fn write_csv(&self, path: &Path) -> Result<usize, ExportError> {
+        let error = |e| ExportError::Csv(path.to_path_buf(), e);
+        let mut writer = csv::Writer::from_path(path).map_err(error)?;
+        writer.write_record(&self.headers).map_err(error)?;
+        for row in &self.rows {
+            writer
+                .write_record(row.iter().map(ExportCell::to_string))
+                .map_err(error)?;
+        }
+        writer.flush().map_err(|e| error(e.into()))?;
+        Ok(self.rows.len())
+    }
+
+    fn write_xlsx(&self, path: &Path) -> Result<usize, ExportError> {
+        let error = |e| ExportError::Xlsx(path.to_path_buf(), e);
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(self.name).map_err(error)?;
+        let bold = Format::new().set_bold();
+        for (column, header) in self.headers.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, column as u16, *header, &bold)
+                .map_err(error)?;
+        }
+        for (index, row) in self.rows.iter().enumerate() {
+            let line = index as u32 + 1;
+            for (column, cell) in row.iter().enumerate() {
+                match cell {
+                    ExportCell::Number(number) => {
+                        sheet.write_number(line, column as u16, *number as f64)
+                    }
+                    ExportCell::Text(text) => sheet.write_string(line, column as u16, text),
+                }
+                .map_err(error)?;
+            }
+        }
+        sheet.set_freeze_panes(1, 0).map_err(error)?;
+        sheet.autofit();
+        workbook.save(path).map_err(error)?;
+        Ok(self.rows.len())
+    }
+}
+
+fn names_by_id<T: Entity>(list: &[T]) -> HashMap<i64, &str> {
+    list.iter()
+        .map(|entity| (entity.id(), entity.name()))
+        .collect()
+}
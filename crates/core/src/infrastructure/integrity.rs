@@ -0,0 +1,100 @@
+use sqlx::Row;
+
+use crate::domain::{Entity, User};
+
+use super::Database;
+
+/// A row whose foreign key points at a missing parent, as reported by
+/// `PRAGMA foreign_key_check`.
+#[derive(Debug, Clone)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+}
+
+/// A user whose job or organization no longer exists.
+#[derive(Debug, Clone)]
+pub struct OrphanedUser {
+    pub user: User,
+    pub missing_job: bool,
+    pub missing_organization: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Problems reported by `PRAGMA integrity_check`; empty when it says "ok".
+    pub problems: Vec<String>,
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+    pub orphaned_users: Vec<OrphanedUser>,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+            && self.foreign_key_violations.is_empty()
+            && self.orphaned_users.is_empty()
+    }
+}
+
+impl Database {
+    /// Checks the file structure and the references between tables.
+    pub async fn check_integrity(&self) -> Result<IntegrityReport, sqlx::Error> {
+        let problems: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(self.pool())
+            .await?
+            .into_iter()
+            .filter(|message: &String| message != "ok")
+            .collect();
+
+        let foreign_key_violations = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(self.pool())
+            .await?
+            .into_iter()
+            .map(|row| {
+                Ok(ForeignKeyViolation {
+                    table: row.try_get("table")?,
+                    rowid: row.try_get("rowid")?,
+                    parent: row.try_get("parent")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        let orphaned_users = sqlx::query(
+            r#"
+            SELECT u.id, u.name, u.version, u.job_id, u.organization_id, u.email, u.phone,
+                   j.id IS NULL AS missing_job, o.id IS NULL AS missing_organization
+            FROM users u
+            LEFT JOIN jobs j ON j.id = u.job_id
+            LEFT JOIN organizations o ON o.id = u.organization_id
+            WHERE j.id IS NULL OR o.id IS NULL
+            ORDER BY u.name
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?
+        .into_iter()
+        .map(|row| {
+            let mut user = User::new();
+            user.set_id(row.try_get("id")?);
+            user.set_name(row.try_get("name")?);
+            user.set_version(row.try_get("version")?);
+            user.set_job_id(row.try_get("job_id")?);
+            user.set_organization_id(row.try_get("organization_id")?);
+            user.set_email(row.try_get("email")?);
+            user.set_phone(row.try_get("phone")?);
+            Ok(OrphanedUser {
+                user,
+                missing_job: row.try_get("missing_job")?,
+                missing_organization: row.try_get("missing_organization")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(IntegrityReport {
+            problems,
+            foreign_key_violations,
+            orphaned_users,
+        })
+    }
+}
@@ -0,0 +1,205 @@
+use super::unit_of_work::SqliteExecutor;
+use super::{fts_query, map_sqlx_error};
+use crate::domain::{
+    repositories::{JobRepository, RepositoryError, SEARCH_LIMIT},
+    Entity, Job,
+};
+use async_trait::async_trait;
+
+pub struct JobSqliteRepository {
+    executor: SqliteExecutor,
+}
+
+impl JobSqliteRepository {
+    pub fn new(executor: SqliteExecutor) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl JobRepository for JobSqliteRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Job>, RepositoryError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, name, version
+            FROM jobs
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(row.map(|r| {
+            let mut job = Job::new();
+            job.set_id(r.id);
+            job.set_name(r.name);
+            job.set_version(r.version);
+            job
+        }))
+    }
+
+    async fn find_all(&self) -> Result<Vec<Job>, RepositoryError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id as "id!", name, version
+            FROM jobs
+            ORDER BY name
+            "#
+        )
+        .fetch_all(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let mut job = Job::new();
+                job.set_id(r.id);
+                job.set_name(r.name);
+                job.set_version(r.version);
+                job
+            })
+            .collect())
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Job>, RepositoryError> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT j.id as "id!", j.name as "name!", j.version as "version!"
+            FROM search_index
+            JOIN jobs j ON j.id = search_index.entity_id
+            WHERE search_index MATCH ? AND search_index.entity_type = 'job'
+            ORDER BY search_index.rank
+            LIMIT ?
+            "#,
+            query,
+            SEARCH_LIMIT
+        )
+        .fetch_all(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let mut job = Job::new();
+                job.set_id(r.id);
+                job.set_name(r.name);
+                job.set_version(r.version);
+                job
+            })
+            .collect())
+    }
+
+    async fn create(&self, job: &Job) -> Result<Job, RepositoryError> {
+        let job_name = job.name();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO jobs (name)
+            VALUES (?)
+            "#,
+            job_name
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        let mut saved_job = job.clone();
+        saved_job.set_id(result.last_insert_rowid());
+        saved_job.set_version(1);
+        Ok(saved_job)
+    }
+
+    async fn restore(&self, job: &Job) -> Result<(), RepositoryError> {
+        let job_id = job.id();
+        let job_name = job.name();
+        let version = job.version().max(1);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO jobs (id, name, version)
+            VALUES (?, ?, ?)
+            "#,
+            job_id,
+            job_name,
+            version
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), RepositoryError> {
+        let job_name = job.name();
+        let job_id = job.id();
+        let version = job.version();
+
+        let rows_affected = sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET name = ?, version = version + 1
+            WHERE id = ? AND version = ?
+            "#,
+            job_name,
+            job_id,
+            version
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return match self.find_by_id(job_id).await? {
+                Some(_) => Err(RepositoryError::Conflict),
+                None => Err(RepositoryError::NotFound),
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let mut connection = self.executor.acquire().await?;
+        // Checked up front rather than left to ON DELETE RESTRICT: on a pooled
+        // connection a DELETE refused by the constraint could still take
+        // effect once the users were gone.
+        let in_use: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE job_id = ?)")
+                .bind(id)
+                .fetch_one(&mut *connection)
+                .await
+                .map_err(map_sqlx_error)?;
+        if in_use {
+            return Err(RepositoryError::ConstraintViolation(
+                "FOREIGN KEY constraint failed".to_string(),
+            ));
+        }
+
+        let rows_affected = sqlx::query!(
+            r#"
+            DELETE FROM jobs WHERE id = ?
+            "#,
+            id
+        )
+        .execute(&mut *connection)
+        .await
+        .map_err(map_sqlx_error)?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        Ok(())
+    }
+}
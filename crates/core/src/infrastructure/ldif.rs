@@ -0,0 +1,715 @@
+//! Reading and writing LDIF (RFC 2849) so user data can be fed to an LDAP
+//! directory and read back from a directory dump.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use thiserror::Error;
+
+use super::csv_import::{canonical, ids_by_name, reference_id, resolve, Reference};
+use crate::domain::{CommandError, Entity, Job, Organization, Services, User};
+
+/// LDIF lines are folded once they get longer than this.
+const LINE_WIDTH: usize = 76;
+/// The inetOrgPerson attribute phone numbers are written to and read from.
+const PHONE_ATTRIBUTE: &str = "telephoneNumber";
+
+#[derive(Debug, Error)]
+pub enum LdifError {
+    #[error("Could not read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Could not write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("Invalid LDIF on line {0}: {1}")]
+    Parse(usize, String),
+    #[error(transparent)]
+    Command(#[from] CommandError),
+}
+
+/// How users map onto directory entries. Templates may use `{name}`,
+/// `{job}`, `{organization}`, `{uid}` (the name in lower case, joined by
+/// dots) and `{id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LdifOptions {
+    pub dn_template: String,
+    pub name_attribute: String,
+    pub job_attribute: String,
+    pub organization_attribute: String,
+    pub mail_attribute: String,
+    /// Used for users without an email address. Left empty, no mail
+    /// attribute is written for them.
+    pub mail_template: String,
+    pub object_classes: Vec<String>,
+}
+
+impl Default for LdifOptions {
+    fn default() -> Self {
+        Self {
+            dn_template: "cn={name},ou={organization},dc=example,dc=com".to_string(),
+            name_attribute: "cn".to_string(),
+            job_attribute: "title".to_string(),
+            organization_attribute: "ou".to_string(),
+            mail_attribute: "mail".to_string(),
+            mail_template: "{uid}@example.com".to_string(),
+            object_classes: ["top", "person", "organizationalPerson", "inetOrgPerson"]
+                .map(str::to_string)
+                .to_vec(),
+        }
+    }
+}
+
+/// The options that can be edited on the Settings page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LdifField {
+    DnTemplate,
+    NameAttribute,
+    JobAttribute,
+    OrganizationAttribute,
+    MailAttribute,
+    MailTemplate,
+}
+
+impl LdifField {
+    pub const ALL: [LdifField; 6] = [
+        LdifField::DnTemplate,
+        LdifField::NameAttribute,
+        LdifField::JobAttribute,
+        LdifField::OrganizationAttribute,
+        LdifField::MailAttribute,
+        LdifField::MailTemplate,
+    ];
+}
+
+impl std::fmt::Display for LdifField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            LdifField::DnTemplate => "DN template",
+            LdifField::NameAttribute => "Name attribute",
+            LdifField::JobAttribute => "Job attribute",
+            LdifField::OrganizationAttribute => "Organization attribute",
+            LdifField::MailAttribute => "Mail attribute",
+            LdifField::MailTemplate => "Mail template",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+impl LdifOptions {
+    pub fn get(&self, field: LdifField) -> &str {
+        match field {
+            LdifField::DnTemplate => &self.dn_template,
+            LdifField::NameAttribute => &self.name_attribute,
+            LdifField::JobAttribute => &self.job_attribute,
+            LdifField::OrganizationAttribute => &self.organization_attribute,
+            LdifField::MailAttribute => &self.mail_attribute,
+            LdifField::MailTemplate => &self.mail_template,
+        }
+    }
+
+    pub fn set(&mut self, field: LdifField, value: String) {
+        match field {
+            LdifField::DnTemplate => self.dn_template = value,
+            LdifField::NameAttribute => self.name_attribute = value,
+            LdifField::JobAttribute => self.job_attribute = value,
+            LdifField::OrganizationAttribute => self.organization_attribute = value,
+            LdifField::MailAttribute => self.mail_attribute = value,
+            LdifField::MailTemplate => self.mail_template = value,
+        }
+    }
+}
+
+/// One entry of an LDIF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdifEntry {
+    /// The line the entry starts on.
+    pub line: usize,
+    pub dn: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl LdifEntry {
+    /// The first value of an attribute. Names are matched ignoring case and
+    /// attribute options, so `cn;lang-en` counts as `cn`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| {
+                attribute
+                    .split(';')
+                    .next()
+                    .is_some_and(|base| base.eq_ignore_ascii_case(name))
+            })
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Splits LDIF content into entries, unfolding continued lines and decoding
+/// base64 values. `changetype` records are read like plain entries.
+pub fn parse_ldif(content: &str) -> Result<Vec<LdifEntry>, LdifError> {
+    // Unfold first, remembering where each logical line started.
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        match line.strip_prefix(' ') {
+            Some(continued) if !lines.is_empty() && !line.trim().is_empty() => {
+                if let Some((_, last)) = lines.last_mut() {
+                    last.push_str(continued);
+                }
+            }
+            _ => lines.push((index + 1, line.to_string())),
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut entry: Option<LdifEntry> = None;
+    for (number, line) in lines {
+        if line.trim().is_empty() {
+            entries.extend(entry.take());
+            continue;
+        }
+        // Comments, and the `-` ending each modification in a `changetype:
+        // modify` record.
+        if line.starts_with('#') || (line == "-" && entry.is_some()) {
+            continue;
+        }
+        let (name, value) = parse_line(number, &line)?;
+        match &mut entry {
+            None if name.eq_ignore_ascii_case("version") => {}
+            None if name.eq_ignore_ascii_case("dn") => {
+                entry = Some(LdifEntry {
+                    line: number,
+                    dn: value,
+                    attributes: Vec::new(),
+                })
+            }
+            None => {
+                return Err(LdifError::Parse(
+                    number,
+                    format!("expected 'dn:', found '{}:'", name),
+                ))
+            }
+            Some(entry) => entry.attributes.push((name, value)),
+        }
+    }
+    entries.extend(entry);
+    Ok(entries)
+}
+
+fn parse_line(number: usize, line: &str) -> Result<(String, String), LdifError> {
+    let Some((name, rest)) = line.split_once(':') else {
+        return Err(LdifError::Parse(number, "missing ':'".to_string()));
+    };
+    let value = if let Some(encoded) = rest.strip_prefix(':') {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| LdifError::Parse(number, format!("invalid base64: {}", e)))?;
+        String::from_utf8(bytes)
+            .map_err(|_| LdifError::Parse(number, "value is not UTF-8 text".to_string()))?
+    } else if rest.starts_with('<') {
+        return Err(LdifError::Parse(
+            number,
+            "values read from URLs are not supported".to_string(),
+        ));
+    } else {
+        rest.trim_start().to_string()
+    };
+    Ok((name.trim().to_string(), value))
+}
+
+/// Appends one `name: value` line, base64-encoded when the value is not a
+/// safe string and folded when it is long.
+fn write_line(out: &mut String, name: &str, value: &str) {
+    let safe = value
+        .bytes()
+        .all(|byte| byte.is_ascii() && byte != b'\r' && byte != b'\n')
+        && !value.starts_with([' ', ':', '<'])
+        && !value.ends_with(' ')
+        && !value.contains('\0');
+    let line = if safe {
+        format!("{}: {}", name, value)
+    } else {
+        format!("{}:: {}", name, STANDARD.encode(value))
+    };
+
+    // Only ASCII remains at this point, so slicing by bytes is safe.
+    let mut rest = line.as_str();
+    let mut width = LINE_WIDTH;
+    while rest.len() > width {
+        let (head, tail) = rest.split_at(width);
+        out.push_str(head);
+        out.push_str("\n ");
+        rest = tail;
+        width = LINE_WIDTH - 1;
+    }
+    out.push_str(rest);
+    out.push('\n');
+}
+
+/// Escapes a value for use inside a DN, as described in RFC 4514.
+fn escape_dn_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let last = value.chars().count().saturating_sub(1);
+    for (index, c) in value.chars().enumerate() {
+        let special = matches!(c, ',' | '+' | '"' | '\\' | '<' | '>' | ';' | '=')
+            || (index == 0 && (c == '#' || c == ' '))
+            || (index == last && c == ' ');
+        if special {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The name as a lower-case login, such as `ada.lovelace`.
+fn uid(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '-')
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn fill_template(
+    template: &str,
+    user: &User,
+    job: &str,
+    organization: &str,
+    escape: fn(&str) -> String,
+) -> String {
+    template
+        .replace("{name}", &escape(user.name()))
+        .replace("{job}", &escape(job))
+        .replace("{organization}", &escape(organization))
+        .replace("{uid}", &escape(&uid(user.name())))
+        .replace("{id}", &user.id().to_string())
+}
+
+/// Writes every user as a directory entry, ordered by id.
+pub async fn export_ldif(
+    services: &Services,
+    options: &LdifOptions,
+) -> Result<(String, usize), CommandError> {
+    let job_names: HashMap<i64, String> = services
+        .job
+        .get_all_jobs()
+        .await?
+        .into_iter()
+        .map(|job| (job.id(), job.name().to_string()))
+        .collect();
+    let organization_names: HashMap<i64, String> = services
+        .organization
+        .get_all_organizations()
+        .await?
+        .into_iter()
+        .map(|organization| (organization.id(), organization.name().to_string()))
+        .collect();
+    let mut users = services.user.get_all_users().await?;
+    users.sort_by_key(User::id);
+
+    let mut out = String::from("version: 1\n");
+    for user in &users {
+        let job = job_names
+            .get(&user.job_id())
+            .map(String::as_str)
+            .unwrap_or_default();
+        let organization = organization_names
+            .get(&user.organization_id())
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        out.push('\n');
+        write_line(
+            &mut out,
+            "dn",
+            &fill_template(
+                &options.dn_template,
+                user,
+                job,
+                organization,
+                escape_dn_value,
+            ),
+        );
+        for class in &options.object_classes {
+            write_line(&mut out, "objectClass", class);
+        }
+        write_line(&mut out, &options.name_attribute, user.name());
+        // inetOrgPerson requires a surname.
+        if let Some(surname) = user.name().split_whitespace().last() {
+            write_line(&mut out, "sn", surname);
+        }
+        if !job.is_empty() {
+            write_line(&mut out, &options.job_attribute, job);
+        }
+        if !organization.is_empty() {
+            write_line(&mut out, &options.organization_attribute, organization);
+        }
+        if !user.email().is_empty() {
+            write_line(&mut out, &options.mail_attribute, user.email());
+        } else if !options.mail_template.is_empty() {
+            let mail = fill_template(&options.mail_template, user, job, organization, |value| {
+                value.to_string()
+            });
+            write_line(&mut out, &options.mail_attribute, &mail);
+        }
+        if !user.phone().is_empty() {
+            write_line(&mut out, PHONE_ATTRIBUTE, user.phone());
+        }
+    }
+    Ok((out, users.len()))
+}
+
+/// A user the import would create or change.
+#[derive(Debug, Clone)]
+pub struct LdifChange {
+    /// The user as stored now, or `None` for a new user.
+    pub existing: Option<User>,
+    pub name: String,
+    pub job_name: String,
+    pub organization_name: String,
+    pub job: Reference,
+    pub organization: Reference,
+    pub email: String,
+    pub phone: String,
+}
+
+/// An entry the import leaves alone, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdifSkip {
+    pub line: usize,
+    pub dn: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for LdifSkip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "! line {} {}: {}", self.line, self.dn, self.reason)
+    }
+}
+
+/// What importing a directory dump would do, worked out without writing
+/// anything so it can be reviewed first.
+#[derive(Debug, Clone, Default)]
+pub struct LdifPlan {
+    pub new_jobs: BTreeSet<String>,
+    pub new_organizations: BTreeSet<String>,
+    pub changes: Vec<LdifChange>,
+    pub unchanged: usize,
+    pub skipped: Vec<LdifSkip>,
+}
+
+impl LdifPlan {
+    /// Matches entries to users by name, ignoring case, and jobs and
+    /// organizations likewise. Names not found become new records.
+    pub fn new(
+        entries: &[LdifEntry],
+        options: &LdifOptions,
+        users: &[User],
+        jobs: &[Job],
+        organizations: &[Organization],
+    ) -> Self {
+        let job_ids = ids_by_name(jobs);
+        let organization_ids = ids_by_name(organizations);
+        let job_names: HashMap<i64, &str> = jobs.iter().map(|job| (job.id(), job.name())).collect();
+        let organization_names: HashMap<i64, &str> = organizations
+            .iter()
+            .map(|organization| (organization.id(), organization.name()))
+            .collect();
+        let mut users_by_name: HashMap<String, Vec<&User>> = HashMap::new();
+        for user in users {
+            users_by_name
+                .entry(user.name().trim().to_lowercase())
+                .or_default()
+                .push(user);
+        }
+
+        let mut new_job_names = HashMap::new();
+        let mut new_organization_names = HashMap::new();
+        let mut seen = HashMap::new();
+        let mut plan = Self::default();
+
+        for entry in entries {
+            let skip = |reason: String| LdifSkip {
+                line: entry.line,
+                dn: entry.dn.clone(),
+                reason,
+            };
+            if entry
+                .get("changetype")
+                .is_some_and(|change| !change.eq_ignore_ascii_case("add"))
+            {
+                plan.skipped
+                    .push(skip("only entries to add can be imported".to_string()));
+                continue;
+            }
+            let Some(name) = entry.get(&options.name_attribute).map(str::trim) else {
+                // Entries for the organizational units themselves and the like.
+                plan.skipped
+                    .push(skip(format!("no {} attribute", options.name_attribute)));
+                continue;
+            };
+            if let Some(line) = seen.insert(name.to_lowercase(), entry.line) {
+                plan.skipped
+                    .push(skip(format!("same name as the entry on line {}", line)));
+                continue;
+            }
+
+            let job_name = entry.get(&options.job_attribute).unwrap_or_default().trim();
+            let organization_name = entry
+                .get(&options.organization_attribute)
+                .unwrap_or_default()
+                .trim();
+            let mut errors = Vec::new();
+            let job = resolve::<Job>("Job", job_name, &job_ids, true, &mut errors)
+                .map(|reference| canonical(reference, &mut new_job_names));
+            let organization = resolve::<Organization>(
+                "Organization",
+                organization_name,
+                &organization_ids,
+                true,
+                &mut errors,
+            )
+            .map(|reference| canonical(reference, &mut new_organization_names));
+
+            let matches = users_by_name
+                .get(&name.to_lowercase())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if matches.len() > 1 {
+                errors.push(format!("matches {} users with that name", matches.len()));
+            }
+            let existing = matches.first().map(|user| (*user).clone());
+            let mut user = existing.clone().unwrap_or_else(|| {
+                let mut user = User::new();
+                user.set_name(name.to_string());
+                user
+            });
+            user.set_job_id(reference_id(&job));
+            user.set_organization_id(reference_id(&organization));
+            // A mail value made from the mail template on export is not a
+            // real address, so it is not stored on users without one.
+            if let Some(mail) = entry.get(&options.mail_attribute).map(str::trim) {
+                let templated = !options.mail_template.is_empty()
+                    && user.email().is_empty()
+                    && mail
+                        == fill_template(
+                            &options.mail_template,
+                            &user,
+                            job_name,
+                            organization_name,
+                            |value| value.to_string(),
+                        );
+                if !templated {
+                    user.set_email(mail.to_string());
+                }
+            }
+            if let Some(phone) = entry.get(PHONE_ATTRIBUTE) {
+                user.set_phone(phone.trim().to_string());
+            }
+            if let Err(user_errors) = user.validate() {
+                let mut messages: Vec<&str> = user_errors.values().copied().collect();
+                messages.sort();
+                errors.extend(messages.into_iter().map(str::to_string));
+            }
+            let (Some(job), Some(organization), true) = (job, organization, errors.is_empty())
+            else {
+                plan.skipped.push(skip(errors.join("; ")));
+                continue;
+            };
+
+            if let Some(existing) = &existing {
+                if Reference::Existing(existing.job_id()) == job
+                    && Reference::Existing(existing.organization_id()) == organization
+                    && existing.email() == user.email()
+                    && existing.phone() == user.phone()
+                {
+                    plan.unchanged += 1;
+                    continue;
+                }
+            }
+            if let Reference::New(name) = &job {
+                plan.new_jobs.insert(name.clone());
+            }
+            if let Reference::New(name) = &organization {
+                plan.new_organizations.insert(name.clone());
+            }
+            let name_of = |reference: &Reference, names: &HashMap<i64, &str>| match reference {
+                Reference::Existing(id) => names.get(id).copied().unwrap_or_default().to_string(),
+                Reference::New(name) => name.clone(),
+            };
+            plan.changes.push(LdifChange {
+                name: existing
+                    .as_ref()
+                    .map(|user| user.name().to_string())
+                    .unwrap_or_else(|| name.to_string()),
+                job_name: name_of(&job, &job_names),
+                organization_name: name_of(&organization, &organization_names),
+                existing,
+                job,
+                organization,
+                email: user.email().to_string(),
+                phone: user.phone().to_string(),
+            });
+        }
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The plan as a diff: `+` for new records, `~` for changed users and
+    /// `!` for skipped entries.
+    pub fn diff(&self, jobs: &[Job], organizations: &[Organization]) -> Vec<String> {
+        let job_name = |id: i64| {
+            jobs.iter()
+                .find(|job| job.id() == id)
+                .map(|job| job.name().to_string())
+                .unwrap_or_default()
+        };
+        let organization_name = |id: i64| {
+            organizations
+                .iter()
+                .find(|organization| organization.id() == id)
+                .map(|organization| organization.name().to_string())
+                .unwrap_or_default()
+        };
+
+        let mut lines: Vec<String> = self
+            .new_organizations
+            .iter()
+            .map(|name| format!("+ organization {}", name))
+            .chain(self.new_jobs.iter().map(|name| format!("+ job {}", name)))
+            .collect();
+        for change in &self.changes {
+            match &change.existing {
+                None => lines.push(format!(
+                    "+ user {} ({}, {})",
+                    change.name, change.job_name, change.organization_name
+                )),
+                Some(user) => {
+                    let mut differences = Vec::new();
+                    if Reference::Existing(user.job_id()) != change.job {
+                        differences.push(format!(
+                            "job {} -> {}",
+                            job_name(user.job_id()),
+                            change.job_name
+                        ));
+                    }
+                    if Reference::Existing(user.organization_id()) != change.organization {
+                        differences.push(format!(
+                            "organization {} -> {}",
+                            organization_name(user.organization_id()),
+                            change.organization_name
+                        ));
+                    }
+                    if user.email() != change.email {
+                        differences.push(format!("email {} -> {}", user.email(), change.email));
+                    }
+                    if user.phone() != change.phone {
+                        differences.push(format!("phone {} -> {}", user.phone(), change.phone));
+                    }
+                    lines.push(format!(
+                        "~ user {}: {}",
+                        change.name,
+                        differences.join(", ")
+                    ));
+                }
+            }
+        }
+        lines.extend(self.skipped.iter().map(LdifSkip::to_string));
+        lines
+    }
+}
+
+/// The number of records an LDIF import created, updated or left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LdifSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub jobs: usize,
+    pub organizations: usize,
+    pub skipped: usize,
+}
+
+impl std::fmt::Display for LdifSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Created {} and updated {} users, created {} jobs and {} organizations, skipped {} entries",
+            self.created, self.updated, self.jobs, self.organizations, self.skipped
+        )
+    }
+}
+
+/// Applies a plan in one transaction. A user changed by someone else since
+/// the plan was made fails the whole import with a conflict.
+pub async fn apply_ldif(services: &Services, plan: &LdifPlan) -> Result<LdifSummary, CommandError> {
+    let (services, transaction) = services.begin().await?;
+    let result = apply_plan(&services, plan).await;
+    transaction.complete(result).await
+}
+
+async fn apply_plan(services: &Services, plan: &LdifPlan) -> Result<LdifSummary, CommandError> {
+    let mut summary = LdifSummary {
+        skipped: plan.skipped.len(),
+        ..LdifSummary::default()
+    };
+
+    let mut job_ids = HashMap::new();
+    for name in &plan.new_jobs {
+        let mut job = Job::new();
+        job.set_name(name.clone());
+        let job = services.job.create_job(job).await?;
+        job_ids.insert(name.clone(), job.id());
+        summary.jobs += 1;
+    }
+    let mut organization_ids = HashMap::new();
+    for name in &plan.new_organizations {
+        let mut organization = Organization::new();
+        organization.set_name(name.clone());
+        let organization = services
+            .organization
+            .create_organization(organization)
+            .await?;
+        organization_ids.insert(name.clone(), organization.id());
+        summary.organizations += 1;
+    }
+
+    for change in &plan.changes {
+        let id = |reference: &Reference, created: &HashMap<String, i64>| match reference {
+            Reference::Existing(id) => *id,
+            Reference::New(name) => created.get(name).copied().unwrap_or_default(),
+        };
+        match &change.existing {
+            Some(user) => {
+                let mut user = user.clone();
+                user.set_job_id(id(&change.job, &job_ids));
+                user.set_organization_id(id(&change.organization, &organization_ids));
+                user.set_email(change.email.clone());
+                user.set_phone(change.phone.clone());
+                services.user.update_user(user).await?;
+                summary.updated += 1;
+            }
+            None => {
+                let mut user = User::new();
+                user.set_name(change.name.clone());
+                user.set_job_id(id(&change.job, &job_ids));
+                user.set_organization_id(id(&change.organization, &organization_ids));
+                user.set_email(change.email.clone());
+                user.set_phone(change.phone.clone());
+                services.user.create_user(user).await?;
+                summary.created += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
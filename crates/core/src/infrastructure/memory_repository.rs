@@ -0,0 +1,663 @@
+use crate::domain::{
+    repositories::{
+        AuditRepository, JobRepository, OrganizationRepository, RepositoryError, UserRepository,
+        SEARCH_LIMIT,
+    },
+    AuditEntry, AuditFilter, CommandError, Entity, Job, Organization, Repositories, Services,
+    SortDirection, Transaction, UnitOfWork, User, UserCursor, UserPage, UserQuery, UserSortKey,
+};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::audited_repository::audited_services;
+
+/// Tables shared by the in-memory repositories so they can enforce the same
+/// uniqueness and foreign key rules as the SQLite schema.
+#[derive(Debug, Clone, Default)]
+struct Tables {
+    users: BTreeMap<i64, User>,
+    jobs: BTreeMap<i64, Job>,
+    organizations: BTreeMap<i64, Organization>,
+    audit_log: Vec<AuditEntry>,
+    next_user_id: i64,
+    next_job_id: i64,
+    next_organization_id: i64,
+    revision: u64,
+}
+
+impl Tables {
+    /// Counts a successful change to users, jobs or organizations, which
+    /// transactions begun before it then conflict with.
+    fn changed(&mut self) {
+        self.revision += 1;
+    }
+
+    fn check_user_references(&self, user: &User) -> Result<(), RepositoryError> {
+        if !self.jobs.contains_key(&user.job_id())
+            || !self.organizations.contains_key(&user.organization_id())
+        {
+            return Err(foreign_key_violation());
+        }
+        Ok(())
+    }
+
+    /// Mirrors the SQLite sort expressions, where missing names sort as "".
+    fn sort_value(&self, user: &User, sort: UserSortKey) -> String {
+        match sort {
+            UserSortKey::Id => String::new(),
+            UserSortKey::Name => user.name().to_string(),
+            UserSortKey::Job => self
+                .jobs
+                .get(&user.job_id())
+                .map(|job| job.name().to_string())
+                .unwrap_or_default(),
+            UserSortKey::Organization => self
+                .organizations
+                .get(&user.organization_id())
+                .map(|organization| organization.name().to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A thread-safe, process-local stand-in for the SQLite database, used by
+/// demo mode.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDatabase {
+    tables: Arc<RwLock<Tables>>,
+}
+
+impl MemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the domain services on top of audited in-memory repositories.
+    pub fn services(&self) -> Services {
+        audited_services(Arc::new(self.clone()))
+    }
+
+    /// Creates an in-memory database populated with a small sample company,
+    /// going through the services so the audit log is filled in as well.
+    pub async fn with_demo_data() -> Result<Self, CommandError> {
+        let database = Self::new();
+        let services = database.services();
+
+        let mut organization_ids = Vec::new();
+        for name in DEMO_ORGANIZATIONS {
+            let mut organization = Organization::new();
+            organization.set_name(name.to_string());
+            let organization = services
+                .organization
+                .create_organization(organization)
+                .await?;
+            organization_ids.push(organization.id());
+        }
+
+        let mut job_ids = Vec::new();
+        for name in DEMO_JOBS {
+            let mut job = Job::new();
+            job.set_name(name.to_string());
+            let job = services.job.create_job(job).await?;
+            job_ids.push(job.id());
+        }
+
+        for (index, name) in DEMO_USERS.iter().enumerate() {
+            let mut user = User::new();
+            user.set_name(name.to_string());
+            user.set_job_id(job_ids[index % job_ids.len()]);
+            user.set_organization_id(organization_ids[index % organization_ids.len()]);
+            user.set_email(format!(
+                "{}@example.com",
+                name.to_lowercase().replace(' ', ".")
+            ));
+            user.set_phone(format!("+1 555 01{:02}", index));
+            services.user.create_user(user).await?;
+        }
+
+        Ok(database)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for MemoryDatabase {
+    fn repositories(&self) -> Repositories {
+        Repositories {
+            user: Arc::new(UserMemoryRepository::new(self.clone())),
+            job: Arc::new(JobMemoryRepository::new(self.clone())),
+            organization: Arc::new(OrganizationMemoryRepository::new(self.clone())),
+            audit: Arc::new(AuditMemoryRepository::new(self.clone())),
+        }
+    }
+
+    async fn begin(&self) -> Result<Arc<dyn Transaction>, RepositoryError> {
+        let tables = self.read().clone();
+        let target = Target {
+            database: self.clone(),
+            revision: tables.revision,
+            audit_entries: tables.audit_log.len(),
+        };
+        Ok(Arc::new(MemoryTransaction {
+            working_copy: MemoryDatabase {
+                tables: Arc::new(RwLock::new(tables)),
+            },
+            target: Some(target),
+        }))
+    }
+}
+
+/// Collects writes in a private copy of the tables and publishes them on
+/// commit, unless something else wrote to the database in the meantime.
+pub struct MemoryTransaction {
+    working_copy: MemoryDatabase,
+    /// `None` for a handle that joined an outer transaction.
+    target: Option<Target>,
+}
+
+/// The database a transaction publishes to, as it was when it began.
+struct Target {
+    database: MemoryDatabase,
+    revision: u64,
+    /// Entries recorded outside the transaction after this are kept, and the
+    /// transaction's own entries are added after them.
+    audit_entries: usize,
+}
+
+#[async_trait]
+impl UnitOfWork for MemoryTransaction {
+    fn repositories(&self) -> Repositories {
+        self.working_copy.repositories()
+    }
+
+    async fn begin(&self) -> Result<Arc<dyn Transaction>, RepositoryError> {
+        Ok(Arc::new(MemoryTransaction {
+            working_copy: self.working_copy.clone(),
+            target: None,
+        }))
+    }
+}
+
+#[async_trait]
+impl Transaction for MemoryTransaction {
+    async fn commit(&self) -> Result<(), RepositoryError> {
+        let Some(target) = &self.target else {
+            return Ok(());
+        };
+
+        let mut tables = target.database.write();
+        if tables.revision != target.revision {
+            return Err(RepositoryError::Conflict);
+        }
+        let working_copy = self.working_copy.read();
+        let mut audit_log = std::mem::take(&mut tables.audit_log);
+        for entry in &working_copy.audit_log[target.audit_entries..] {
+            let mut entry = entry.clone();
+            entry.id = audit_log.len() as i64 + 1;
+            audit_log.push(entry);
+        }
+        *tables = Tables {
+            audit_log,
+            ..working_copy.clone()
+        };
+        tables.changed();
+        Ok(())
+    }
+
+    /// The working copy is simply left unpublished.
+    async fn rollback(&self) -> Result<(), RepositoryError> {
+        Ok(())
+    }
+}
+
+const DEMO_ORGANIZATIONS: [&str; 3] = ["Engineering", "Operations", "Sales"];
+
+const DEMO_JOBS: [&str; 4] = [
+    "Account Manager",
+    "Developer",
+    "Support Engineer",
+    "Team Lead",
+];
+
+const DEMO_USERS: [&str; 10] = [
+    "Ada Lovelace",
+    "Alan Turing",
+    "Barbara Liskov",
+    "Dennis Ritchie",
+    "Edsger Dijkstra",
+    "Frances Allen",
+    "Grace Hopper",
+    "Ken Thompson",
+    "Margaret Hamilton",
+    "Niklaus Wirth",
+];
+
+pub struct UserMemoryRepository {
+    database: MemoryDatabase,
+}
+
+impl UserMemoryRepository {
+    pub fn new(database: MemoryDatabase) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl UserRepository for UserMemoryRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<User>, RepositoryError> {
+        Ok(self.database.read().users.get(&id).cloned())
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>, RepositoryError> {
+        Ok(sorted_by_name(self.database.read().users.values()))
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<User>, RepositoryError> {
+        Ok(search_by_name(self.database.read().users.values(), query))
+    }
+
+    async fn find_page(&self, query: &UserQuery) -> Result<UserPage, RepositoryError> {
+        let tables = self.database.read();
+        let name_filter = query.name_filter().map(str::to_lowercase);
+        let mut matches: Vec<(String, &User)> = tables
+            .users
+            .values()
+            .filter(|user| {
+                query
+                    .organization_id
+                    .is_none_or(|id| id == user.organization_id())
+                    && query.job_id.is_none_or(|id| id == user.job_id())
+                    && name_filter
+                        .as_ref()
+                        .is_none_or(|name| user.name().to_lowercase().contains(name))
+            })
+            .map(|user| (tables.sort_value(user, query.sort), user))
+            .collect();
+        let total = matches.len() as i64;
+
+        matches.sort_by(|(a_value, a), (b_value, b)| {
+            let ordering = a_value.cmp(b_value).then(a.id().cmp(&b.id()));
+            match query.direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+        if let Some(after) = &query.after {
+            let after = (after.sort_value.as_str(), after.id);
+            matches.retain(|(value, user)| {
+                let position = (value.as_str(), user.id());
+                match query.direction {
+                    SortDirection::Ascending => position > after,
+                    SortDirection::Descending => position < after,
+                }
+            });
+        }
+
+        let limit = query.limit.max(0) as usize;
+        let next = match matches.get(limit.wrapping_sub(1)) {
+            Some((sort_value, user)) if matches.len() > limit => Some(UserCursor {
+                sort_value: sort_value.clone(),
+                id: user.id(),
+            }),
+            _ => None,
+        };
+        let users = matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, user)| user.clone())
+            .collect();
+
+        Ok(UserPage { users, total, next })
+    }
+
+    async fn create(&self, user: &User) -> Result<User, RepositoryError> {
+        let mut tables = self.database.write();
+        tables.check_user_references(user)?;
+
+        tables.next_user_id += 1;
+        let mut saved_user = user.clone();
+        saved_user.set_id(tables.next_user_id);
+        saved_user.set_version(1);
+        saved_user.clear_errors();
+        tables.users.insert(saved_user.id(), saved_user.clone());
+        tables.changed();
+        Ok(saved_user)
+    }
+
+    async fn restore(&self, user: &User) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        if tables.users.contains_key(&user.id()) {
+            return Err(unique_violation("users.id"));
+        }
+        tables.check_user_references(user)?;
+
+        let mut restored_user = user.clone();
+        restored_user.set_version(user.version().max(1));
+        restored_user.clear_errors();
+        tables.next_user_id = tables.next_user_id.max(user.id());
+        tables.users.insert(user.id(), restored_user);
+        tables.changed();
+        Ok(())
+    }
+
+    async fn update(&self, user: &User) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        let version = check_version(tables.users.get(&user.id()), user)?;
+        tables.check_user_references(user)?;
+
+        let mut updated_user = user.clone();
+        updated_user.set_version(version + 1);
+        updated_user.clear_errors();
+        tables.users.insert(user.id(), updated_user);
+        tables.changed();
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        tables.users.remove(&id).ok_or(RepositoryError::NotFound)?;
+        tables.changed();
+        Ok(())
+    }
+}
+
+pub struct JobMemoryRepository {
+    database: MemoryDatabase,
+}
+
+impl JobMemoryRepository {
+    pub fn new(database: MemoryDatabase) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl JobRepository for JobMemoryRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Job>, RepositoryError> {
+        Ok(self.database.read().jobs.get(&id).cloned())
+    }
+
+    async fn find_all(&self) -> Result<Vec<Job>, RepositoryError> {
+        Ok(sorted_by_name(self.database.read().jobs.values()))
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Job>, RepositoryError> {
+        Ok(search_by_name(self.database.read().jobs.values(), query))
+    }
+
+    async fn create(&self, job: &Job) -> Result<Job, RepositoryError> {
+        let mut tables = self.database.write();
+        check_unique_name(&tables.jobs, job, "jobs.name")?;
+
+        tables.next_job_id += 1;
+        let mut saved_job = job.clone();
+        saved_job.set_id(tables.next_job_id);
+        saved_job.set_version(1);
+        saved_job.clear_errors();
+        tables.jobs.insert(saved_job.id(), saved_job.clone());
+        tables.changed();
+        Ok(saved_job)
+    }
+
+    async fn restore(&self, job: &Job) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        if tables.jobs.contains_key(&job.id()) {
+            return Err(unique_violation("jobs.id"));
+        }
+        check_unique_name(&tables.jobs, job, "jobs.name")?;
+
+        let mut restored_job = job.clone();
+        restored_job.set_version(job.version().max(1));
+        restored_job.clear_errors();
+        tables.next_job_id = tables.next_job_id.max(job.id());
+        tables.jobs.insert(job.id(), restored_job);
+        tables.changed();
+        Ok(())
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        let version = check_version(tables.jobs.get(&job.id()), job)?;
+        check_unique_name(&tables.jobs, job, "jobs.name")?;
+
+        let mut updated_job = job.clone();
+        updated_job.set_version(version + 1);
+        updated_job.clear_errors();
+        tables.jobs.insert(job.id(), updated_job);
+        tables.changed();
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        if !tables.jobs.contains_key(&id) {
+            return Err(RepositoryError::NotFound);
+        }
+        if tables.users.values().any(|user| user.job_id() == id) {
+            return Err(foreign_key_violation());
+        }
+        tables.jobs.remove(&id);
+        tables.changed();
+        Ok(())
+    }
+}
+
+pub struct OrganizationMemoryRepository {
+    database: MemoryDatabase,
+}
+
+impl OrganizationMemoryRepository {
+    pub fn new(database: MemoryDatabase) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl OrganizationRepository for OrganizationMemoryRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Organization>, RepositoryError> {
+        Ok(self.database.read().organizations.get(&id).cloned())
+    }
+
+    async fn find_all(&self) -> Result<Vec<Organization>, RepositoryError> {
+        Ok(sorted_by_name(self.database.read().organizations.values()))
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Organization>, RepositoryError> {
+        Ok(search_by_name(
+            self.database.read().organizations.values(),
+            query,
+        ))
+    }
+
+    async fn create(&self, org: &Organization) -> Result<Organization, RepositoryError> {
+        let mut tables = self.database.write();
+        check_unique_name(&tables.organizations, org, "organizations.name")?;
+
+        tables.next_organization_id += 1;
+        let mut saved_org = org.clone();
+        saved_org.set_id(tables.next_organization_id);
+        saved_org.set_version(1);
+        saved_org.clear_errors();
+        tables
+            .organizations
+            .insert(saved_org.id(), saved_org.clone());
+        tables.changed();
+        Ok(saved_org)
+    }
+
+    async fn restore(&self, org: &Organization) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        if tables.organizations.contains_key(&org.id()) {
+            return Err(unique_violation("organizations.id"));
+        }
+        check_unique_name(&tables.organizations, org, "organizations.name")?;
+
+        let mut restored_org = org.clone();
+        restored_org.set_version(org.version().max(1));
+        restored_org.clear_errors();
+        tables.next_organization_id = tables.next_organization_id.max(org.id());
+        tables.organizations.insert(org.id(), restored_org);
+        tables.changed();
+        Ok(())
+    }
+
+    async fn update(&self, org: &Organization) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        let version = check_version(tables.organizations.get(&org.id()), org)?;
+        check_unique_name(&tables.organizations, org, "organizations.name")?;
+
+        let mut updated_org = org.clone();
+        updated_org.set_version(version + 1);
+        updated_org.clear_errors();
+        tables.organizations.insert(org.id(), updated_org);
+        tables.changed();
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let mut tables = self.database.write();
+        if !tables.organizations.contains_key(&id) {
+            return Err(RepositoryError::NotFound);
+        }
+        if tables
+            .users
+            .values()
+            .any(|user| user.organization_id() == id)
+        {
+            return Err(foreign_key_violation());
+        }
+        tables.organizations.remove(&id);
+        tables.changed();
+        Ok(())
+    }
+}
+
+pub struct AuditMemoryRepository {
+    database: MemoryDatabase,
+}
+
+impl AuditMemoryRepository {
+    pub fn new(database: MemoryDatabase) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for AuditMemoryRepository {
+    async fn record(&self, entry: &AuditEntry) -> Result<AuditEntry, RepositoryError> {
+        let mut tables = self.database.write();
+        let mut saved_entry = entry.clone();
+        saved_entry.id = tables.audit_log.len() as i64 + 1;
+        tables.audit_log.push(saved_entry.clone());
+        Ok(saved_entry)
+    }
+
+    async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let tables = self.database.read();
+        let mut entries: Vec<AuditEntry> = tables
+            .audit_log
+            .iter()
+            .filter(|entry| {
+                let date = entry.timestamp.date_naive();
+                filter.entity_type.is_none_or(|e| e == entry.entity_type)
+                    && filter.entity_id.is_none_or(|id| id == entry.entity_id)
+                    && filter.action.is_none_or(|a| a == entry.action)
+                    && filter.from.is_none_or(|from| date >= from)
+                    && filter.to.is_none_or(|to| date <= to)
+            })
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+        Ok(entries)
+    }
+}
+
+fn sorted_by_name<'a, T: Entity + 'a>(items: impl Iterator<Item = &'a T>) -> Vec<T> {
+    let mut items: Vec<T> = items.cloned().collect();
+    items.sort_by(|a, b| a.name().cmp(b.name()));
+    items
+}
+
+/// Mirrors the FTS5 search: every query word must start a word of the name.
+/// Names matching more words exactly, then shorter names, rank first.
+fn search_by_name<'a, T: Entity + 'a>(items: impl Iterator<Item = &'a T>, query: &str) -> Vec<T> {
+    let terms = words(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<(usize, &T)> = items
+        .filter_map(|item| {
+            let name_words = words(item.name());
+            let mut exact = 0;
+            for term in &terms {
+                if name_words.contains(term) {
+                    exact += 1;
+                } else if !name_words
+                    .iter()
+                    .any(|word| word.starts_with(term.as_str()))
+                {
+                    return None;
+                }
+            }
+            Some((exact, item))
+        })
+        .collect();
+    matches.sort_by(|(a_exact, a), (b_exact, b)| {
+        b_exact
+            .cmp(a_exact)
+            .then(a.name().len().cmp(&b.name().len()))
+            .then(a.name().cmp(b.name()))
+    });
+    matches
+        .into_iter()
+        .take(SEARCH_LIMIT as usize)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn check_unique_name<T: Entity>(
+    table: &BTreeMap<i64, T>,
+    entity: &T,
+    column: &str,
+) -> Result<(), RepositoryError> {
+    if table
+        .values()
+        .any(|existing| existing.id() != entity.id() && existing.name() == entity.name())
+    {
+        return Err(unique_violation(column));
+    }
+    Ok(())
+}
+
+/// Mirrors the `WHERE id = ? AND version = ?` check of the SQLite repositories.
+fn check_version<T: Entity>(stored: Option<&T>, entity: &T) -> Result<i64, RepositoryError> {
+    match stored {
+        None => Err(RepositoryError::NotFound),
+        Some(stored) if stored.version() != entity.version() => Err(RepositoryError::Conflict),
+        Some(stored) => Ok(stored.version()),
+    }
+}
+
+fn unique_violation(column: &str) -> RepositoryError {
+    RepositoryError::ConstraintViolation(format!("UNIQUE constraint failed: {}", column))
+}
+
+fn foreign_key_violation() -> RepositoryError {
+    RepositoryError::ConstraintViolation("FOREIGN KEY constraint failed".to_string())
+}
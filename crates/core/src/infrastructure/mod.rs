@@ -0,0 +1,40 @@
+mod backup;
+mod change_tracking;
+mod csv_import;
+mod data_generator;
+mod database;
+mod export;
+mod integrity;
+mod ldif;
+mod memory_repository;
+mod org_chart;
+mod snapshot;
+mod unit_of_work;
+mod vcard;
+
+pub use backup::BackupError;
+pub use change_tracking::ChangeCounters;
+pub use csv_import::{
+    import_users, write_rejected, ColumnMapping, CsvColumn, CsvFile, ImportError, ImportField,
+    ImportPreview, ImportRow, ImportSummary, Reference,
+};
+pub use data_generator::{generate_data, GeneratedData, GeneratorOptions};
+pub use database::{fts_query, get_database_path, map_sqlx_error, Database};
+pub use export::{ExportCell, ExportError, ExportFormat, ExportTable};
+pub use integrity::{IntegrityReport, OrphanedUser};
+pub use ldif::{
+    apply_ldif, export_ldif, parse_ldif, LdifChange, LdifEntry, LdifError, LdifField, LdifOptions,
+    LdifPlan, LdifSkip, LdifSummary,
+};
+pub use memory_repository::MemoryDatabase;
+pub use org_chart::{org_chart, ChartCollapse, ChartFormat, ChartNode, ChartNodeKind, OrgChart};
+pub use snapshot::{
+    Snapshot, SnapshotError, SnapshotRecord, SnapshotSummary, SnapshotUser, SNAPSHOT_FORMAT,
+    SNAPSHOT_VERSION,
+};
+pub use vcard::{export_contacts, ContactCards, ContactScope};
+pub mod audit_repository;
+pub mod audited_repository;
+pub mod job_repository;
+pub mod organization_repository;
+pub mod user_repository;
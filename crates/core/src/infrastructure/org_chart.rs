@@ -0,0 +1,404 @@
+//! Organization charts, written as Graphviz DOT for further editing or as a
+//! ready-to-share SVG drawn without Graphviz.
+//!
+//! Users have no manager, so the only reporting lines are from each
+//! organization to its people; the chart shows every organization under one
+//! root with its users, or its jobs, listed below it. Users whose
+//! organization no longer exists are listed under "Unassigned".
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::domain::{CommandError, Entity, Job, Organization, Services, User};
+
+const NODE_WIDTH: usize = 180;
+const NODE_HEIGHT: usize = 40;
+/// Listed nodes are indented this much below their organization.
+const INDENT: usize = 20;
+const COLUMN_GAP: usize = 20;
+const ROW_GAP: usize = 12;
+const LEVEL_GAP: usize = 40;
+const MARGIN: usize = 20;
+/// Roughly how wide a character of the 12px labels is.
+const CHARACTER_WIDTH: usize = 7;
+
+/// How much detail the chart shows below each organization.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartCollapse {
+    /// Every user, with their job.
+    #[default]
+    None,
+    /// One node per job, with how many users have it.
+    Job,
+    /// Only the organizations, with how many users are in them.
+    Organization,
+}
+
+impl ChartCollapse {
+    pub const ALL: [ChartCollapse; 3] = [
+        ChartCollapse::None,
+        ChartCollapse::Job,
+        ChartCollapse::Organization,
+    ];
+}
+
+impl std::fmt::Display for ChartCollapse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChartCollapse::None => "Every user",
+            ChartCollapse::Job => "Collapse by job",
+            ChartCollapse::Organization => "Collapse by organization",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartFormat {
+    Dot,
+    Svg,
+}
+
+impl ChartFormat {
+    /// Picks the format from the file extension, defaulting to SVG.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension)
+                if extension.eq_ignore_ascii_case("dot")
+                    || extension.eq_ignore_ascii_case("gv") =>
+            {
+                ChartFormat::Dot
+            }
+            _ => ChartFormat::Svg,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartNodeKind {
+    Root,
+    Organization,
+    Job,
+    User,
+}
+
+impl ChartNodeKind {
+    fn class(&self) -> &'static str {
+        match self {
+            ChartNodeKind::Root => "root",
+            ChartNodeKind::Organization => "organization",
+            ChartNodeKind::Job => "job",
+            ChartNodeKind::User => "user",
+        }
+    }
+
+    fn fill(&self) -> &'static str {
+        match self {
+            ChartNodeKind::Root => "#c9d6ea",
+            ChartNodeKind::Organization => "#dde7f3",
+            ChartNodeKind::Job => "#eef1e6",
+            ChartNodeKind::User => "#f7f7f7",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartNode {
+    pub kind: ChartNodeKind,
+    /// Unique within the chart, and usable as a DOT node id.
+    pub key: String,
+    pub label: String,
+    /// A second line, such as the job of a user or a head count.
+    pub detail: String,
+    pub children: Vec<ChartNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgChart {
+    pub root: ChartNode,
+    pub users: usize,
+}
+
+pub async fn org_chart(
+    services: &Services,
+    collapse: ChartCollapse,
+) -> Result<OrgChart, CommandError> {
+    let jobs = services.job.get_all_jobs().await?;
+    let organizations = services.organization.get_all_organizations().await?;
+    let users = services.user.get_all_users().await?;
+    Ok(OrgChart::build(&jobs, &organizations, &users, collapse))
+}
+
+impl OrgChart {
+    pub fn build(
+        jobs: &[Job],
+        organizations: &[Organization],
+        users: &[User],
+        collapse: ChartCollapse,
+    ) -> Self {
+        let job_names: HashMap<i64, &str> = jobs.iter().map(|job| (job.id(), job.name())).collect();
+        let mut members: HashMap<i64, Vec<&User>> = HashMap::new();
+        for user in users {
+            members
+                .entry(user.organization_id())
+                .or_default()
+                .push(user);
+        }
+
+        let job_name = |user: &User| {
+            job_names
+                .get(&user.job_id())
+                .copied()
+                .unwrap_or_default()
+                .to_string()
+        };
+        // `key` identifies the group in the keys of its job nodes.
+        let group = |key: String, label: String, mut members: Vec<&User>| {
+            members.sort_by(|a, b| a.name().cmp(b.name()).then(a.id().cmp(&b.id())));
+            let children = match collapse {
+                ChartCollapse::None => members
+                    .iter()
+                    .map(|user| ChartNode {
+                        kind: ChartNodeKind::User,
+                        key: format!("user_{}", user.id()),
+                        label: user.name().to_string(),
+                        detail: job_name(user),
+                        children: Vec::new(),
+                    })
+                    .collect(),
+                ChartCollapse::Job => {
+                    let mut by_job: BTreeMap<(String, i64), usize> = BTreeMap::new();
+                    for user in &members {
+                        *by_job.entry((job_name(user), user.job_id())).or_default() += 1;
+                    }
+                    by_job
+                        .into_iter()
+                        .map(|((name, job_id), count)| ChartNode {
+                            kind: ChartNodeKind::Job,
+                            key: format!("job_{}_{}", key, job_id),
+                            label: name,
+                            detail: head_count(count),
+                            children: Vec::new(),
+                        })
+                        .collect()
+                }
+                ChartCollapse::Organization => Vec::new(),
+            };
+            ChartNode {
+                kind: ChartNodeKind::Organization,
+                key: format!("organization_{}", key),
+                label,
+                detail: head_count(members.len()),
+                children,
+            }
+        };
+
+        let mut organizations: Vec<&Organization> = organizations.iter().collect();
+        organizations.sort_by(|a, b| a.name().cmp(b.name()).then(a.id().cmp(&b.id())));
+        let mut children: Vec<ChartNode> = organizations
+            .into_iter()
+            .map(|organization| {
+                group(
+                    organization.id().to_string(),
+                    organization.name().to_string(),
+                    members.remove(&organization.id()).unwrap_or_default(),
+                )
+            })
+            .collect();
+        // Users whose organization is gone, such as orphans restored from an
+        // old backup, are still counted, so they are drawn too.
+        let unassigned: Vec<&User> = members.into_values().flatten().collect();
+        if !unassigned.is_empty() {
+            children.push(group(
+                "unassigned".to_string(),
+                "Unassigned".to_string(),
+                unassigned,
+            ));
+        }
+
+        Self {
+            root: ChartNode {
+                kind: ChartNodeKind::Root,
+                key: "root".to_string(),
+                label: "All organizations".to_string(),
+                detail: head_count(users.len()),
+                children,
+            },
+            users: users.len(),
+        }
+    }
+
+    pub fn render(&self, format: ChartFormat) -> String {
+        match format {
+            ChartFormat::Dot => self.to_dot(),
+            ChartFormat::Svg => self.to_svg(),
+        }
+    }
+
+    /// Laid out left to right, so long lists of users stack rather than
+    /// spreading across the page.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph org_chart {\n");
+        out.push_str("    graph [rankdir=LR, fontname=\"Helvetica\"];\n");
+        out.push_str(
+            "    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\", fontsize=10];\n",
+        );
+        out.push_str("    edge [arrowhead=none, color=\"#8a94a6\"];\n");
+        dot_node(&mut out, &self.root);
+        out.push_str("}\n");
+        out
+    }
+
+    /// The organizations side by side below the root, each with its users
+    /// or jobs listed underneath.
+    pub fn to_svg(&self) -> String {
+        let organizations = &self.root.children;
+        let columns = organizations.len().max(1);
+        let width = 2 * MARGIN + columns * NODE_WIDTH + (columns - 1) * COLUMN_GAP;
+        let rows = organizations
+            .iter()
+            .map(|organization| organization.children.len())
+            .max()
+            .unwrap_or(0);
+        let organization_y = MARGIN + NODE_HEIGHT + LEVEL_GAP;
+        let height = organization_y + NODE_HEIGHT + rows * (NODE_HEIGHT + ROW_GAP) + MARGIN;
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+             viewBox=\"0 0 {0} {1}\" font-family=\"Helvetica, Arial, sans-serif\">",
+            width, height
+        );
+        out.push_str("<style>\n");
+        for kind in [
+            ChartNodeKind::Root,
+            ChartNodeKind::Organization,
+            ChartNodeKind::Job,
+            ChartNodeKind::User,
+        ] {
+            let _ = writeln!(
+                out,
+                ".{} rect {{ fill: {}; stroke: #8a94a6; }}",
+                kind.class(),
+                kind.fill()
+            );
+        }
+        out.push_str(".label { font-size: 12px; font-weight: bold; fill: #1f2933; }\n");
+        out.push_str(".detail { font-size: 11px; fill: #52606d; }\n");
+        out.push_str("path { fill: none; stroke: #8a94a6; }\n");
+        out.push_str("</style>\n");
+
+        let root_x = (width - NODE_WIDTH) / 2;
+        svg_node(&mut out, &self.root, root_x, MARGIN, NODE_WIDTH);
+        let bar_y = MARGIN + NODE_HEIGHT + LEVEL_GAP / 2;
+        for (column, organization) in organizations.iter().enumerate() {
+            let x = MARGIN + column * (NODE_WIDTH + COLUMN_GAP);
+            let _ = writeln!(
+                out,
+                "<path d=\"M{} {} V{} H{} V{}\"/>",
+                root_x + NODE_WIDTH / 2,
+                MARGIN + NODE_HEIGHT,
+                bar_y,
+                x + NODE_WIDTH / 2,
+                organization_y
+            );
+            svg_node(&mut out, organization, x, organization_y, NODE_WIDTH);
+
+            let line_x = x + INDENT / 2;
+            for (row, child) in organization.children.iter().enumerate() {
+                let y = organization_y + NODE_HEIGHT + ROW_GAP + row * (NODE_HEIGHT + ROW_GAP);
+                let _ = writeln!(
+                    out,
+                    "<path d=\"M{} {} V{} H{}\"/>",
+                    line_x,
+                    y - ROW_GAP - if row == 0 { 0 } else { NODE_HEIGHT / 2 },
+                    y + NODE_HEIGHT / 2,
+                    x + INDENT
+                );
+                svg_node(&mut out, child, x + INDENT, y, NODE_WIDTH - INDENT);
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+fn head_count(count: usize) -> String {
+    format!("{} user{}", count, if count == 1 { "" } else { "s" })
+}
+
+fn dot_node(out: &mut String, node: &ChartNode) {
+    let _ = writeln!(
+        out,
+        "    {} [label=\"{}\\n{}\", fillcolor=\"{}\"];",
+        node.key,
+        dot_escape(&node.label),
+        dot_escape(&node.detail),
+        node.kind.fill()
+    );
+    for child in &node.children {
+        dot_node(out, child);
+        let _ = writeln!(out, "    {} -> {};", node.key, child.key);
+    }
+}
+
+fn dot_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\n', '\r'], " ")
+}
+
+/// Draws a box with the label and detail, cut short to fit. The full text
+/// is kept as a tooltip.
+fn svg_node(out: &mut String, node: &ChartNode, x: usize, y: usize, width: usize) {
+    let characters = (width - 16) / CHARACTER_WIDTH;
+    let _ = writeln!(
+        out,
+        "<g class=\"{}\"><title>{}\n{}</title>\
+         <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\"/>\
+         <text class=\"label\" x=\"{}\" y=\"{}\">{}</text>\
+         <text class=\"detail\" x=\"{}\" y=\"{}\">{}</text></g>",
+        node.kind.class(),
+        xml_escape(&node.label),
+        xml_escape(&node.detail),
+        x,
+        y,
+        width,
+        NODE_HEIGHT,
+        x + 8,
+        y + 17,
+        xml_escape(&truncate(&node.label, characters)),
+        x + 8,
+        y + 32,
+        xml_escape(&truncate(&node.detail, characters)),
+    );
+}
+
+fn truncate(value: &str, characters: usize) -> String {
+    if value.chars().count() <= characters {
+        return value.to_string();
+    }
+    let mut cut: String = value.chars().take(characters.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_control() && c != '\n' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,205 @@
+use super::unit_of_work::SqliteExecutor;
+use super::{fts_query, map_sqlx_error};
+use crate::domain::{
+    repositories::{OrganizationRepository, RepositoryError, SEARCH_LIMIT},
+    Entity, Organization,
+};
+use async_trait::async_trait;
+
+pub struct OrganizationSqliteRepository {
+    executor: SqliteExecutor,
+}
+
+impl OrganizationSqliteRepository {
+    pub fn new(executor: SqliteExecutor) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl OrganizationRepository for OrganizationSqliteRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Organization>, RepositoryError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, name, version
+            FROM organizations
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(row.map(|r| {
+            let mut organization = Organization::new();
+            organization.set_id(r.id);
+            organization.set_name(r.name);
+            organization.set_version(r.version);
+            organization
+        }))
+    }
+
+    async fn find_all(&self) -> Result<Vec<Organization>, RepositoryError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id as "id!", name, version
+            FROM organizations
+            ORDER BY name
+            "#
+        )
+        .fetch_all(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let mut organization = Organization::new();
+                organization.set_id(r.id);
+                organization.set_name(r.name);
+                organization.set_version(r.version);
+                organization
+            })
+            .collect())
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Organization>, RepositoryError> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT o.id as "id!", o.name as "name!", o.version as "version!"
+            FROM search_index
+            JOIN organizations o ON o.id = search_index.entity_id
+            WHERE search_index MATCH ? AND search_index.entity_type = 'organization'
+            ORDER BY search_index.rank
+            LIMIT ?
+            "#,
+            query,
+            SEARCH_LIMIT
+        )
+        .fetch_all(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let mut organization = Organization::new();
+                organization.set_id(r.id);
+                organization.set_name(r.name);
+                organization.set_version(r.version);
+                organization
+            })
+            .collect())
+    }
+
+    async fn create(&self, organization: &Organization) -> Result<Organization, RepositoryError> {
+        let organization_name = organization.name();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO organizations (name)
+            VALUES (?)
+            "#,
+            organization_name
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        let mut saved_organization = organization.clone();
+        saved_organization.set_id(result.last_insert_rowid());
+        saved_organization.set_version(1);
+        Ok(saved_organization)
+    }
+
+    async fn restore(&self, organization: &Organization) -> Result<(), RepositoryError> {
+        let organization_id = organization.id();
+        let organization_name = organization.name();
+        let version = organization.version().max(1);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO organizations (id, name, version)
+            VALUES (?, ?, ?)
+            "#,
+            organization_id,
+            organization_name,
+            version
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn update(&self, organization: &Organization) -> Result<(), RepositoryError> {
+        let organization_name = organization.name();
+        let organization_id = organization.id();
+        let version = organization.version();
+
+        let rows_affected = sqlx::query!(
+            r#"
+            UPDATE organizations
+            SET name = ?, version = version + 1
+            WHERE id = ? AND version = ?
+            "#,
+            organization_name,
+            organization_id,
+            version
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return match self.find_by_id(organization_id).await? {
+                Some(_) => Err(RepositoryError::Conflict),
+                None => Err(RepositoryError::NotFound),
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let mut connection = self.executor.acquire().await?;
+        // Checked up front rather than left to ON DELETE RESTRICT: on a pooled
+        // connection a DELETE refused by the constraint could still take
+        // effect once the users were gone.
+        let in_use: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE organization_id = ?)")
+                .bind(id)
+                .fetch_one(&mut *connection)
+                .await
+                .map_err(map_sqlx_error)?;
+        if in_use {
+            return Err(RepositoryError::ConstraintViolation(
+                "FOREIGN KEY constraint failed".to_string(),
+            ));
+        }
+
+        let rows_affected = sqlx::query!(
+            r#"
+            DELETE FROM organizations WHERE id = ?
+            "#,
+            id
+        )
+        .execute(&mut *connection)
+        .await
+        .map_err(map_sqlx_error)?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        Ok(())
+    }
+}
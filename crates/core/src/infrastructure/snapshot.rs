@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::domain::{CommandError, DomainEntity, Entity, Job, Organization, Services, User};
+
+/// Identifies snapshot files, so importing some other JSON file fails early.
+pub const SNAPSHOT_FORMAT: &str = "iced-user-management";
+/// Bumped whenever the layout changes in a way older readers cannot follow.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Could not read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Could not write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("{0} is not a valid snapshot: {1}")]
+    Parse(PathBuf, serde_json::Error),
+    #[error("{0} is not a snapshot file")]
+    UnknownFormat(PathBuf),
+    #[error("Snapshot version {0} is newer than this app supports ({SNAPSHOT_VERSION})")]
+    UnsupportedVersion(u32),
+    #[error("Snapshots can only be imported into an empty database; create a new one first")]
+    NotEmpty,
+    #[error("User {user} refers to {entity} {id}, which is not in the snapshot")]
+    MissingReference {
+        user: i64,
+        entity: DomainEntity,
+        id: i64,
+    },
+    #[error(transparent)]
+    Command(#[from] CommandError),
+}
+
+/// A job or organization in a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A user in a snapshot. The ids refer to records in the same snapshot,
+/// not to rows in any particular database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotUser {
+    pub id: i64,
+    pub name: String,
+    pub job_id: i64,
+    pub organization_id: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub phone: String,
+}
+
+/// Every organization, job and user, ordered by id so exporting the same
+/// data twice gives the same file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: String,
+    pub version: u32,
+    pub organizations: Vec<SnapshotRecord>,
+    pub jobs: Vec<SnapshotRecord>,
+    pub users: Vec<SnapshotUser>,
+}
+
+/// The number of records in a snapshot that was written or imported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub organizations: usize,
+    pub jobs: usize,
+    pub users: usize,
+}
+
+impl std::fmt::Display for SnapshotSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} organizations, {} jobs and {} users",
+            self.organizations, self.jobs, self.users
+        )
+    }
+}
+
+impl Snapshot {
+    /// Reads everything in one transaction, so a write from elsewhere cannot
+    /// leave users pointing at records the snapshot does not have.
+    pub async fn capture(services: &Services) -> Result<Self, CommandError> {
+        let (services, transaction) = services.begin().await?;
+        let tables = async {
+            let organizations = services.organization.get_all_organizations().await?;
+            let jobs = services.job.get_all_jobs().await?;
+            let users = services.user.get_all_users().await?;
+            Ok::<_, CommandError>((organizations, jobs, users))
+        }
+        .await;
+        // Nothing was written, so the transaction is rolled back either way.
+        transaction.rollback().await?;
+        let (organizations, jobs, users) = tables?;
+
+        let mut organizations: Vec<SnapshotRecord> = organizations.iter().map(record).collect();
+        let mut jobs: Vec<SnapshotRecord> = jobs.iter().map(record).collect();
+        let mut users: Vec<SnapshotUser> = users
+            .iter()
+            .map(|user| SnapshotUser {
+                id: user.id(),
+                name: user.name().to_string(),
+                job_id: user.job_id(),
+                organization_id: user.organization_id(),
+                email: user.email().to_string(),
+                phone: user.phone().to_string(),
+            })
+            .collect();
+        organizations.sort_by_key(|organization| organization.id);
+        jobs.sort_by_key(|job| job.id);
+        users.sort_by_key(|user| user.id);
+
+        Ok(Self {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            organizations,
+            jobs,
+            users,
+        })
+    }
+
+    pub fn summary(&self) -> SnapshotSummary {
+        SnapshotSummary {
+            organizations: self.organizations.len(),
+            jobs: self.jobs.len(),
+            users: self.users.len(),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| SnapshotError::Read(path.to_path_buf(), e))?;
+        let snapshot: Snapshot = serde_json::from_str(&contents)
+            .map_err(|e| SnapshotError::Parse(path.to_path_buf(), e))?;
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(SnapshotError::UnknownFormat(path.to_path_buf()));
+        }
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SnapshotError> {
+        let error = |e| SnapshotError::Write(path.to_path_buf(), e);
+        let mut contents =
+            serde_json::to_string_pretty(self).map_err(|e| error(std::io::Error::other(e)))?;
+        contents.push('\n');
+        std::fs::write(path, contents).map_err(error)
+    }
+
+    /// Recreates the snapshot in an empty database, in one transaction.
+    /// Records get new ids there; users are pointed at the new ids of their
+    /// job and organization.
+    pub async fn restore(&self, services: &Services) -> Result<SnapshotSummary, SnapshotError> {
+        let (services, transaction) = services.begin().await.map_err(CommandError::from)?;
+        if let Err(e) = self.restore_into(&services).await {
+            // The error that stopped the restore is the one to report.
+            let _ = transaction.rollback().await;
+            return Err(e);
+        }
+        transaction.commit().await.map_err(CommandError::from)?;
+        Ok(self.summary())
+    }
+
+    async fn restore_into(&self, services: &Services) -> Result<(), SnapshotError> {
+        let is_empty = services
+            .organization
+            .get_all_organizations()
+            .await
+            .map_err(CommandError::from)?
+            .is_empty()
+            && services
+                .job
+                .get_all_jobs()
+                .await
+                .map_err(CommandError::from)?
+                .is_empty()
+            && services
+                .user
+                .get_all_users()
+                .await
+                .map_err(CommandError::from)?
+                .is_empty();
+        if !is_empty {
+            return Err(SnapshotError::NotEmpty);
+        }
+
+        let mut organization_ids = HashMap::new();
+        for snapshot_organization in &self.organizations {
+            let mut organization = Organization::new();
+            organization.set_name(snapshot_organization.name.clone());
+            let created = services
+                .organization
+                .create_organization(organization)
+                .await
+                .map_err(CommandError::from)?;
+            organization_ids.insert(snapshot_organization.id, created.id());
+        }
+
+        let mut job_ids = HashMap::new();
+        for snapshot_job in &self.jobs {
+            let mut job = Job::new();
+            job.set_name(snapshot_job.name.clone());
+            let created = services
+                .job
+                .create_job(job)
+                .await
+                .map_err(CommandError::from)?;
+            job_ids.insert(snapshot_job.id, created.id());
+        }
+
+        for snapshot_user in &self.users {
+            let missing = |entity, id| SnapshotError::MissingReference {
+                user: snapshot_user.id,
+                entity,
+                id,
+            };
+            let job_id = *job_ids
+                .get(&snapshot_user.job_id)
+                .ok_or_else(|| missing(DomainEntity::Job, snapshot_user.job_id))?;
+            let organization_id = *organization_ids
+                .get(&snapshot_user.organization_id)
+                .ok_or_else(|| {
+                    missing(DomainEntity::Organization, snapshot_user.organization_id)
+                })?;
+            let mut user = User::new();
+            user.set_name(snapshot_user.name.clone());
+            user.set_job_id(job_id);
+            user.set_organization_id(organization_id);
+            user.set_email(snapshot_user.email.clone());
+            user.set_phone(snapshot_user.phone.clone());
+            services
+                .user
+                .create_user(user)
+                .await
+                .map_err(CommandError::from)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn record<T: Entity>(entity: &T) -> SnapshotRecord {
+    SnapshotRecord {
+        id: entity.id(),
+        name: entity.name().to_string(),
+    }
+}
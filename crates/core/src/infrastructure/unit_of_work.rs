@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use smol::lock::{Mutex, MutexGuard};
+use sqlx::pool::PoolConnection;
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use crate::domain::{Repositories, RepositoryError, Transaction, UnitOfWork};
+
+use super::audit_repository::AuditSqliteRepository;
+use super::job_repository::JobSqliteRepository;
+use super::organization_repository::OrganizationSqliteRepository;
+use super::user_repository::UserSqliteRepository;
+use super::{map_sqlx_error, Database};
+
+type SharedTransaction = Arc<Mutex<Option<sqlx::Transaction<'static, Sqlite>>>>;
+
+/// Where a SQLite repository sends its statements: straight to the pool, or
+/// into a transaction shared with the other repositories of a unit of work.
+#[derive(Clone)]
+pub enum SqliteExecutor {
+    Pool(SqlitePool),
+    Transaction(SharedTransaction),
+}
+
+impl SqliteExecutor {
+    /// Borrows a connection for a single statement.
+    pub async fn acquire(&self) -> Result<SqliteConnectionGuard<'_>, RepositoryError> {
+        match self {
+            SqliteExecutor::Pool(pool) => Ok(SqliteConnectionGuard::Pool(
+                pool.acquire().await.map_err(map_sqlx_error)?,
+            )),
+            SqliteExecutor::Transaction(transaction) => {
+                let guard = transaction.lock().await;
+                if guard.is_none() {
+                    return Err(RepositoryError::DatabaseError(
+                        "Transaction already finished".to_string(),
+                    ));
+                }
+                Ok(SqliteConnectionGuard::Transaction(guard))
+            }
+        }
+    }
+}
+
+pub enum SqliteConnectionGuard<'a> {
+    Pool(PoolConnection<Sqlite>),
+    Transaction(MutexGuard<'a, Option<sqlx::Transaction<'static, Sqlite>>>),
+}
+
+impl Deref for SqliteConnectionGuard<'_> {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &SqliteConnection {
+        match self {
+            SqliteConnectionGuard::Pool(connection) => connection,
+            SqliteConnectionGuard::Transaction(guard) => {
+                guard.as_ref().expect("checked when the guard was acquired")
+            }
+        }
+    }
+}
+
+impl DerefMut for SqliteConnectionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut SqliteConnection {
+        match self {
+            SqliteConnectionGuard::Pool(connection) => connection,
+            SqliteConnectionGuard::Transaction(guard) => {
+                guard.as_mut().expect("checked when the guard was acquired")
+            }
+        }
+    }
+}
+
+fn sqlite_repositories(executor: SqliteExecutor) -> Repositories {
+    Repositories {
+        user: Arc::new(UserSqliteRepository::new(executor.clone())),
+        job: Arc::new(JobSqliteRepository::new(executor.clone())),
+        organization: Arc::new(OrganizationSqliteRepository::new(executor.clone())),
+        audit: Arc::new(AuditSqliteRepository::new(executor)),
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for Database {
+    fn repositories(&self) -> Repositories {
+        sqlite_repositories(SqliteExecutor::Pool(self.pool.clone()))
+    }
+
+    async fn begin(&self) -> Result<Arc<dyn Transaction>, RepositoryError> {
+        let transaction = self.pool.begin().await.map_err(map_sqlx_error)?;
+        Ok(Arc::new(SqliteTransaction {
+            transaction: Arc::new(Mutex::new(Some(transaction))),
+            joined: false,
+        }))
+    }
+}
+
+/// A SQLite transaction, or a handle that joined one and leaves committing to
+/// its owner.
+pub struct SqliteTransaction {
+    transaction: SharedTransaction,
+    joined: bool,
+}
+
+#[async_trait]
+impl UnitOfWork for SqliteTransaction {
+    fn repositories(&self) -> Repositories {
+        sqlite_repositories(SqliteExecutor::Transaction(self.transaction.clone()))
+    }
+
+    async fn begin(&self) -> Result<Arc<dyn Transaction>, RepositoryError> {
+        Ok(Arc::new(SqliteTransaction {
+            transaction: self.transaction.clone(),
+            joined: true,
+        }))
+    }
+}
+
+#[async_trait]
+impl Transaction for SqliteTransaction {
+    async fn commit(&self) -> Result<(), RepositoryError> {
+        if self.joined {
+            return Ok(());
+        }
+        match self.transaction.lock().await.take() {
+            Some(transaction) => transaction.commit().await.map_err(map_sqlx_error),
+            None => Err(RepositoryError::DatabaseError(
+                "Transaction already finished".to_string(),
+            )),
+        }
+    }
+
+    async fn rollback(&self) -> Result<(), RepositoryError> {
+        if self.joined {
+            return Ok(());
+        }
+        match self.transaction.lock().await.take() {
+            Some(transaction) => transaction.rollback().await.map_err(map_sqlx_error),
+            None => Ok(()),
+        }
+    }
+}
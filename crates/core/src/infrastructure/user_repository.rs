@@ -0,0 +1,331 @@
+use super::unit_of_work::SqliteExecutor;
+use super::{fts_query, map_sqlx_error};
+use crate::domain::{
+    repositories::{RepositoryError, UserRepository, SEARCH_LIMIT},
+    Entity, SortDirection, User, UserCursor, UserPage, UserQuery, UserSortKey,
+};
+use async_trait::async_trait;
+use sqlx::{QueryBuilder, Row, Sqlite};
+
+pub struct UserSqliteRepository {
+    executor: SqliteExecutor,
+}
+
+impl UserSqliteRepository {
+    pub fn new(executor: SqliteExecutor) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl UserRepository for UserSqliteRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<User>, RepositoryError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, name, job_id, organization_id, email, phone, version
+            FROM users
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(row.map(|r| {
+            let mut user = User::new();
+            user.set_id(r.id);
+            user.set_name(r.name);
+            user.set_job_id(r.job_id);
+            user.set_organization_id(r.organization_id);
+            user.set_email(r.email);
+            user.set_phone(r.phone);
+            user.set_version(r.version);
+            user
+        }))
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>, RepositoryError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, name, job_id, organization_id, email, phone, version
+            FROM users
+            ORDER BY name
+            "#
+        )
+        .fetch_all(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let mut user = User::new();
+                user.set_id(r.id);
+                user.set_name(r.name);
+                user.set_job_id(r.job_id);
+                user.set_organization_id(r.organization_id);
+                user.set_email(r.email);
+                user.set_phone(r.phone);
+                user.set_version(r.version);
+                user
+            })
+            .collect())
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<User>, RepositoryError> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT u.id as "id!", u.name as "name!", u.job_id as "job_id!", u.organization_id as "organization_id!", u.email as "email!", u.phone as "phone!", u.version as "version!"
+            FROM search_index
+            JOIN users u ON u.id = search_index.entity_id
+            WHERE search_index MATCH ? AND search_index.entity_type = 'user'
+            ORDER BY search_index.rank
+            LIMIT ?
+            "#,
+            query,
+            SEARCH_LIMIT
+        )
+        .fetch_all(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let mut user = User::new();
+                user.set_id(r.id);
+                user.set_name(r.name);
+                user.set_job_id(r.job_id);
+                user.set_organization_id(r.organization_id);
+                user.set_email(r.email);
+                user.set_phone(r.phone);
+                user.set_version(r.version);
+                user
+            })
+            .collect())
+    }
+
+    async fn find_page(&self, query: &UserQuery) -> Result<UserPage, RepositoryError> {
+        let mut count: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*) FROM users u");
+        push_filters(&mut count, query);
+        let total: i64 = count
+            .build_query_scalar()
+            .fetch_one(&mut *self.executor.acquire().await?)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let sort_value = sort_expression(query.sort);
+        let (comparison, order) = match query.direction {
+            SortDirection::Ascending => (">", "ASC"),
+            SortDirection::Descending => ("<", "DESC"),
+        };
+
+        let mut page: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+            "SELECT u.id, u.name, u.job_id, u.organization_id, u.email, u.phone, u.version, {sort_value} AS sort_value \
+             FROM users u \
+             LEFT JOIN jobs j ON j.id = u.job_id \
+             LEFT JOIN organizations o ON o.id = u.organization_id"
+        ));
+        push_filters(&mut page, query);
+        if let Some(after) = &query.after {
+            page.push(format!(" AND ({sort_value} {comparison} "))
+                .push_bind(after.sort_value.clone())
+                .push(format!(" OR ({sort_value} = "))
+                .push_bind(after.sort_value.clone())
+                .push(format!(" AND u.id {comparison} "))
+                .push_bind(after.id)
+                .push("))");
+        }
+        page.push(format!(
+            " ORDER BY {sort_value} {order}, u.id {order} LIMIT "
+        ))
+        .push_bind(query.limit + 1);
+
+        let mut rows = page
+            .build()
+            .fetch_all(&mut *self.executor.acquire().await?)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        // One extra row was fetched to tell whether another page follows.
+        let has_more = rows.len() as i64 > query.limit;
+        rows.truncate(query.limit.max(0) as usize);
+        let next = match rows.last() {
+            Some(last) if has_more => Some(UserCursor {
+                sort_value: last.get("sort_value"),
+                id: last.get("id"),
+            }),
+            _ => None,
+        };
+
+        let users = rows
+            .into_iter()
+            .map(|r| {
+                let mut user = User::new();
+                user.set_id(r.get("id"));
+                user.set_name(r.get("name"));
+                user.set_job_id(r.get("job_id"));
+                user.set_organization_id(r.get("organization_id"));
+                user.set_email(r.get("email"));
+                user.set_phone(r.get("phone"));
+                user.set_version(r.get("version"));
+                user
+            })
+            .collect();
+
+        Ok(UserPage { users, total, next })
+    }
+
+    async fn create(&self, user: &User) -> Result<User, RepositoryError> {
+        let name = user.name().to_string();
+        let job_id = user.job_id();
+        let org_id = user.organization_id();
+        let email = user.email().trim().to_string();
+        let phone = user.phone().trim().to_string();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO users (name, job_id, organization_id, email, phone)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            name,
+            job_id,
+            org_id,
+            email,
+            phone,
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        let mut saved_user = user.clone();
+        saved_user.set_id(result.last_insert_rowid());
+        saved_user.set_version(1);
+        Ok(saved_user)
+    }
+
+    async fn restore(&self, user: &User) -> Result<(), RepositoryError> {
+        let user_id = user.id();
+        let name = user.name().to_string();
+        let job_id = user.job_id();
+        let org_id = user.organization_id();
+        let email = user.email().trim().to_string();
+        let phone = user.phone().trim().to_string();
+        let version = user.version().max(1);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO users (id, name, job_id, organization_id, email, phone, version)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            user_id,
+            name,
+            job_id,
+            org_id,
+            email,
+            phone,
+            version,
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn update(&self, user: &User) -> Result<(), RepositoryError> {
+        let name = user.name().to_string();
+        let job_id = user.job_id();
+        let org_id = user.organization_id();
+        let email = user.email().trim().to_string();
+        let phone = user.phone().trim().to_string();
+        let user_id = user.id();
+        let version = user.version();
+
+        let rows_affected = sqlx::query!(
+            r#"
+            UPDATE users
+            SET name = ?, job_id = ?, organization_id = ?, email = ?, phone = ?,
+                version = version + 1
+            WHERE id = ? AND version = ?
+            "#,
+            name,
+            job_id,
+            org_id,
+            email,
+            phone,
+            user_id,
+            version
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return match self.find_by_id(user_id).await? {
+                Some(_) => Err(RepositoryError::Conflict),
+                None => Err(RepositoryError::NotFound),
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepositoryError> {
+        let rows_affected = sqlx::query!(
+            r#"
+            DELETE FROM users WHERE id = ?
+            "#,
+            id
+        )
+        .execute(&mut *self.executor.acquire().await?)
+        .await
+        .map_err(map_sqlx_error)?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        Ok(())
+    }
+}
+
+fn push_filters(builder: &mut QueryBuilder<Sqlite>, query: &UserQuery) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(organization_id) = query.organization_id {
+        builder
+            .push(" AND u.organization_id = ")
+            .push_bind(organization_id);
+    }
+    if let Some(job_id) = query.job_id {
+        builder.push(" AND u.job_id = ").push_bind(job_id);
+    }
+    if let Some(name) = query.name_filter() {
+        let escaped = name
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        builder
+            .push(" AND u.name LIKE ")
+            .push_bind(format!("%{}%", escaped))
+            .push(" ESCAPE '\\'");
+    }
+}
+
+/// The SQL the page is ordered by. Sorting by id uses a constant so the
+/// keyset condition has the same shape for every key.
+fn sort_expression(sort: UserSortKey) -> &'static str {
+    match sort {
+        UserSortKey::Id => "''",
+        UserSortKey::Name => "u.name",
+        UserSortKey::Job => "COALESCE(j.name, '')",
+        UserSortKey::Organization => "COALESCE(o.name, '')",
+    }
+}
@@ -0,0 +1,167 @@
+//! vCard 4.0 (RFC 6350) contact export, so colleagues can be added to a
+//! mail client's address book.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::{CommandError, Entity, Organization, Services, User};
+
+/// vCard lines are folded once they get longer than this many bytes.
+const LINE_WIDTH: usize = 75;
+
+/// Which contacts to export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContactScope {
+    User(i64),
+    Selection(Vec<i64>),
+    /// The organization's own card followed by all of its users.
+    Organization(i64),
+    /// Every organization and user, as one address book.
+    All,
+}
+
+/// The cards of an export, ready to be written to a `.vcf` file.
+#[derive(Debug, Clone, Default)]
+pub struct ContactCards {
+    /// A file name suggestion, without the extension.
+    pub name: String,
+    pub content: String,
+    pub users: usize,
+}
+
+pub async fn export_contacts(
+    services: &Services,
+    scope: &ContactScope,
+) -> Result<ContactCards, CommandError> {
+    let jobs: HashMap<i64, String> = services
+        .job
+        .get_all_jobs()
+        .await?
+        .into_iter()
+        .map(|job| (job.id(), job.name().to_string()))
+        .collect();
+    let mut organizations = services.organization.get_all_organizations().await?;
+    organizations.sort_by(|a, b| a.name().cmp(b.name()));
+    let organization_names: HashMap<i64, String> = organizations
+        .iter()
+        .map(|organization| (organization.id(), organization.name().to_string()))
+        .collect();
+
+    let mut users = services.user.get_all_users().await?;
+    users.sort_by(|a, b| a.name().cmp(b.name()).then(a.id().cmp(&b.id())));
+    let (name, users, organizations): (String, Vec<User>, Vec<Organization>) = match scope {
+        ContactScope::User(id) => {
+            let users: Vec<User> = users.into_iter().filter(|user| user.id() == *id).collect();
+            let name = users
+                .first()
+                .map(|user| user.name().to_string())
+                .unwrap_or_else(|| "contact".to_string());
+            (name, users, Vec::new())
+        }
+        ContactScope::Selection(ids) => {
+            let ids: HashSet<i64> = ids.iter().copied().collect();
+            let users = users
+                .into_iter()
+                .filter(|user| ids.contains(&user.id()))
+                .collect();
+            ("contacts".to_string(), users, Vec::new())
+        }
+        ContactScope::Organization(id) => {
+            let users = users
+                .into_iter()
+                .filter(|user| user.organization_id() == *id)
+                .collect();
+            let organizations: Vec<Organization> = organizations
+                .into_iter()
+                .filter(|organization| organization.id() == *id)
+                .collect();
+            let name = organizations
+                .first()
+                .map(|organization| organization.name().to_string())
+                .unwrap_or_else(|| "organization".to_string());
+            (name, users, organizations)
+        }
+        ContactScope::All => ("address-book".to_string(), users, organizations),
+    };
+
+    let mut content = String::new();
+    for organization in &organizations {
+        organization_card(&mut content, organization);
+    }
+    for user in &users {
+        user_card(
+            &mut content,
+            user,
+            jobs.get(&user.job_id()).map(String::as_str),
+            organization_names
+                .get(&user.organization_id())
+                .map(String::as_str),
+        );
+    }
+    Ok(ContactCards {
+        name,
+        content,
+        users: users.len(),
+    })
+}
+
+fn organization_card(out: &mut String, organization: &Organization) {
+    write_line(out, "BEGIN:VCARD");
+    write_line(out, "VERSION:4.0");
+    write_line(out, "KIND:org");
+    write_line(out, &format!("FN:{}", escape(organization.name())));
+    write_line(out, &format!("ORG:{}", escape(organization.name())));
+    write_line(out, "END:VCARD");
+}
+
+fn user_card(out: &mut String, user: &User, job: Option<&str>, organization: Option<&str>) {
+    // The last word is taken as the family name, the rest as given names.
+    let mut words: Vec<&str> = user.name().split_whitespace().collect();
+    let family = words.pop().unwrap_or_default();
+
+    write_line(out, "BEGIN:VCARD");
+    write_line(out, "VERSION:4.0");
+    write_line(out, "KIND:individual");
+    write_line(out, &format!("FN:{}", escape(user.name())));
+    write_line(
+        out,
+        &format!("N:{};{};;;", escape(family), escape(&words.join(" "))),
+    );
+    if let Some(organization) = organization {
+        write_line(out, &format!("ORG:{}", escape(organization)));
+    }
+    if let Some(job) = job {
+        write_line(out, &format!("TITLE:{}", escape(job)));
+    }
+    if !user.email().is_empty() {
+        write_line(out, &format!("EMAIL;TYPE=work:{}", escape(user.email())));
+    }
+    if !user.phone().is_empty() {
+        write_line(
+            out,
+            &format!("TEL;VALUE=text;TYPE=work:{}", escape(user.phone())),
+        );
+    }
+    write_line(out, "END:VCARD");
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+/// Appends a content line, folded without splitting a UTF-8 character.
+fn write_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_WIDTH {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
@@ -0,0 +1,5 @@
+//! Users, jobs and organizations and where they are stored, without any
+//! window. The GUI, the API and SCIM servers and the CLI are built on this.
+
+pub mod domain;
+pub mod infrastructure;
@@ -0,0 +1,137 @@
+//! Backs up and restores SQLite databases in a temporary directory.
+
+use sqlx::pool::PoolConnection;
+use sqlx::Sqlite;
+use tempfile::TempDir;
+
+use usermgmt_core::domain::{Entity, Job};
+use usermgmt_core::infrastructure::Database;
+
+async fn open(dir: &TempDir, name: &str) -> Database {
+    let path = dir.path().join(name);
+    Database::open(&path.to_string_lossy(), false)
+        .await
+        .expect("database opens")
+}
+
+async fn job_names(database: &Database) -> Vec<String> {
+    let mut names: Vec<String> = database
+        .services()
+        .job
+        .get_all_jobs()
+        .await
+        .expect("jobs load")
+        .iter()
+        .map(|job| job.name().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+async fn create_job(database: &Database, name: &str) {
+    let mut job = Job::new();
+    job.set_name(name.to_string());
+    database
+        .services()
+        .job
+        .create_job(job)
+        .await
+        .expect("job saved");
+}
+
+/// Holds every pooled connection at once, so each of them can be checked.
+async fn all_connections(database: &Database) -> Vec<PoolConnection<Sqlite>> {
+    let mut connections = Vec::new();
+    for _ in 0..database.pool.size() {
+        connections.push(database.pool.acquire().await.expect("connection"));
+    }
+    connections
+}
+
+async fn assert_connections_reset(database: &Database) {
+    for mut connection in all_connections(database).await {
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&mut *connection)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+        let attached: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_database_list")
+            .fetch_all(&mut *connection)
+            .await
+            .unwrap();
+        assert!(
+            !attached.iter().any(|name| name == "backup"),
+            "{:?}",
+            attached
+        );
+    }
+}
+
+#[test]
+fn a_backup_restores_the_data_it_was_taken_with() {
+    smol::block_on(async {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let database = open(&dir, "live.db").await;
+        create_job(&database, "Developer").await;
+        let backup = dir.path().join("backup.db");
+        database.backup_to(&backup).await.unwrap();
+
+        create_job(&database, "Tester").await;
+        database.restore_from(&backup).await.unwrap();
+
+        assert_eq!(job_names(&database).await, ["Developer"]);
+        assert_connections_reset(&database).await;
+    });
+}
+
+#[test]
+fn a_failed_backup_keeps_the_previous_one() {
+    smol::block_on(async {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let database = open(&dir, "live.db").await;
+        create_job(&database, "Developer").await;
+        let backup = dir.path().join("backup.db");
+        database.backup_to(&backup).await.unwrap();
+        let previous = std::fs::read(&backup).unwrap();
+
+        create_job(&database, "Tester").await;
+        database.pool.close().await;
+        assert!(database.backup_to(&backup).await.is_err());
+
+        assert_eq!(std::fs::read(&backup).unwrap(), previous);
+        let names: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("backup"))
+            .collect();
+        assert_eq!(names, ["backup.db"]);
+
+        let restored = open(&dir, "restored.db").await;
+        restored.restore_from(&backup).await.unwrap();
+        assert_eq!(job_names(&restored).await, ["Developer"]);
+    });
+}
+
+#[test]
+fn a_failed_restore_leaves_the_database_as_it_was() {
+    smol::block_on(async {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let database = open(&dir, "live.db").await;
+        create_job(&database, "Developer").await;
+        let backup = dir.path().join("backup.db");
+        database.backup_to(&backup).await.unwrap();
+
+        create_job(&database, "Tester").await;
+        sqlx::query(
+            "CREATE TRIGGER refuse_jobs BEFORE INSERT ON jobs \
+             BEGIN SELECT RAISE(ABORT, 'refused'); END",
+        )
+        .execute(&database.pool)
+        .await
+        .unwrap();
+        assert!(database.restore_from(&backup).await.is_err());
+
+        assert_eq!(job_names(&database).await, ["Developer", "Tester"]);
+        assert_connections_reset(&database).await;
+    });
+}
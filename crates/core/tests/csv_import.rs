@@ -0,0 +1,236 @@
+//! Previews and imports small CSV files built in memory.
+
+use std::path::PathBuf;
+
+use usermgmt_core::domain::{Entity, Job, Organization, Services};
+use usermgmt_core::infrastructure::{
+    import_users, write_rejected, ColumnMapping, CsvFile, ImportPreview, ImportSummary,
+    MemoryDatabase, Reference,
+};
+
+fn csv(headers: &[&str], rows: &[&[&str]]) -> CsvFile {
+    let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    CsvFile {
+        path: PathBuf::from("users.csv"),
+        headers: strings(headers),
+        rows: rows.iter().map(|row| strings(row)).collect(),
+    }
+}
+
+fn job(id: i64, name: &str) -> Job {
+    let mut job = Job::new();
+    job.set_id(id);
+    job.set_name(name.to_string());
+    job
+}
+
+fn organization(id: i64, name: &str) -> Organization {
+    let mut organization = Organization::new();
+    organization.set_id(id);
+    organization.set_name(name.to_string());
+    organization
+}
+
+fn names<T: Entity>(records: Vec<T>) -> Vec<String> {
+    let mut names: Vec<String> = records
+        .iter()
+        .map(|record| record.name().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+async fn job_names(services: &Services) -> Vec<String> {
+    names(services.job.get_all_jobs().await.unwrap())
+}
+
+async fn organization_names(services: &Services) -> Vec<String> {
+    names(services.organization.get_all_organizations().await.unwrap())
+}
+
+#[test]
+fn columns_are_mapped_by_header_or_alias() {
+    let file = csv(
+        &[
+            "Full Name",
+            " TITLE ",
+            "Department",
+            "E-mail",
+            "Mobile",
+            "",
+            "Notes",
+        ],
+        &[],
+    );
+    let mapping = ColumnMapping::guess(&file.headers);
+    assert_eq!(
+        mapping,
+        ColumnMapping {
+            name: Some(0),
+            job: Some(1),
+            organization: Some(2),
+            email: Some(3),
+            phone: Some(4),
+        }
+    );
+    let labels: Vec<String> = file.columns().iter().map(ToString::to_string).collect();
+    assert_eq!(labels[5], "Column 6");
+    assert_eq!(labels[6], "Notes");
+
+    let file = csv(&["user", "role", "company", "mail"], &[]);
+    let mapping = ColumnMapping::guess(&file.headers);
+    assert_eq!(
+        (
+            mapping.name,
+            mapping.job,
+            mapping.organization,
+            mapping.email
+        ),
+        (Some(0), Some(1), Some(2), Some(3))
+    );
+    assert_eq!(mapping.phone, None);
+    assert_eq!(ColumnMapping::guess(&file.headers[1..]).name, None);
+}
+
+#[test]
+fn names_are_resolved_ignoring_case() {
+    let file = csv(
+        &["name", "job", "organization"],
+        &[
+            &["Ada Lovelace", "developer", " ENGINEERING "],
+            &["Alan Turing", "Analyst", "Research"],
+        ],
+    );
+    let preview = ImportPreview::new(
+        &file,
+        ColumnMapping::guess(&file.headers),
+        &[job(3, "Developer")],
+        &[organization(8, "Engineering")],
+        false,
+    );
+
+    assert_eq!(preview.rows[0].job, Some(Reference::Existing(3)));
+    assert_eq!(preview.rows[0].organization, Some(Reference::Existing(8)));
+    assert!(preview.rows[0].is_valid());
+    assert_eq!(preview.rows[1].line, 3);
+    assert_eq!(
+        preview.rows[1].errors,
+        ["Unknown job 'Analyst'", "Unknown organization 'Research'"]
+    );
+    assert_eq!(preview.valid_count(), 1);
+    assert!(preview.new_jobs.is_empty());
+    assert!(preview.new_organizations.is_empty());
+}
+
+#[test]
+fn missing_records_are_created_once_per_name() {
+    smol::block_on(async {
+        let database = MemoryDatabase::new();
+        let services = database.services();
+        let file = csv(
+            &["name", "job", "organization", "email", "phone"],
+            &[
+                &["Ada Lovelace", "analyst", "Acme", "ada@example.com", ""],
+                &["Alan Turing", "Analyst", "ACME", "", "+44 161 555 0100"],
+                &["Grace Hopper", "ANALYST", "acme", "", ""],
+                &["Al", "Auditor", "Auditors Ltd", "", ""],
+            ],
+        );
+        let preview =
+            ImportPreview::new(&file, ColumnMapping::guess(&file.headers), &[], &[], true);
+
+        // The first spelling is kept, and names only on rejected rows are
+        // not created.
+        let new = |name: &str| Some(Reference::New(name.to_string()));
+        assert!(preview.rows[..3]
+            .iter()
+            .all(|row| row.job == new("analyst") && row.organization == new("Acme")));
+        assert_eq!(preview.new_jobs.iter().collect::<Vec<_>>(), ["analyst"]);
+        assert_eq!(
+            preview.new_organizations.iter().collect::<Vec<_>>(),
+            ["Acme"]
+        );
+        assert_eq!(preview.valid_count(), 3);
+
+        let summary = import_users(&services, &preview).await.unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                users: 3,
+                jobs: 1,
+                organizations: 1,
+                rejected: 1,
+            }
+        );
+        assert_eq!(job_names(&services).await, ["analyst"]);
+        assert_eq!(organization_names(&services).await, ["Acme"]);
+        let users = services.user.get_all_users().await.unwrap();
+        let alan = users
+            .iter()
+            .find(|user| user.name() == "Alan Turing")
+            .unwrap();
+        assert_eq!(alan.phone(), "+44 161 555 0100");
+
+        // Importing again finds the records just created.
+        let jobs = services.job.get_all_jobs().await.unwrap();
+        let organizations = services.organization.get_all_organizations().await.unwrap();
+        let again = ImportPreview::new(
+            &file,
+            ColumnMapping::guess(&file.headers),
+            &jobs,
+            &organizations,
+            true,
+        );
+        assert!(again.new_jobs.is_empty());
+        assert!(again.new_organizations.is_empty());
+        assert_eq!(again.rows[2].job, Some(Reference::Existing(jobs[0].id())));
+    });
+}
+
+#[test]
+fn rejected_rows_are_written_with_their_reasons() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let file = csv(
+        &["name", "job", "organization", "email", "notes"],
+        &[
+            &[
+                "Ada Lovelace",
+                "Developer",
+                "Engineering",
+                "ada@example.com",
+                "ok",
+            ],
+            &["Al", "Developer", "Engineering", "", "too short"],
+            &["Alan Turing", "", "Engineering", "alan.example.com"],
+            &[
+                "Grace Hopper",
+                "Admiral",
+                "Navy",
+                "",
+                "unknown, both",
+                "extra",
+            ],
+        ],
+    );
+    let preview = ImportPreview::new(
+        &file,
+        ColumnMapping::guess(&file.headers),
+        &[job(1, "Developer")],
+        &[organization(1, "Engineering")],
+        false,
+    );
+    let lines: Vec<usize> = preview.rejected().map(|row| row.line).collect();
+    assert_eq!(lines, [3, 4, 5]);
+
+    let path = dir.path().join("rejected.csv");
+    assert_eq!(write_rejected(&path, &file, &preview).unwrap(), 3);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "name,job,organization,email,notes,line,errors\n\
+         Al,Developer,Engineering,,too short,3,Name must be at least 3 characters\n\
+         Alan Turing,,Engineering,alan.example.com,,4,\
+         Email must look like name@example.com; Job selection is required\n\
+         Grace Hopper,Admiral,Navy,,\"unknown, both\",5,\
+         Unknown job 'Admiral'; Unknown organization 'Navy'\n"
+    );
+}
@@ -0,0 +1,85 @@
+//! Checks a SQLite database for users left without a job or organization
+//! and repairs them.
+
+use usermgmt_core::domain::{Command, Entity, Job, Organization, User};
+use usermgmt_core::infrastructure::Database;
+
+#[test]
+fn a_reassigned_user_keeps_their_contact_details() {
+    smol::block_on(async {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let path = dir.path().join("integrity.db");
+        let database = Database::open(&path.to_string_lossy(), false)
+            .await
+            .expect("database opens");
+        let services = database.services();
+
+        let mut job = Job::new();
+        job.set_name("Developer".to_string());
+        let job = services.job.create_job(job).await.unwrap();
+        let mut organizations = Vec::new();
+        for name in ["Engineering", "Research"] {
+            let mut organization = Organization::new();
+            organization.set_name(name.to_string());
+            organizations.push(
+                services
+                    .organization
+                    .create_organization(organization)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let mut user = User::new();
+        user.set_name("Ada Lovelace".to_string());
+        user.set_job_id(job.id());
+        user.set_organization_id(organizations[0].id());
+        user.set_email("ada@example.com".to_string());
+        user.set_phone("+44 20 7946 0000".to_string());
+        let user = services.user.create_user(user).await.unwrap();
+
+        // Only possible with foreign keys off, as in a file from an older
+        // version of the app.
+        let mut connection = database.pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM organizations WHERE id = ?")
+            .bind(organizations[0].id())
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+        drop(connection);
+
+        let report = database.check_integrity().await.unwrap();
+        assert!(!report.is_healthy());
+        let [orphan] = report.orphaned_users.as_slice() else {
+            panic!("expected one orphaned user: {:?}", report.orphaned_users);
+        };
+        assert!(orphan.missing_organization && !orphan.missing_job);
+        assert_eq!(orphan.user.email(), "ada@example.com");
+        assert_eq!(orphan.user.phone(), "+44 20 7946 0000");
+
+        let mut reassigned = orphan.user.clone();
+        reassigned.set_organization_id(organizations[1].id());
+        Command::UpdateUser(reassigned)
+            .execute(&services)
+            .await
+            .unwrap();
+
+        let stored = services
+            .user
+            .get_user_by_id(user.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.organization_id(), organizations[1].id());
+        assert_eq!(stored.email(), "ada@example.com");
+        assert_eq!(stored.phone(), "+44 20 7946 0000");
+        assert!(database.check_integrity().await.unwrap().is_healthy());
+    });
+}
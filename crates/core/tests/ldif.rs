@@ -0,0 +1,344 @@
+//! Exports users as LDIF, parses it back and plans imports from it.
+
+use usermgmt_core::domain::{Entity, Job, Organization, Services, User};
+use usermgmt_core::infrastructure::{
+    apply_ldif, export_ldif, parse_ldif, LdifError, LdifOptions, LdifPlan, LdifSummary,
+    MemoryDatabase,
+};
+
+struct Directory {
+    users: Vec<User>,
+    jobs: Vec<Job>,
+    organizations: Vec<Organization>,
+}
+
+impl Directory {
+    async fn load(services: &Services) -> Self {
+        Self {
+            users: services.user.get_all_users().await.unwrap(),
+            jobs: services.job.get_all_jobs().await.unwrap(),
+            organizations: services.organization.get_all_organizations().await.unwrap(),
+        }
+    }
+
+    fn plan(&self, content: &str, options: &LdifOptions) -> LdifPlan {
+        let entries = parse_ldif(content).expect("valid LDIF");
+        LdifPlan::new(
+            &entries,
+            options,
+            &self.users,
+            &self.jobs,
+            &self.organizations,
+        )
+    }
+
+    fn diff(&self, plan: &LdifPlan) -> Vec<String> {
+        plan.diff(&self.jobs, &self.organizations)
+    }
+}
+
+async fn job(services: &Services, name: &str) -> Job {
+    let mut job = Job::new();
+    job.set_name(name.to_string());
+    services.job.create_job(job).await.expect("job saved")
+}
+
+async fn organization(services: &Services, name: &str) -> Organization {
+    let mut organization = Organization::new();
+    organization.set_name(name.to_string());
+    services
+        .organization
+        .create_organization(organization)
+        .await
+        .expect("organization saved")
+}
+
+async fn user(
+    services: &Services,
+    name: &str,
+    job: &Job,
+    organization: &Organization,
+    email: &str,
+    phone: &str,
+) -> User {
+    let mut user = User::new();
+    user.set_name(name.to_string());
+    user.set_job_id(job.id());
+    user.set_organization_id(organization.id());
+    user.set_email(email.to_string());
+    user.set_phone(phone.to_string());
+    services.user.create_user(user).await.expect("user saved")
+}
+
+fn parse_error(content: &str) -> (usize, String) {
+    match parse_ldif(content) {
+        Err(LdifError::Parse(line, message)) => (line, message),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn an_export_parses_back_unchanged() {
+    smol::block_on(async {
+        let database = MemoryDatabase::new();
+        let services = database.services();
+        let developer = job(&services, "Developer").await;
+        let research = organization(&services, "Research, Inc.").await;
+        let engineering = organization(&services, "Engineering").await;
+        user(
+            &services,
+            "Ada Lovelace",
+            &developer,
+            &research,
+            "ada@example.org",
+            "",
+        )
+        .await;
+        user(
+            &services,
+            "Grace \"Amazing\" Hopper",
+            &developer,
+            &engineering,
+            "",
+            "+1 555 0100",
+        )
+        .await;
+        user(&services, "Zoë Ångström", &developer, &research, "", "").await;
+        let options = LdifOptions {
+            dn_template:
+                "cn={name},ou={organization},ou=people,dc=research-department,dc=example,dc=com"
+                    .to_string(),
+            ..LdifOptions::default()
+        };
+
+        let (content, count) = export_ldif(&services, &options).await.unwrap();
+        assert_eq!(count, 3);
+        assert!(
+            content.contains("\n "),
+            "long lines are folded:\n{}",
+            content
+        );
+        assert!(
+            content.contains("\ncn:: "),
+            "non-ASCII is base64:\n{}",
+            content
+        );
+
+        let entries = parse_ldif(&content).unwrap();
+        let dns: Vec<&str> = entries.iter().map(|entry| entry.dn.as_str()).collect();
+        assert_eq!(
+            dns,
+            [
+                "cn=Ada Lovelace,ou=Research\\, Inc.,ou=people,dc=research-department,dc=example,dc=com",
+                "cn=Grace \\\"Amazing\\\" Hopper,ou=Engineering,ou=people,dc=research-department,dc=example,dc=com",
+                "cn=Zoë Ångström,ou=Research\\, Inc.,ou=people,dc=research-department,dc=example,dc=com",
+            ]
+        );
+        assert_eq!(entries[0].get("mail"), Some("ada@example.org"));
+        assert_eq!(entries[1].get("cn"), Some("Grace \"Amazing\" Hopper"));
+        assert_eq!(entries[1].get("telephoneNumber"), Some("+1 555 0100"));
+        assert_eq!(
+            entries[1].get("mail"),
+            Some("grace.amazing.hopper@example.com")
+        );
+        assert_eq!(entries[2].get("CN"), Some("Zoë Ångström"));
+        assert_eq!(entries[2].get("ou"), Some("Research, Inc."));
+
+        // Reading the export back into the same data changes nothing, and
+        // the templated mail is not taken for a real address.
+        let directory = Directory::load(&services).await;
+        let plan = directory.plan(&content, &options);
+        assert_eq!(plan.unchanged, 3);
+        assert!(plan.is_empty());
+        assert!(plan.skipped.is_empty());
+
+        // Records that change or delete entries are not imports.
+        let with_changes = format!(
+            "{}\ndn: cn=Ada Lovelace,ou=Engineering,dc=example,dc=com\n\
+             changetype: modify\nreplace: title\ntitle: Analyst\n-\n\n\
+             dn: cn=Alan Turing,ou=Engineering,dc=example,dc=com\n\
+             changetype: delete\n\n\
+             dn: cn=Alan Turing,ou=Engineering,dc=example,dc=com\n\
+             changetype: add\ncn: Alan Turing\ntitle: Developer\nou: Engineering\n",
+            content
+        );
+        let plan = directory.plan(&with_changes, &options);
+        assert_eq!(plan.unchanged, 3);
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].name, "Alan Turing");
+        let reasons: Vec<&str> = plan
+            .skipped
+            .iter()
+            .map(|skip| skip.reason.as_str())
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                "only entries to add can be imported",
+                "only entries to add can be imported"
+            ]
+        );
+    });
+}
+
+#[test]
+fn folded_and_base64_lines_are_decoded() {
+    let content = "version: 1\n\
+                   # A comment, then an entry split over several lines\n\
+                   dn: cn=Ada Lovelace,ou=Engineering,dc=exa\n \
+                   mple,dc=com\n\
+                   objectClass: person\n\
+                   cn:: QWRhIExvdmVsYWNl\n\
+                   title: Devel\n \
+                   oper\n\
+                   ou;lang-en: Engineering\n\
+                   description:: WmHDq1xuICA=\n\
+                   \n\
+                   \n\
+                   dn:: Y249R3JhY2UgSG9wcGVy\n\
+                   cn: Grace Hopper\n";
+
+    let entries = parse_ldif(content).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].line, 3);
+    assert_eq!(
+        entries[0].dn,
+        "cn=Ada Lovelace,ou=Engineering,dc=example,dc=com"
+    );
+    assert_eq!(entries[0].get("cn"), Some("Ada Lovelace"));
+    assert_eq!(entries[0].get("title"), Some("Developer"));
+    assert_eq!(entries[0].get("ou"), Some("Engineering"));
+    assert_eq!(entries[0].get("description"), Some("Zaë\\n  "));
+    assert_eq!(entries[0].get("mail"), None);
+    assert_eq!(entries[1].line, 13);
+    assert_eq!(entries[1].dn, "cn=Grace Hopper");
+    assert_eq!(
+        entries[1].attributes,
+        [("cn".to_string(), "Grace Hopper".to_string())]
+    );
+}
+
+#[test]
+fn malformed_lines_are_reported_with_their_number() {
+    assert_eq!(
+        parse_error("version: 1\ndn: cn=Ada\ncn Ada\n"),
+        (3, "missing ':'".to_string())
+    );
+    assert_eq!(
+        parse_error("version: 1\n\ncn: Ada Lovelace\n"),
+        (3, "expected 'dn:', found 'cn:'".to_string())
+    );
+    let (line, message) = parse_error("dn: cn=Ada\ncn:: not base64!\n");
+    assert_eq!(line, 2);
+    assert!(message.starts_with("invalid base64"), "{}", message);
+    assert_eq!(
+        parse_error("dn: cn=Ada\ncn:: /w==\n"),
+        (2, "value is not UTF-8 text".to_string())
+    );
+    assert_eq!(
+        parse_error("dn: cn=Ada,\n dc=example\njpegPhoto:< file:///photo.jpg\n"),
+        (3, "values read from URLs are not supported".to_string())
+    );
+}
+
+#[test]
+fn a_plan_counts_and_lists_its_changes() {
+    smol::block_on(async {
+        let database = MemoryDatabase::new();
+        let services = database.services();
+        let developer = job(&services, "Developer").await;
+        let engineering = organization(&services, "Engineering").await;
+        user(
+            &services,
+            "Ada Lovelace",
+            &developer,
+            &engineering,
+            "ada@example.org",
+            "",
+        )
+        .await;
+        user(
+            &services,
+            "Alan Turing",
+            &developer,
+            &engineering,
+            "",
+            "+44 161 555 0100",
+        )
+        .await;
+        let options = LdifOptions::default();
+        let content = "version: 1\n\
+                       \n\
+                       dn: cn=Ada Lovelace,ou=Engineering,dc=example,dc=com\n\
+                       cn: ada lovelace\n\
+                       title: developer\n\
+                       ou: ENGINEERING\n\
+                       mail: ada@example.org\n\
+                       \n\
+                       dn: cn=Alan Turing,ou=Engineering,dc=example,dc=com\n\
+                       cn: Alan Turing\n\
+                       title: Analyst\n\
+                       ou: Engineering\n\
+                       mail: alan.turing@example.com\n\
+                       telephoneNumber: +44 161 555 0199\n\
+                       \n\
+                       dn: cn=Grace Hopper,ou=Navy,dc=example,dc=com\n\
+                       cn: Grace Hopper\n\
+                       title: Developer\n\
+                       ou: Navy\n\
+                       mail: grace@navy.example\n\
+                       \n\
+                       dn: cn=grace hopper,ou=Navy,dc=example,dc=com\n\
+                       cn: grace hopper\n\
+                       title: Developer\n\
+                       ou: navy\n\
+                       \n\
+                       dn: ou=Engineering,dc=example,dc=com\n\
+                       ou: Engineering\n\
+                       \n\
+                       dn: cn=Al,ou=Engineering,dc=example,dc=com\n\
+                       cn: Al\n\
+                       title: Developer\n\
+                       ou: Engineering\n";
+
+        let directory = Directory::load(&services).await;
+        let plan = directory.plan(content, &options);
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.changes.len(), 2);
+        assert_eq!(plan.skipped.len(), 3);
+        assert_eq!(
+            directory.diff(&plan),
+            [
+                "+ organization Navy",
+                "+ job Analyst",
+                "~ user Alan Turing: job Developer -> Analyst, \
+                 phone +44 161 555 0100 -> +44 161 555 0199",
+                "+ user Grace Hopper (Developer, Navy)",
+                "! line 22 cn=grace hopper,ou=Navy,dc=example,dc=com: \
+                 same name as the entry on line 16",
+                "! line 27 ou=Engineering,dc=example,dc=com: no cn attribute",
+                "! line 30 cn=Al,ou=Engineering,dc=example,dc=com: \
+                 Name must be at least 3 characters",
+            ]
+        );
+
+        let summary = apply_ldif(&services, &plan).await.unwrap();
+        assert_eq!(
+            summary,
+            LdifSummary {
+                created: 1,
+                updated: 1,
+                jobs: 1,
+                organizations: 1,
+                skipped: 3,
+            }
+        );
+
+        // Planning the same file again finds nothing left to do.
+        let plan = Directory::load(&services).await.plan(content, &options);
+        assert_eq!(plan.unchanged, 3);
+        assert!(plan.is_empty());
+        assert!(plan.new_jobs.is_empty());
+        assert!(plan.new_organizations.is_empty());
+    });
+}
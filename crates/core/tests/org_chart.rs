@@ -0,0 +1,219 @@
+//! Builds org charts from records in memory and renders them as DOT and SVG.
+
+use usermgmt_core::domain::{Entity, Job, Organization, User};
+use usermgmt_core::infrastructure::{ChartCollapse, ChartNode, ChartNodeKind, OrgChart};
+
+fn job(id: i64, name: &str) -> Job {
+    let mut job = Job::new();
+    job.set_id(id);
+    job.set_name(name.to_string());
+    job
+}
+
+fn organization(id: i64, name: &str) -> Organization {
+    let mut organization = Organization::new();
+    organization.set_id(id);
+    organization.set_name(name.to_string());
+    organization
+}
+
+fn user(id: i64, name: &str, job_id: i64, organization_id: i64) -> User {
+    let mut user = User::new();
+    user.set_id(id);
+    user.set_name(name.to_string());
+    user.set_job_id(job_id);
+    user.set_organization_id(organization_id);
+    user
+}
+
+/// Two organizations, one with an awkward name, and a user whose
+/// organization is gone.
+fn chart(collapse: ChartCollapse) -> OrgChart {
+    OrgChart::build(
+        &[job(1, "Developer"), job(2, "R&D \"Lead\"")],
+        &[
+            organization(20, "Research \\ <Labs>"),
+            organization(10, "Engineering"),
+        ],
+        &[
+            user(1, "Grace Hopper", 2, 10),
+            user(2, "Ada Lovelace", 1, 10),
+            user(3, "Alan Turing", 1, 10),
+            user(4, "Zoë \"Z\"\r\nÅngström", 2, 20),
+            user(5, "Olive Orphan", 1, 99),
+        ],
+        collapse,
+    )
+}
+
+fn summary(nodes: &[ChartNode]) -> Vec<(&str, &str, &str)> {
+    nodes
+        .iter()
+        .map(|node| (node.key.as_str(), node.label.as_str(), node.detail.as_str()))
+        .collect()
+}
+
+/// Checks that every tag is closed in order, every attribute value is
+/// quoted and every `&` starts an entity.
+fn assert_well_formed(svg: &str) {
+    let check_text = |text: &str| {
+        for (index, _) in text.match_indices('&') {
+            assert!(
+                ["&amp;", "&lt;", "&gt;", "&quot;"]
+                    .iter()
+                    .any(|entity| text[index..].starts_with(entity)),
+                "bare '&' in {:?}",
+                text
+            );
+        }
+    };
+    let mut open: Vec<&str> = Vec::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find('<') {
+        check_text(&rest[..start]);
+        let end = start + rest[start..].find('>').expect("tag is closed");
+        let tag = &rest[start + 1..end];
+        assert!(!tag.contains('<'), "'<' inside {:?}", tag);
+        assert_eq!(
+            tag.matches('"').count() % 2,
+            0,
+            "unquoted value in {:?}",
+            tag
+        );
+        check_text(tag);
+        if let Some(name) = tag.strip_prefix('/') {
+            assert_eq!(open.pop(), Some(name));
+        } else if !tag.ends_with('/') {
+            open.push(tag.split_whitespace().next().expect("tag name"));
+        }
+        rest = &rest[end + 1..];
+    }
+    check_text(rest);
+    assert!(open.is_empty(), "unclosed {:?}", open);
+}
+
+#[test]
+fn users_are_listed_under_their_organization() {
+    let chart = chart(ChartCollapse::None);
+    assert_eq!(chart.users, 5);
+    assert_eq!(chart.root.kind, ChartNodeKind::Root);
+    assert_eq!(chart.root.detail, "5 users");
+    assert_eq!(
+        summary(&chart.root.children),
+        [
+            ("organization_10", "Engineering", "3 users"),
+            ("organization_20", "Research \\ <Labs>", "1 user"),
+            ("organization_unassigned", "Unassigned", "1 user"),
+        ]
+    );
+    assert_eq!(
+        summary(&chart.root.children[0].children),
+        [
+            ("user_2", "Ada Lovelace", "Developer"),
+            ("user_3", "Alan Turing", "Developer"),
+            ("user_1", "Grace Hopper", "R&D \"Lead\""),
+        ]
+    );
+    // Everyone counted at the root is drawn somewhere.
+    let drawn: usize = chart
+        .root
+        .children
+        .iter()
+        .map(|organization| organization.children.len())
+        .sum();
+    assert_eq!(drawn, chart.users);
+    assert_eq!(
+        summary(&chart.root.children[2].children),
+        [("user_5", "Olive Orphan", "Developer")]
+    );
+}
+
+#[test]
+fn collapsing_counts_users_instead_of_listing_them() {
+    let chart = chart(ChartCollapse::Job);
+    assert_eq!(
+        summary(&chart.root.children[0].children),
+        [
+            ("job_10_1", "Developer", "2 users"),
+            ("job_10_2", "R&D \"Lead\"", "1 user"),
+        ]
+    );
+    assert!(chart.root.children[0]
+        .children
+        .iter()
+        .all(|node| node.kind == ChartNodeKind::Job));
+    assert_eq!(
+        summary(&chart.root.children[2].children),
+        [("job_unassigned_1", "Developer", "1 user")]
+    );
+
+    let chart = self::chart(ChartCollapse::Organization);
+    assert_eq!(
+        summary(&chart.root.children),
+        [
+            ("organization_10", "Engineering", "3 users"),
+            ("organization_20", "Research \\ <Labs>", "1 user"),
+            ("organization_unassigned", "Unassigned", "1 user"),
+        ]
+    );
+    assert!(chart
+        .root
+        .children
+        .iter()
+        .all(|organization| organization.children.is_empty()));
+
+    assert!(OrgChart::build(&[], &[], &[], ChartCollapse::None)
+        .root
+        .children
+        .is_empty());
+}
+
+#[test]
+fn dot_labels_are_escaped() {
+    let dot = chart(ChartCollapse::None).to_dot();
+    assert!(dot.starts_with("digraph org_chart {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(
+        dot.contains("    organization_20 [label=\"Research \\\\ <Labs>\\n1 user\""),
+        "{}",
+        dot
+    );
+    assert!(
+        dot.contains("    user_4 [label=\"Zoë \\\"Z\\\"  Ångström\\nR&D \\\"Lead\\\"\""),
+        "{}",
+        dot
+    );
+    // Each node is on one line, and every node but the root has one edge.
+    let nodes = dot.lines().filter(|line| line.contains("[label=")).count();
+    let edges = dot.lines().filter(|line| line.contains(" -> ")).count();
+    assert_eq!((nodes, edges), (9, 8));
+    assert!(dot.contains("    organization_unassigned -> user_5;\n"));
+}
+
+#[test]
+fn svg_is_well_formed_in_every_mode() {
+    for collapse in ChartCollapse::ALL {
+        let svg = chart(collapse).to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert_well_formed(&svg);
+        assert!(svg.contains("Research \\ &lt;Labs&gt;"), "{}", svg);
+        assert!(svg.contains(">Unassigned</text>"), "{}", svg);
+    }
+
+    let svg = chart(ChartCollapse::None).to_svg();
+    assert!(svg.contains("<title>Zoë &quot;Z&quot;\nÅngström\nR&amp;D &quot;Lead&quot;</title>"));
+    assert!(svg.contains(">R&amp;D &quot;Lead&quot;</text>"));
+
+    // Long names are cut short on the box but kept whole in the tooltip.
+    let long = "Maximilian Alexander Fitzgerald-Montgomery";
+    let svg = OrgChart::build(
+        &[job(1, "Developer")],
+        &[organization(1, "Engineering")],
+        &[user(1, long, 1, 1)],
+        ChartCollapse::None,
+    )
+    .to_svg();
+    assert_well_formed(&svg);
+    assert!(svg.contains(&format!("<title>{}\nDeveloper</title>", long)));
+    assert!(svg.contains("…</text>"));
+}
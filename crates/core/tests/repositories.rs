@@ -0,0 +1,451 @@
+//! Runs the same repository checks against SQLite and the in-memory
+//! database, so demo mode and tests behave like a real database file.
+
+use std::future::Future;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use usermgmt_core::domain::{
+    AuditAction, AuditEntry, AuditFilter, DomainEntity, Entity, Job, Organization, Repositories,
+    RepositoryError, SortDirection, UnitOfWork, User, UserQuery, UserSortKey,
+};
+use usermgmt_core::infrastructure::{Database, MemoryDatabase};
+
+struct Backend {
+    name: &'static str,
+    unit_of_work: Arc<dyn UnitOfWork>,
+    // Removed, with the database, when the test ends.
+    _dir: Option<TempDir>,
+}
+
+impl Backend {
+    async fn sqlite() -> Self {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let path = dir.path().join("repositories.db");
+        let database = Database::open(&path.to_string_lossy(), false)
+            .await
+            .expect("database opens");
+        Self {
+            name: "sqlite",
+            unit_of_work: Arc::new(database),
+            _dir: Some(dir),
+        }
+    }
+
+    fn memory() -> Self {
+        Self {
+            name: "memory",
+            unit_of_work: Arc::new(MemoryDatabase::new()),
+            _dir: None,
+        }
+    }
+
+    fn repositories(&self) -> Repositories {
+        self.unit_of_work.repositories()
+    }
+
+    async fn job(&self, name: &str) -> Job {
+        let mut job = Job::new();
+        job.set_name(name.to_string());
+        self.repositories()
+            .job
+            .create(&job)
+            .await
+            .expect("job saved")
+    }
+
+    async fn organization(&self, name: &str) -> Organization {
+        let mut organization = Organization::new();
+        organization.set_name(name.to_string());
+        self.repositories()
+            .organization
+            .create(&organization)
+            .await
+            .expect("organization saved")
+    }
+
+    async fn user(&self, name: &str, job: &Job, organization: &Organization) -> User {
+        self.repositories()
+            .user
+            .create(&new_user(name, job.id(), organization.id()))
+            .await
+            .expect("user saved")
+    }
+}
+
+fn new_user(name: &str, job_id: i64, organization_id: i64) -> User {
+    let mut user = User::new();
+    user.set_name(name.to_string());
+    user.set_job_id(job_id);
+    user.set_organization_id(organization_id);
+    user
+}
+
+/// Runs a test on a fresh SQLite database and then on a fresh in-memory one.
+fn on_both<F, Fut>(test: F)
+where
+    F: Fn(Backend) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    smol::block_on(async {
+        test(Backend::sqlite().await).await;
+        test(Backend::memory()).await;
+    });
+}
+
+#[test]
+fn missing_records_are_not_found() {
+    on_both(|backend| async move {
+        let repositories = backend.repositories();
+        let job = backend.job("Developer").await;
+        let organization = backend.organization("Engineering").await;
+
+        assert!(repositories.user.find_by_id(99).await.unwrap().is_none());
+        let mut user = new_user("Ada Lovelace", job.id(), organization.id());
+        user.set_id(99);
+        user.set_version(1);
+        assert!(
+            matches!(
+                repositories.user.update(&user).await,
+                Err(RepositoryError::NotFound)
+            ),
+            "{}",
+            backend.name
+        );
+        assert!(matches!(
+            repositories.user.delete(99).await,
+            Err(RepositoryError::NotFound)
+        ));
+        assert!(matches!(
+            repositories.job.delete(99).await,
+            Err(RepositoryError::NotFound)
+        ));
+        assert!(matches!(
+            repositories.organization.delete(99).await,
+            Err(RepositoryError::NotFound)
+        ));
+    });
+}
+
+#[test]
+fn names_of_jobs_and_organizations_are_unique() {
+    on_both(|backend| async move {
+        let repositories = backend.repositories();
+        backend.job("Developer").await;
+        backend.organization("Engineering").await;
+
+        let mut job = Job::new();
+        job.set_name("Developer".to_string());
+        assert!(
+            matches!(
+                repositories.job.create(&job).await,
+                Err(RepositoryError::ConstraintViolation(_))
+            ),
+            "{}",
+            backend.name
+        );
+        let mut organization = Organization::new();
+        organization.set_name("Engineering".to_string());
+        assert!(matches!(
+            repositories.organization.create(&organization).await,
+            Err(RepositoryError::ConstraintViolation(_))
+        ));
+
+        let mut other = backend.job("Tester").await;
+        other.set_name("Developer".to_string());
+        assert!(matches!(
+            repositories.job.update(&other).await,
+            Err(RepositoryError::ConstraintViolation(_))
+        ));
+    });
+}
+
+#[test]
+fn users_need_existing_jobs_and_organizations() {
+    on_both(|backend| async move {
+        let repositories = backend.repositories();
+        let job = backend.job("Developer").await;
+        let organization = backend.organization("Engineering").await;
+
+        assert!(
+            matches!(
+                repositories
+                    .user
+                    .create(&new_user("Ada Lovelace", 99, organization.id()))
+                    .await,
+                Err(RepositoryError::ConstraintViolation(_))
+            ),
+            "{}",
+            backend.name
+        );
+        assert!(matches!(
+            repositories
+                .user
+                .create(&new_user("Ada Lovelace", job.id(), 99))
+                .await,
+            Err(RepositoryError::ConstraintViolation(_))
+        ));
+    });
+}
+
+#[test]
+fn jobs_and_organizations_in_use_are_not_deleted() {
+    on_both(|backend| async move {
+        let repositories = backend.repositories();
+        let job = backend.job("Developer").await;
+        let organization = backend.organization("Engineering").await;
+        let user = backend.user("Ada Lovelace", &job, &organization).await;
+
+        assert!(
+            matches!(
+                repositories.job.delete(job.id()).await,
+                Err(RepositoryError::ConstraintViolation(_))
+            ),
+            "{}",
+            backend.name
+        );
+        assert!(matches!(
+            repositories.organization.delete(organization.id()).await,
+            Err(RepositoryError::ConstraintViolation(_))
+        ));
+
+        repositories.user.delete(user.id()).await.unwrap();
+        repositories.job.delete(job.id()).await.unwrap();
+        repositories
+            .organization
+            .delete(organization.id())
+            .await
+            .unwrap();
+    });
+}
+
+#[test]
+fn stale_versions_conflict() {
+    on_both(|backend| async move {
+        let repositories = backend.repositories();
+        let job = backend.job("Developer").await;
+        let organization = backend.organization("Engineering").await;
+        let user = backend.user("Ada Lovelace", &job, &organization).await;
+        assert_eq!(user.version(), 1, "{}", backend.name);
+
+        let mut renamed = user.clone();
+        renamed.set_name("Ada King".to_string());
+        repositories.user.update(&renamed).await.unwrap();
+        let stored = repositories.user.find_by_id(user.id()).await.unwrap();
+        assert_eq!(stored.map(|user| user.version()), Some(2));
+
+        let mut stale = user.clone();
+        stale.set_name("Augusta Ada".to_string());
+        assert!(matches!(
+            repositories.user.update(&stale).await,
+            Err(RepositoryError::Conflict)
+        ));
+        let mut stale_job = job.clone();
+        repositories.job.update(&job).await.unwrap();
+        stale_job.set_name("Engineer".to_string());
+        assert!(matches!(
+            repositories.job.update(&stale_job).await,
+            Err(RepositoryError::Conflict)
+        ));
+    });
+}
+
+#[test]
+fn transactions_commit_or_roll_back_together() {
+    on_both(|backend| async move {
+        let job = backend.job("Developer").await;
+        let organization = backend.organization("Engineering").await;
+
+        let transaction = backend.unit_of_work.begin().await.unwrap();
+        let user = transaction
+            .repositories()
+            .user
+            .create(&new_user("Ada Lovelace", job.id(), organization.id()))
+            .await
+            .unwrap();
+        transaction.repositories().job.delete(job.id()).await.ok();
+        drop(transaction);
+        let repositories = backend.repositories();
+        assert!(
+            repositories
+                .user
+                .find_by_id(user.id())
+                .await
+                .unwrap()
+                .is_none(),
+            "{}",
+            backend.name
+        );
+        assert!(repositories
+            .job
+            .find_by_id(job.id())
+            .await
+            .unwrap()
+            .is_some());
+
+        let transaction = backend.unit_of_work.begin().await.unwrap();
+        let user = transaction
+            .repositories()
+            .user
+            .create(&new_user("Alan Turing", job.id(), organization.id()))
+            .await
+            .unwrap();
+        // Beginning again joins the transaction; only the outer commit counts.
+        let joined = transaction.begin().await.unwrap();
+        joined
+            .repositories()
+            .job
+            .create(&{
+                let mut job = Job::new();
+                job.set_name("Tester".to_string());
+                job
+            })
+            .await
+            .unwrap();
+        joined.commit().await.unwrap();
+        transaction.commit().await.unwrap();
+
+        assert!(repositories
+            .user
+            .find_by_id(user.id())
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(repositories.job.find_all().await.unwrap().len(), 2);
+    });
+}
+
+#[test]
+fn pages_are_ordered_and_continue_from_the_cursor() {
+    on_both(|backend| async move {
+        let developer = backend.job("Developer").await;
+        let lead = backend.job("Team Lead").await;
+        let engineering = backend.organization("Engineering").await;
+        let sales = backend.organization("Sales").await;
+        for (name, job, organization) in [
+            ("Grace Hopper", &lead, &engineering),
+            ("Alan Turing", &developer, &sales),
+            ("Ada Lovelace", &developer, &engineering),
+            ("Alan Turing", &lead, &engineering),
+            ("Barbara Liskov", &developer, &sales),
+        ] {
+            backend.user(name, job, organization).await;
+        }
+        let repositories = backend.repositories();
+
+        let mut query = UserQuery {
+            sort: UserSortKey::Name,
+            limit: 2,
+            ..UserQuery::default()
+        };
+        let mut names = Vec::new();
+        loop {
+            let page = repositories.user.find_page(&query).await.unwrap();
+            assert_eq!(page.total, 5, "{}", backend.name);
+            names.extend(
+                page.users
+                    .iter()
+                    .map(|user| (user.name().to_string(), user.id())),
+            );
+            match page.next {
+                Some(next) => query.after = Some(next),
+                None => break,
+            }
+        }
+        // Equal names keep the order of their ids.
+        assert_eq!(
+            names,
+            [
+                ("Ada Lovelace".to_string(), 3),
+                ("Alan Turing".to_string(), 2),
+                ("Alan Turing".to_string(), 4),
+                ("Barbara Liskov".to_string(), 5),
+                ("Grace Hopper".to_string(), 1),
+            ],
+            "{}",
+            backend.name
+        );
+
+        let page = repositories
+            .user
+            .find_page(&UserQuery {
+                sort: UserSortKey::Job,
+                direction: SortDirection::Descending,
+                organization_id: Some(engineering.id()),
+                ..UserQuery::default()
+            })
+            .await
+            .unwrap();
+        let ids: Vec<i64> = page.users.iter().map(User::id).collect();
+        assert_eq!(ids, [4, 1, 3], "{}", backend.name);
+        assert_eq!(page.total, 3);
+        assert!(page.next.is_none());
+
+        let page = repositories
+            .user
+            .find_page(&UserQuery {
+                name_contains: Some(" alan ".to_string()),
+                job_id: Some(lead.id()),
+                ..UserQuery::default()
+            })
+            .await
+            .unwrap();
+        let ids: Vec<i64> = page.users.iter().map(User::id).collect();
+        assert_eq!(ids, [4], "{}", backend.name);
+    });
+}
+
+/// Failed writes and audit entries made outside a transaction leave it free
+/// to commit; only a real change to the data conflicts with it.
+#[test]
+fn memory_transactions_only_conflict_with_data_changes() {
+    smol::block_on(async {
+        let backend = Backend::memory();
+        let job = backend.job("Developer").await;
+        let organization = backend.organization("Engineering").await;
+        let repositories = backend.repositories();
+
+        let transaction = backend.unit_of_work.begin().await.unwrap();
+        transaction
+            .repositories()
+            .user
+            .create(&new_user("Ada Lovelace", job.id(), organization.id()))
+            .await
+            .unwrap();
+        assert!(repositories.user.delete(99).await.is_err());
+        assert!(repositories
+            .user
+            .create(&new_user("Alan Turing", 99, organization.id()))
+            .await
+            .is_err());
+        repositories
+            .audit
+            .record(&AuditEntry::new(
+                DomainEntity::Job,
+                job.id(),
+                AuditAction::Update,
+                "test",
+                None,
+                None,
+            ))
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+        assert_eq!(repositories.user.find_all().await.unwrap().len(), 1);
+        // The entry recorded meanwhile survives the commit.
+        let entries = repositories
+            .audit
+            .find(&AuditFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let transaction = backend.unit_of_work.begin().await.unwrap();
+        transaction.repositories().job.delete(job.id()).await.ok();
+        backend.organization("Sales").await;
+        assert!(matches!(
+            transaction.commit().await,
+            Err(RepositoryError::Conflict)
+        ));
+    });
+}
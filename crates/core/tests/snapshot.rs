@@ -0,0 +1,164 @@
+//! Exports snapshots and imports them into empty databases.
+
+use usermgmt_core::domain::{DomainEntity, Entity, Services};
+use usermgmt_core::infrastructure::{
+    Database, MemoryDatabase, Snapshot, SnapshotError, SnapshotRecord, SnapshotUser,
+    SNAPSHOT_FORMAT, SNAPSHOT_VERSION,
+};
+
+fn export(snapshot: &Snapshot, dir: &tempfile::TempDir, name: &str) -> Vec<u8> {
+    let path = dir.path().join(name);
+    snapshot.write(&path).expect("snapshot written");
+    std::fs::read(&path).expect("snapshot read")
+}
+
+fn records(records: &[(i64, &str)]) -> Vec<SnapshotRecord> {
+    records
+        .iter()
+        .map(|(id, name)| SnapshotRecord {
+            id: *id,
+            name: name.to_string(),
+        })
+        .collect()
+}
+
+fn snapshot_user(id: i64, name: &str, job_id: i64, organization_id: i64) -> SnapshotUser {
+    SnapshotUser {
+        id,
+        name: name.to_string(),
+        job_id,
+        organization_id,
+        email: String::new(),
+        phone: String::new(),
+    }
+}
+
+/// Each user's name with the names of their job and organization.
+async fn users_by_name(services: &Services) -> Vec<(String, String, String)> {
+    let jobs = services.job.get_all_jobs().await.unwrap();
+    let organizations = services.organization.get_all_organizations().await.unwrap();
+    let mut users: Vec<_> = services
+        .user
+        .get_all_users()
+        .await
+        .unwrap()
+        .iter()
+        .map(|user| {
+            let job = jobs.iter().find(|job| job.id() == user.job_id()).unwrap();
+            let organization = organizations
+                .iter()
+                .find(|organization| organization.id() == user.organization_id())
+                .unwrap();
+            (
+                user.name().to_string(),
+                job.name().to_string(),
+                organization.name().to_string(),
+            )
+        })
+        .collect();
+    users.sort();
+    users
+}
+
+#[test]
+fn a_restored_snapshot_exports_the_same_file() {
+    smol::block_on(async {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let source = MemoryDatabase::with_demo_data().await.unwrap();
+        let snapshot = Snapshot::capture(&source.services()).await.unwrap();
+        let exported = export(&snapshot, &dir, "first.json");
+        assert_eq!(
+            Snapshot::capture(&source.services()).await.unwrap(),
+            snapshot
+        );
+
+        let read = Snapshot::read(&dir.path().join("first.json")).unwrap();
+        assert_eq!(read, snapshot);
+        let target = MemoryDatabase::new();
+        let summary = read.restore(&target.services()).await.unwrap();
+        assert_eq!(summary, snapshot.summary());
+
+        let again = Snapshot::capture(&target.services()).await.unwrap();
+        assert_eq!(export(&again, &dir, "second.json"), exported);
+    });
+}
+
+#[test]
+fn restoring_gives_records_new_ids() {
+    smol::block_on(async {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let path = dir.path().join("restored.db");
+        let database = Database::open(&path.to_string_lossy(), false)
+            .await
+            .expect("database opens");
+        let snapshot = Snapshot {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            organizations: records(&[(40, "Engineering"), (7, "Research")]),
+            jobs: records(&[(12, "Developer"), (3, "Analyst")]),
+            users: vec![
+                snapshot_user(100, "Ada Lovelace", 3, 7),
+                snapshot_user(5, "Alan Turing", 12, 40),
+            ],
+        };
+
+        snapshot.restore(&database.services()).await.unwrap();
+
+        let restored = Snapshot::capture(&database.services()).await.unwrap();
+        let ids = |records: &[SnapshotRecord]| records.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(&restored.organizations), [1, 2]);
+        assert_eq!(ids(&restored.jobs), [1, 2]);
+        assert_eq!(
+            users_by_name(&database.services()).await,
+            [
+                (
+                    "Ada Lovelace".to_string(),
+                    "Analyst".to_string(),
+                    "Research".to_string()
+                ),
+                (
+                    "Alan Turing".to_string(),
+                    "Developer".to_string(),
+                    "Engineering".to_string()
+                ),
+            ]
+        );
+
+        assert!(matches!(
+            snapshot.restore(&database.services()).await,
+            Err(SnapshotError::NotEmpty)
+        ));
+    });
+}
+
+#[test]
+fn users_must_refer_to_records_in_the_snapshot() {
+    smol::block_on(async {
+        let database = MemoryDatabase::new();
+        let snapshot = Snapshot {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            organizations: records(&[(1, "Engineering")]),
+            jobs: records(&[(1, "Developer")]),
+            users: vec![snapshot_user(1, "Ada Lovelace", 2, 1)],
+        };
+
+        assert!(matches!(
+            snapshot.restore(&database.services()).await,
+            Err(SnapshotError::MissingReference {
+                user: 1,
+                entity: DomainEntity::Job,
+                id: 2,
+            })
+        ));
+        // Nothing of a failed restore is kept.
+        let services = database.services();
+        assert!(services.job.get_all_jobs().await.unwrap().is_empty());
+        assert!(services
+            .organization
+            .get_all_organizations()
+            .await
+            .unwrap()
+            .is_empty());
+    });
+}
@@ -0,0 +1,102 @@
+//! Scrolls a table of 100,000 users from top to bottom and times each frame.
+//!
+//! Run with `cargo bench --bench user_table`. Every frame applies a scroll
+//! message and builds the whole view, which is what iced does between
+//! redraws, and should stay well inside the 16 ms a 60 Hz display allows.
+//! Preferences are read from and written to a temporary config directory,
+//! never the user's own.
+
+use std::time::{Duration, Instant};
+
+use iced_user_management::app::AppState;
+use iced_user_management::config::StartupOptions;
+use iced_user_management::message::Message;
+use usermgmt_core::domain::{Entity, Job, Organization, User, UserPage};
+
+const USERS: i64 = 100_000;
+const JOBS: i64 = 200;
+const ORGANIZATIONS: i64 = 100;
+const FRAMES: usize = 2_000;
+const VIEWPORT_HEIGHT: f32 = 800.0;
+const FRAME_BUDGET: Duration = Duration::from_millis(16);
+
+fn main() {
+    let config_dir = tempfile::tempdir().expect("temporary config directory");
+    std::env::set_var("USERMGMT_CONFIG_DIR", config_dir.path());
+
+    let (mut state, _) = AppState::new(StartupOptions {
+        demo: true,
+        ..StartupOptions::default()
+    });
+    let _ = state.update(Message::JobsLoaded(jobs()));
+    let _ = state.update(Message::OrganizationsLoaded(organizations()));
+    let _ = state.update(Message::UserPageLoaded(UserPage {
+        users: users(),
+        total: USERS,
+        next: None,
+    }));
+
+    let started = Instant::now();
+    for user in state.users.list() {
+        std::hint::black_box(state.get_job_name(user.job_id()));
+        std::hint::black_box(state.get_organization_name(user.organization_id()));
+    }
+    println!("name lookups for {} users: {:?}", USERS, started.elapsed());
+
+    let table_height = state.user_table.height_of(USERS as usize);
+    let mut frames = Vec::with_capacity(FRAMES);
+    for frame in 0..FRAMES {
+        let offset = table_height * frame as f32 / FRAMES as f32;
+        let started = Instant::now();
+        let _ = state.update(Message::UserTableScrolled(offset, VIEWPORT_HEIGHT));
+        std::hint::black_box(state.view());
+        frames.push(started.elapsed());
+    }
+    frames.sort();
+
+    let mean = frames.iter().sum::<Duration>() / FRAMES as u32;
+    let p99 = frames[FRAMES * 99 / 100];
+    let max = frames[FRAMES - 1];
+    println!(
+        "scrolling {} users over {} frames: mean {:?}, p99 {:?}, max {:?}",
+        USERS, FRAMES, mean, p99, max
+    );
+    if p99 > FRAME_BUDGET {
+        println!("p99 frame time is over the {:?} budget", FRAME_BUDGET);
+    }
+}
+
+fn jobs() -> Vec<Job> {
+    (1..=JOBS)
+        .map(|id| {
+            let mut job = Job::new();
+            job.set_id(id);
+            job.set_name(format!("Job {}", id));
+            job
+        })
+        .collect()
+}
+
+fn organizations() -> Vec<Organization> {
+    (1..=ORGANIZATIONS)
+        .map(|id| {
+            let mut organization = Organization::new();
+            organization.set_id(id);
+            organization.set_name(format!("Organization {}", id));
+            organization
+        })
+        .collect()
+}
+
+fn users() -> Vec<User> {
+    (1..=USERS)
+        .map(|id| {
+            let mut user = User::new();
+            user.set_id(id);
+            user.set_name(format!("User {}", id));
+            user.set_job_id(id % JOBS + 1);
+            user.set_organization_id(id % ORGANIZATIONS + 1);
+            user
+        })
+        .collect()
+}
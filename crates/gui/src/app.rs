@@ -0,0 +1,1867 @@
+use iced::{Task, Theme};
+
+use iced::keyboard::{self, Key};
+use iced::widget::operation::{self, RelativeOffset};
+use iced::{time, window, Subscription};
+use std::time::{Duration, Instant};
+
+use crate::config::{theme_named, StartupOptions};
+use crate::domain::{
+    Command, CommandError, DomainEntity, Entity, Job, Organization, Services, User,
+};
+use crate::infrastructure::{
+    apply_ldif, export_contacts, export_ldif, generate_data, import_users, org_chart, parse_ldif,
+    write_rejected, BackupError, ChangeCounters, ChartFormat, CsvFile, Database, ExportError,
+    ExportTable, GeneratorOptions, ImportError, LdifError, LdifPlan, MemoryDatabase, Snapshot,
+    SnapshotError,
+};
+use crate::message::{Message, Page};
+use crate::state::{
+    AuditState, Conflict, EntityState, GeneratorState, ImportState, IntegrityState, LdifState,
+    Preferences, RecentDatabases, SearchResults, SearchState, UndoAction, UndoStack, UserPageState,
+    VirtualList, WindowGeometry,
+};
+use crate::view::{USER_ROW_HEIGHT, USER_TABLE};
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+/// How long the preferences must stay unchanged before they are written.
+const PREFERENCES_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+const READ_ONLY_MESSAGE: &str = "The database is open read-only";
+
+/// How often an open database file is checked for writes by other processes.
+const CHANGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct AppState {
+    pub current_page: Page,
+    pub active_entity: DomainEntity,
+    pub users: EntityState<User>,
+    pub user_page: UserPageState,
+    pub user_table: VirtualList,
+    pub organizations: EntityState<Organization>,
+    pub jobs: EntityState<Job>,
+    pub audit: AuditState,
+    pub integrity: IntegrityState,
+    pub generator: GeneratorState,
+    pub import: ImportState,
+    pub ldif: LdifState,
+    /// Sample data requested on the command line, generated once the
+    /// database is open.
+    pub pending_generation: Option<GeneratorOptions>,
+    pub search: SearchState,
+    pub undo_stack: UndoStack,
+    pub conflict: Option<Conflict>,
+    /// The table revisions seen at the last poll, to spot outside writes.
+    pub change_counters: Option<ChangeCounters>,
+    /// Whether the open database has change counters to poll.
+    pub tracks_changes: bool,
+    pub theme: Theme,
+    pub status_message: String,
+    pub database: Option<Database>,
+    pub is_demo: bool,
+    /// Set from the startup options; every database is then opened without
+    /// write access and commands are refused.
+    pub read_only: bool,
+    pub recent_databases: RecentDatabases,
+    pub preferences: Preferences,
+    /// When the preferences were last changed without being saved yet.
+    pub preferences_changed_at: Option<Instant>,
+    pub services: Option<Services>,
+}
+
+impl AppState {
+    /// Starts the app on the configured database, or on seeded in-memory
+    /// repositories in demo mode.
+    pub fn new(options: StartupOptions) -> (Self, Task<Message>) {
+        let task = if options.demo {
+            Task::perform(MemoryDatabase::with_demo_data(), |result| match result {
+                Ok(database) => Message::DemoInitialized(database),
+                Err(e) => Message::InitializationError(e.to_string()),
+            })
+        } else {
+            let db_path = options.database_path.clone();
+            let read_only = options.read_only;
+            Task::perform(
+                async move { Database::open(&db_path.to_string_lossy(), read_only).await },
+                |result| match result {
+                    Ok(database) => Message::AppInitialized(database),
+                    Err(e) => Message::InitializationError(e.to_string()),
+                },
+            )
+        };
+
+        let preferences = Preferences::load();
+        let theme = options
+            .theme
+            .clone()
+            .or_else(|| preferences.theme.as_deref().and_then(theme_named))
+            .unwrap_or(Theme::Dark);
+        let page = options
+            .page
+            .or_else(|| preferences.page.as_deref().and_then(Page::from_name))
+            .unwrap_or(Page::User);
+        let mut user_page = UserPageState::new();
+        user_page.query.sort = preferences.user_sort;
+        user_page.query.direction = preferences.user_sort_direction;
+        if preferences.user_page_size > 0 {
+            user_page.query.limit = preferences.user_page_size;
+        }
+
+        let mut state = Self {
+            current_page: Page::User,
+            active_entity: DomainEntity::User,
+            users: EntityState::new(),
+            user_page,
+            user_table: VirtualList::new(USER_ROW_HEIGHT),
+            organizations: EntityState::new(),
+            jobs: EntityState::new(),
+            audit: AuditState::new(),
+            integrity: IntegrityState::new(),
+            generator: GeneratorState::new(),
+            import: ImportState::new(),
+            ldif: LdifState::new(),
+            pending_generation: options.generate,
+            search: SearchState::new(),
+            undo_stack: UndoStack::new(),
+            conflict: None,
+            change_counters: None,
+            tracks_changes: false,
+            theme,
+            status_message: String::from("Loading..."),
+            database: None,
+            is_demo: false,
+            read_only: options.read_only,
+            recent_databases: RecentDatabases::load(),
+            preferences_changed_at: None,
+            services: None,
+            preferences,
+        };
+        state.set_current_page(page);
+        // Restoring the saved page is not a change worth saving.
+        state.preferences_changed_at = None;
+
+        (state, task)
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Navigate(page) => {
+                self.set_current_page(page);
+                if page == Page::Audit {
+                    return self.load_audit_entries();
+                }
+            }
+            Message::SearchChanged(query) => {
+                self.search.query = query.clone();
+                if query.trim().is_empty() {
+                    self.search.results = SearchResults::default();
+                    return Task::none();
+                }
+                if let Some(services) = self.services.clone() {
+                    return Task::perform(
+                        async move {
+                            let results = SearchResults {
+                                users: services
+                                    .user
+                                    .search_users(&query)
+                                    .await
+                                    .map_err(|e| e.to_string())?,
+                                jobs: services
+                                    .job
+                                    .search_jobs(&query)
+                                    .await
+                                    .map_err(|e| e.to_string())?,
+                                organizations: services
+                                    .organization
+                                    .search_organizations(&query)
+                                    .await
+                                    .map_err(|e| e.to_string())?,
+                            };
+                            Ok::<_, String>((query, results))
+                        },
+                        |result| match result {
+                            Ok((query, results)) => Message::SearchCompleted(query, results),
+                            Err(e) => Message::OperationFailed(e),
+                        },
+                    );
+                }
+            }
+            Message::SearchCompleted(query, results) => {
+                // Ignore results for text that has since been edited.
+                if query == self.search.query {
+                    self.search.results = results;
+                }
+            }
+            Message::SearchResultSelected(entity, id) => {
+                self.search.clear();
+                match entity {
+                    DomainEntity::User => {
+                        self.set_current_page(Page::User);
+                        return self.update(Message::UserLoad(id));
+                    }
+                    DomainEntity::Job => {
+                        self.set_current_page(Page::Job);
+                        return self.update(Message::JobLoad(id));
+                    }
+                    DomainEntity::Organization => {
+                        self.set_current_page(Page::Organization);
+                        return self.update(Message::OrganizationLoad(id));
+                    }
+                    DomainEntity::None => {}
+                }
+            }
+            Message::UserNameChanged(name) => {
+                self.users.current.set_name(name);
+                self.users.current.validate_property("name");
+            }
+            Message::UserJobSelected(job) => {
+                self.users.current.set_job_id(job.id());
+                self.users.current.validate_property("job_id");
+            }
+            Message::UserOrganizationSelected(organization) => {
+                self.users.current.set_organization_id(organization.id());
+                self.users.current.validate_property("organization_id");
+            }
+            Message::UserEmailChanged(email) => {
+                self.users.current.set_email(email);
+                self.users.current.validate_property("email");
+            }
+            Message::UserPhoneChanged(phone) => {
+                self.users.current.set_phone(phone);
+                self.users.current.validate_property("phone");
+            }
+            Message::JobClicked(job_id) => {
+                if let Some(job) = self.jobs.get(job_id).cloned() {
+                    self.set_current_page(Page::Job);
+                    return self.update(Message::JobLoaded(job));
+                }
+            }
+            Message::OrganizationClicked(organization_id) => {
+                if let Some(organization) = self.organizations.get(organization_id).cloned() {
+                    self.set_current_page(Page::Organization);
+                    return self.update(Message::OrganizationLoaded(organization));
+                }
+            }
+            Message::UserCreate => match self.users.current.validate() {
+                Ok(()) => {
+                    let user = self.users.current.clone();
+                    let description = format!("Create user {}", user.name());
+                    return self.execute(description, Command::CreateUser(user));
+                }
+                Err(_) => self.status_message = "Validation Errors".to_string(),
+            },
+            Message::UserUpdate => match self.users.current.validate() {
+                Ok(()) => {
+                    let user = self.users.current.clone();
+                    let description = format!("Update user {}", user.name());
+                    return self.execute(description, Command::UpdateUser(user));
+                }
+                Err(_) => self.status_message = "Validation Errors".to_string(),
+            },
+            Message::UserDelete(id) => {
+                let description = format!("Delete user {}", self.get_user_name(id));
+                return self.execute(description, Command::DeleteUser(id));
+            }
+            Message::UserToggleSelected(id, selected) => self.users.toggle_selected(id, selected),
+            Message::UserDeleteSelected => {
+                let commands: Vec<Command> = self
+                    .users
+                    .selected
+                    .iter()
+                    .map(|id| Command::DeleteUser(*id))
+                    .collect();
+                if !commands.is_empty() {
+                    let description = format!("Delete {} users", commands.len());
+                    return self.execute(description, Command::Batch(commands));
+                }
+            }
+
+            Message::ImportCsv => {
+                return Task::perform(
+                    async {
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Import users")
+                            .add_filter("CSV file", &["csv"])
+                            .pick_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        CsvFile::read(handle.path()).map(Some)
+                    },
+                    |result| match result {
+                        Ok(file) => Message::ImportFileLoaded(file),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::ImportFileLoaded(Some(file)) => {
+                self.status_message =
+                    format!("Read {} rows from {}", file.rows.len(), file.path.display());
+                self.import
+                    .load(file, self.jobs.list(), self.organizations.list());
+                self.set_current_page(Page::Import);
+            }
+            Message::ImportFileLoaded(None) => self.status_message = "Cancelled".to_string(),
+            Message::ImportColumnMapped(field, column) => {
+                self.import.map_column(
+                    field,
+                    Some(column.index),
+                    self.jobs.list(),
+                    self.organizations.list(),
+                );
+            }
+            Message::ImportColumnCleared(field) => {
+                self.import
+                    .map_column(field, None, self.jobs.list(), self.organizations.list());
+            }
+            Message::ImportCreateMissingToggled(create_missing) => {
+                self.import.set_create_missing(
+                    create_missing,
+                    self.jobs.list(),
+                    self.organizations.list(),
+                );
+            }
+            Message::ImportCommit => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                if self.read_only {
+                    self.status_message = READ_ONLY_MESSAGE.to_string();
+                    return Task::none();
+                }
+                let preview = self.import.preview.clone();
+                self.import.is_running = true;
+                return Task::perform(
+                    async move { import_users(&services, &preview).await },
+                    |result| match result {
+                        Ok(summary) => Message::ImportCompleted(summary),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::ImportCompleted(summary) => {
+                log::info!("{}", summary);
+                self.import = ImportState::new();
+                self.set_current_page(Page::User);
+                self.status_message = summary.to_string();
+                return self.reload(&DomainEntity::ALL);
+            }
+            Message::ImportSaveRejected => {
+                let Some(file) = self.import.file.clone() else {
+                    return Task::none();
+                };
+                let preview = self.import.preview.clone();
+                return Task::perform(
+                    async move {
+                        let file_name = file
+                            .path
+                            .file_stem()
+                            .map(|stem| format!("{}-rejected.csv", stem.to_string_lossy()))
+                            .unwrap_or_else(|| "rejected.csv".to_string());
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Save rejected rows")
+                            .add_filter("CSV file", &["csv"])
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        let count = write_rejected(&path, &file, &preview)?;
+                        Ok::<_, ImportError>(Some((path, count)))
+                    },
+                    |result| match result {
+                        Ok(saved) => Message::RejectedRowsSaved(saved),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::RejectedRowsSaved(Some((path, count))) => {
+                self.status_message =
+                    format!("Saved {} rejected rows to {}", count, path.display());
+            }
+            Message::RejectedRowsSaved(None) => self.status_message = "Cancelled".to_string(),
+            Message::ImportCancel => {
+                self.import = ImportState::new();
+                self.set_current_page(Page::User);
+                self.status_message = "Import cancelled".to_string();
+            }
+
+            Message::ExportList(entity) => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                let query = self.user_page.query.clone();
+                let jobs = self.jobs.list().to_vec();
+                let organizations = self.organizations.list().to_vec();
+                return Task::perform(
+                    async move {
+                        let table = match entity {
+                            DomainEntity::User => {
+                                ExportTable::users(&services, &query, &jobs, &organizations).await?
+                            }
+                            DomainEntity::Job => ExportTable::jobs(&jobs),
+                            DomainEntity::Organization => {
+                                ExportTable::organizations(&organizations)
+                            }
+                            DomainEntity::None => return Ok(None),
+                        };
+                        let file_name = format!(
+                            "{}-{}.csv",
+                            table.name.to_lowercase(),
+                            chrono::Local::now().format("%Y%m%d")
+                        );
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title(format!("Export {}", table.name.to_lowercase()))
+                            .add_filter("CSV file", &["csv"])
+                            .add_filter("Excel workbook", &["xlsx"])
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        let count = table.write(&path)?;
+                        Ok::<_, ExportError>(Some((path, count)))
+                    },
+                    |result| match result {
+                        Ok(exported) => Message::ListExported(exported),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::ListExported(Some((path, count))) => {
+                self.status_message = format!("Exported {} rows to {}", count, path.display());
+            }
+            Message::ListExported(None) => self.status_message = "Cancelled".to_string(),
+            Message::ExportContacts(scope) => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        let cards = export_contacts(&services, &scope).await?;
+                        let file_name: String = cards
+                            .name
+                            .chars()
+                            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+                            .collect();
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Export contacts")
+                            .add_filter("vCard", &["vcf"])
+                            .set_file_name(format!("{}.vcf", file_name))
+                            .save_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        std::fs::write(&path, cards.content)
+                            .map_err(|e| ExportError::Write(path.clone(), e))?;
+                        Ok::<_, ExportError>(Some((path, cards.users)))
+                    },
+                    |result| match result {
+                        Ok(exported) => Message::ContactsExported(exported),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::ContactsExported(Some((path, count))) => {
+                self.status_message = format!("Exported {} contacts to {}", count, path.display());
+            }
+            Message::ContactsExported(None) => self.status_message = "Cancelled".to_string(),
+            Message::OrgChartCollapseChanged(collapse) => {
+                self.preferences.org_chart = collapse;
+                self.preferences_changed();
+            }
+            Message::ExportOrgChart => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                let collapse = self.preferences.org_chart;
+                return Task::perform(
+                    async move {
+                        let chart = org_chart(&services, collapse).await?;
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Export org chart of users by organization (no managers)")
+                            .add_filter("SVG image", &["svg"])
+                            .add_filter("Graphviz DOT", &["dot", "gv"])
+                            .set_file_name("org-chart.svg")
+                            .save_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        let content = chart.render(ChartFormat::from_path(&path));
+                        std::fs::write(&path, content)
+                            .map_err(|e| ExportError::Write(path.clone(), e))?;
+                        Ok::<_, ExportError>(Some((path, chart.users)))
+                    },
+                    |result| match result {
+                        Ok(exported) => Message::OrgChartExported(exported),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::OrgChartExported(Some((path, count))) => {
+                self.status_message = format!(
+                    "Exported an org chart of {} users to {}",
+                    count,
+                    path.display()
+                );
+            }
+            Message::OrgChartExported(None) => self.status_message = "Cancelled".to_string(),
+
+            Message::UserLoad(id) => {
+                if let Some(services) = &self.services {
+                    let service = services.user.clone();
+                    return Task::perform(
+                        async move { service.get_user_by_id(id).await },
+                        |result| match result {
+                            Ok(Some(user)) => Message::UserLoaded(user),
+                            Ok(None) => Message::UserNotFound,
+                            Err(e) => Message::UserLoadError(e.to_string()),
+                        },
+                    );
+                } else {
+                    self.status_message = "Service not initialized".to_string();
+                }
+            }
+            Message::UserLoaded(user) => {
+                let id = user.id();
+                self.users.current = user;
+                self.users.is_edit = true;
+                self.status_message = "User loaded".to_string();
+                return self.load_history(DomainEntity::User, id);
+            }
+            Message::UserNotFound => {
+                self.status_message = "User not found".to_string();
+                self.users.current = User::new();
+            }
+            Message::UserLoadError(err) => {
+                self.status_message = format!("Error loading user: {}", err);
+                self.users.current = User::new();
+            }
+            Message::UserPageLoaded(page) => {
+                // Step back when the last rows of a trailing page were deleted.
+                if page.users.is_empty() && self.user_page.previous_page() {
+                    return self.load_users();
+                }
+                self.user_page.loaded(&page);
+                self.users.set_list(page.users);
+                self.users.retain_selection();
+            }
+            Message::UserFilterNameChanged(name) => {
+                self.user_page.query.name_contains = Some(name);
+                self.user_page.rewind();
+                return self.load_users_from_top();
+            }
+            Message::UserFilterJobSelected(job) => {
+                self.user_page.query.job_id = Some(job.id());
+                self.user_page.rewind();
+                return self.load_users_from_top();
+            }
+            Message::UserFilterOrganizationSelected(organization) => {
+                self.user_page.query.organization_id = Some(organization.id());
+                self.user_page.rewind();
+                return self.load_users_from_top();
+            }
+            Message::UserFilterClear => {
+                let query = &mut self.user_page.query;
+                query.name_contains = None;
+                query.job_id = None;
+                query.organization_id = None;
+                self.user_page.rewind();
+                return self.load_users_from_top();
+            }
+            Message::UserSortBy(key) => {
+                self.user_page.sort_by(key);
+                self.preferences.user_sort = self.user_page.query.sort;
+                self.preferences.user_sort_direction = self.user_page.query.direction;
+                self.preferences_changed();
+                return self.load_users_from_top();
+            }
+            Message::UserNextPage => {
+                if self.user_page.next_page() {
+                    return self.load_users_from_top();
+                }
+            }
+            Message::UserPreviousPage => {
+                if self.user_page.previous_page() {
+                    return self.load_users_from_top();
+                }
+            }
+            Message::UserPageSizeSelected(limit) => {
+                self.user_page.set_page_size(limit);
+                self.preferences.user_page_size = limit;
+                self.preferences_changed();
+                return self.load_users_from_top();
+            }
+            Message::UserTableScrolled(offset, viewport_height) => {
+                self.user_table.scrolled(offset, viewport_height);
+            }
+            Message::UserColumnWidthChanged(column, width) => {
+                self.preferences.user_columns.set(column, width);
+                self.preferences_changed();
+            }
+
+            Message::JobNameChanged(name) => {
+                self.jobs.current.set_name(name);
+                self.jobs.current.validate_property("name");
+            }
+            Message::JobCreate => match self.jobs.current.validate() {
+                Ok(()) => {
+                    let job = self.jobs.current.clone();
+                    let description = format!("Create job {}", job.name());
+                    return self.execute(description, Command::CreateJob(job));
+                }
+                Err(_) => self.status_message = "Validation Errors".to_string(),
+            },
+            Message::JobUpdate => match self.jobs.current.validate() {
+                Ok(()) => {
+                    let job = self.jobs.current.clone();
+                    let description = format!("Update job {}", job.name());
+                    return self.execute(description, Command::UpdateJob(job));
+                }
+                Err(_) => self.status_message = "Validation Errors".to_string(),
+            },
+            Message::JobDelete(id) => {
+                let description = format!("Delete job {}", self.get_job_name(id));
+                return self.execute(description, Command::DeleteJob(id));
+            }
+            Message::JobLoad(id) => {
+                if let Some(services) = &self.services {
+                    let service = services.job.clone();
+                    return Task::perform(
+                        async move { service.get_job_by_id(id).await },
+                        |result| match result {
+                            Ok(Some(job)) => Message::JobLoaded(job),
+                            Ok(None) => Message::OperationFailed("Job not found".to_string()),
+                            Err(e) => Message::OperationFailed(e.to_string()),
+                        },
+                    );
+                } else {
+                    self.status_message = "Service not initialized".to_string();
+                }
+            }
+            Message::JobLoaded(job) => {
+                let id = job.id();
+                self.jobs.current = job;
+                self.jobs.is_edit = true;
+                self.status_message = "Job loaded".to_string();
+                return self.load_history(DomainEntity::Job, id);
+            }
+            Message::JobsLoaded(jobs) => {
+                self.jobs.set_list(jobs);
+                self.import
+                    .refresh(self.jobs.list(), self.organizations.list());
+            }
+
+            Message::OrganizationNameChanged(name) => {
+                self.organizations.current.set_name(name);
+                self.organizations.current.validate_property("name");
+            }
+            Message::OrganizationCreate => match self.organizations.current.validate() {
+                Ok(()) => {
+                    let organization = self.organizations.current.clone();
+                    let description = format!("Create organization {}", organization.name());
+                    return self.execute(description, Command::CreateOrganization(organization));
+                }
+                Err(_) => self.status_message = "Validation Errors".to_string(),
+            },
+            Message::OrganizationUpdate => match self.organizations.current.validate() {
+                Ok(()) => {
+                    let organization = self.organizations.current.clone();
+                    let description = format!("Update organization {}", organization.name());
+                    return self.execute(description, Command::UpdateOrganization(organization));
+                }
+                Err(_) => self.status_message = "Validation Errors".to_string(),
+            },
+            Message::OrganizationDelete(id) => {
+                let description = format!("Delete organization {}", self.get_organization_name(id));
+                return self.execute(description, Command::DeleteOrganization(id));
+            }
+            Message::OrganizationLoad(id) => {
+                if let Some(services) = &self.services {
+                    let service = services.organization.clone();
+                    return Task::perform(
+                        async move { service.get_organization_by_id(id).await },
+                        |result| match result {
+                            Ok(Some(organization)) => Message::OrganizationLoaded(organization),
+                            Ok(None) => {
+                                Message::OperationFailed("Organization not found".to_string())
+                            }
+                            Err(e) => Message::OperationFailed(e.to_string()),
+                        },
+                    );
+                } else {
+                    self.status_message = "Service not initialized".to_string();
+                }
+            }
+            Message::OrganizationLoaded(organization) => {
+                let id = organization.id();
+                self.organizations.current = organization;
+                self.organizations.is_edit = true;
+                self.status_message = "Organization loaded".to_string();
+                return self.load_history(DomainEntity::Organization, id);
+            }
+            Message::OrganizationsLoaded(organizations) => {
+                self.organizations.set_list(organizations);
+                self.import
+                    .refresh(self.jobs.list(), self.organizations.list());
+            }
+
+            Message::Undo => {
+                if self.undo_stack.can_undo() {
+                    if let Some(entry) = self.undo_stack.take(UndoAction::Undo) {
+                        return self.run_command(
+                            UndoAction::Undo,
+                            entry.description,
+                            entry.command,
+                        );
+                    }
+                }
+            }
+            Message::Redo => {
+                if self.undo_stack.can_redo() {
+                    if let Some(entry) = self.undo_stack.take(UndoAction::Redo) {
+                        return self.run_command(
+                            UndoAction::Redo,
+                            entry.description,
+                            entry.command,
+                        );
+                    }
+                }
+            }
+            Message::CommandExecuted(action, description, inverse) => {
+                self.undo_stack.is_busy = false;
+                let affected = inverse.affected();
+                log::info!("{:?}: {}", action, description);
+                self.status_message = match action {
+                    UndoAction::Perform => description.clone(),
+                    UndoAction::Undo => format!("Undone: {}", description),
+                    UndoAction::Redo => format!("Redone: {}", description),
+                };
+                match action {
+                    UndoAction::Perform => self.undo_stack.record(description, inverse),
+                    UndoAction::Undo => {
+                        self.undo_stack.finish();
+                        self.undo_stack.push_redo(description, inverse);
+                    }
+                    UndoAction::Redo => {
+                        self.undo_stack.finish();
+                        self.undo_stack.push_undo(description, inverse);
+                    }
+                }
+                return self.reload(&affected);
+            }
+            Message::CommandFailed(action, err) => {
+                log::warn!("Command failed: {}", err);
+                self.undo_stack.is_busy = false;
+                if action != UndoAction::Perform {
+                    self.undo_stack.restore();
+                }
+                self.status_message = format!("Error: {}", err);
+            }
+
+            Message::ConflictDetected(action, entity, id) => {
+                self.undo_stack.is_busy = false;
+                self.status_message = format!("{} was modified by someone else", entity);
+                return self.load_conflict(action, entity, id);
+            }
+            Message::ConflictLoaded(conflict) => self.conflict = Some(conflict),
+            Message::ConflictKeepMine => {
+                if let Some(conflict) = self.conflict.take() {
+                    let (description, command) = conflict.keep_mine();
+                    // An undo or redo that conflicted finishes as itself.
+                    if let Some((action, entry)) = self.undo_stack.running() {
+                        let (action, description) = (*action, entry.description.clone());
+                        return self.run_command(action, description, command);
+                    }
+                    return self.execute(description, command);
+                }
+            }
+            Message::ConflictUseStored => {
+                // Keeping the stored values abandons a conflicting undo or redo.
+                self.undo_stack.finish();
+                match self.conflict.take() {
+                    Some(Conflict::User { theirs, .. }) => {
+                        return self.update(Message::UserLoaded(theirs))
+                    }
+                    Some(Conflict::Job { theirs, .. }) => {
+                        return self.update(Message::JobLoaded(theirs))
+                    }
+                    Some(Conflict::Organization { theirs, .. }) => {
+                        return self.update(Message::OrganizationLoaded(theirs))
+                    }
+                    None => {}
+                }
+            }
+            Message::ConflictDismiss => {
+                // A conflicting undo or redo can be tried again later.
+                self.undo_stack.restore();
+                self.conflict = None;
+            }
+
+            Message::PollChanges => {
+                if let Some(database) = self.database.clone().filter(|_| self.tracks_changes) {
+                    return Task::perform(
+                        async move { database.change_counters().await },
+                        |result| match result {
+                            Ok(counters) => Message::ChangesPolled(counters),
+                            Err(e) => Message::OperationFailed(e.to_string()),
+                        },
+                    );
+                }
+            }
+            Message::ChangesPolled(None) => {
+                log::info!("The database has no change counters, outside changes are not shown");
+                self.tracks_changes = false;
+            }
+            Message::ChangesPolled(Some(counters)) => {
+                let previous = self.change_counters.replace(counters);
+                if let Some(previous) = previous {
+                    let affected = counters.changed_since(&previous);
+                    if !affected.is_empty() {
+                        log::debug!("Database changed outside the app: {:?}", affected);
+                        return self.refresh(&affected);
+                    }
+                }
+            }
+            Message::EditedRecordChanged(entity, id) => {
+                self.status_message = format!("{} was modified by someone else", entity);
+                return self.load_conflict(UndoAction::Perform, entity, id);
+            }
+            Message::EditedRecordDeleted(entity) => {
+                self.status_message = format!(
+                    "The {} you are editing was deleted by someone else",
+                    entity.as_str()
+                );
+            }
+
+            Message::AuditEntityFilterSelected(entity_type) => {
+                self.audit.filter.entity_type = Some(entity_type);
+            }
+            Message::AuditActionFilterSelected(action) => {
+                self.audit.filter.action = Some(action);
+            }
+            Message::AuditFromChanged(value) => self.audit.from_input = value,
+            Message::AuditToChanged(value) => self.audit.to_input = value,
+            Message::AuditApplyFilter => {
+                match (
+                    parse_date_input(&self.audit.from_input),
+                    parse_date_input(&self.audit.to_input),
+                ) {
+                    (Ok(from), Ok(to)) => {
+                        self.audit.filter.from = from;
+                        self.audit.filter.to = to;
+                        return self.load_audit_entries();
+                    }
+                    _ => {
+                        self.status_message = "Dates must use the YYYY-MM-DD format".to_string();
+                    }
+                }
+            }
+            Message::AuditClearFilter => {
+                self.audit.clear_filter();
+                return self.load_audit_entries();
+            }
+            Message::AuditEntriesLoaded(entries) => self.audit.entries = entries,
+            Message::AuditHistoryLoaded(entries) => self.audit.history = entries,
+
+            Message::CancelEdit => match self.current_page {
+                Page::User => self.users.cancel_edit(),
+                Page::Job => self.jobs.cancel_edit(),
+                Page::Organization => self.organizations.cancel_edit(),
+                _ => {}
+            },
+            Message::ThemeChanged(theme) => {
+                self.preferences.theme = Some(theme.to_string());
+                self.preferences_changed();
+                self.theme = theme;
+            }
+            Message::WindowResized(size) => {
+                let geometry = self.window_geometry();
+                geometry.width = size.width;
+                geometry.height = size.height;
+                self.preferences_changed();
+            }
+            Message::WindowMoved(position) => {
+                let geometry = self.window_geometry();
+                geometry.x = Some(position.x);
+                geometry.y = Some(position.y);
+                self.preferences_changed();
+            }
+            Message::WindowCloseRequested => {
+                self.save_preferences();
+                return iced::exit();
+            }
+            Message::SavePreferences => {
+                if self
+                    .preferences_changed_at
+                    .is_some_and(|changed| changed.elapsed() >= PREFERENCES_SAVE_DELAY)
+                {
+                    self.save_preferences();
+                }
+            }
+
+            Message::AppInitialized(database) => {
+                self.status_message = "Ready".to_string();
+                let task = self.use_database(database);
+                return Task::batch([task, self.generate_pending()]);
+            }
+            Message::DemoInitialized(database) => {
+                self.status_message = "Demo mode: changes are kept in memory only".to_string();
+                self.is_demo = true;
+                let task = self.use_services(database.services());
+                return Task::batch([task, self.generate_pending()]);
+            }
+            Message::DatabaseOpened(database) => {
+                self.status_message = format!("Opened {}", database.path.display());
+                return self.use_database(database);
+            }
+            Message::NewDatabase if self.read_only => {
+                self.status_message = READ_ONLY_MESSAGE.to_string();
+            }
+            Message::NewDatabase => {
+                return Task::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .set_title("New database")
+                            .add_filter("SQLite database", &["db"])
+                            .set_file_name("app.db")
+                            .save_file()
+                            .await
+                            .map(|handle| handle.path().to_path_buf())
+                    },
+                    Message::DatabaseSelected,
+                )
+            }
+            Message::OpenDatabase => {
+                return Task::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .set_title("Open database")
+                            .add_filter("SQLite database", &["db"])
+                            .pick_file()
+                            .await
+                            .map(|handle| handle.path().to_path_buf())
+                    },
+                    Message::DatabaseSelected,
+                )
+            }
+            Message::DatabaseSelected(Some(path)) => return open_database(path, self.read_only),
+            Message::DatabaseSelected(None) => self.status_message = "Cancelled".to_string(),
+            Message::OpenRecentDatabase(recent) => return open_database(recent.0, self.read_only),
+            Message::BackupDatabase => {
+                if let Some(database) = &self.database {
+                    let database = database.clone();
+                    return Task::perform(
+                        async move {
+                            let file_name = format!(
+                                "app-backup-{}.db",
+                                chrono::Local::now().format("%Y%m%d-%H%M%S")
+                            );
+                            let Some(handle) = rfd::AsyncFileDialog::new()
+                                .set_title("Back up database")
+                                .add_filter("SQLite database", &["db"])
+                                .set_file_name(file_name)
+                                .save_file()
+                                .await
+                            else {
+                                return Ok(None);
+                            };
+                            let path = handle.path().to_path_buf();
+                            database.backup_to(&path).await?;
+                            Ok::<_, BackupError>(Some(path))
+                        },
+                        |result| match result {
+                            Ok(path) => Message::BackupCompleted(path),
+                            Err(e) => Message::OperationFailed(e.to_string()),
+                        },
+                    );
+                } else {
+                    self.status_message = "Database not initialized".to_string();
+                }
+            }
+            Message::BackupCompleted(Some(path)) => {
+                self.status_message = format!("Database backed up to {}", path.display());
+            }
+            Message::BackupCompleted(None) => self.status_message = "Backup cancelled".to_string(),
+            Message::RestoreDatabase if self.read_only => {
+                self.status_message = READ_ONLY_MESSAGE.to_string();
+            }
+            Message::RestoreDatabase => {
+                if let Some(database) = &self.database {
+                    let database = database.clone();
+                    return Task::perform(
+                        async move {
+                            let Some(handle) = rfd::AsyncFileDialog::new()
+                                .set_title("Restore from backup")
+                                .add_filter("SQLite database", &["db"])
+                                .pick_file()
+                                .await
+                            else {
+                                return Ok(None);
+                            };
+                            let confirmed = rfd::AsyncMessageDialog::new()
+                                .set_level(rfd::MessageLevel::Warning)
+                                .set_title("Restore from backup")
+                                .set_description(
+                                    "All current users, jobs, organizations and audit history \
+                                     will be replaced by the backup. Continue?",
+                                )
+                                .set_buttons(rfd::MessageButtons::YesNo)
+                                .show()
+                                .await;
+                            if confirmed != rfd::MessageDialogResult::Yes {
+                                return Ok(None);
+                            }
+                            let path = handle.path().to_path_buf();
+                            database.restore_from(&path).await?;
+                            Ok::<_, BackupError>(Some(path))
+                        },
+                        |result| match result {
+                            Ok(path) => Message::RestoreCompleted(path),
+                            Err(e) => Message::OperationFailed(e.to_string()),
+                        },
+                    );
+                } else {
+                    self.status_message = "Database not initialized".to_string();
+                }
+            }
+            Message::RestoreCompleted(Some(path)) => {
+                self.status_message = format!("Database restored from {}", path.display());
+                self.undo_stack.clear();
+                self.audit.entries.clear();
+                return self.reload(&DomainEntity::ALL);
+            }
+            Message::RestoreCompleted(None) => {
+                self.status_message = "Restore cancelled".to_string()
+            }
+            Message::ExportSnapshot => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        let snapshot = Snapshot::capture(&services).await?;
+                        let file_name = format!(
+                            "snapshot-{}.json",
+                            chrono::Local::now().format("%Y%m%d-%H%M%S")
+                        );
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Export snapshot")
+                            .add_filter("JSON snapshot", &["json"])
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        snapshot.write(&path)?;
+                        Ok::<_, SnapshotError>(Some((path, snapshot.summary())))
+                    },
+                    |result| match result {
+                        Ok(exported) => Message::SnapshotExported(exported),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::SnapshotExported(Some((path, summary))) => {
+                self.status_message = format!("Exported {} to {}", summary, path.display());
+            }
+            Message::SnapshotExported(None) => self.status_message = "Cancelled".to_string(),
+            Message::ImportSnapshot if self.read_only => {
+                self.status_message = READ_ONLY_MESSAGE.to_string();
+            }
+            Message::ImportSnapshot => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Import snapshot")
+                            .add_filter("JSON snapshot", &["json"])
+                            .pick_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        let summary = Snapshot::read(&path)?.restore(&services).await?;
+                        Ok::<_, SnapshotError>(Some((path, summary)))
+                    },
+                    |result| match result {
+                        Ok(imported) => Message::SnapshotImported(imported),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::SnapshotImported(Some((path, summary))) => {
+                self.status_message = format!("Imported {} from {}", summary, path.display());
+                return self.reload(&DomainEntity::ALL);
+            }
+            Message::SnapshotImported(None) => self.status_message = "Cancelled".to_string(),
+            Message::LdifOptionChanged(field, value) => {
+                self.preferences.ldif.set(field, value);
+                self.preferences_changed();
+            }
+            Message::ExportLdif => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                let options = self.preferences.ldif.clone();
+                return Task::perform(
+                    async move {
+                        let (content, count) = export_ldif(&services, &options).await?;
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Export LDIF")
+                            .add_filter("LDIF file", &["ldif"])
+                            .set_file_name("directory.ldif")
+                            .save_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        std::fs::write(&path, content)
+                            .map_err(|e| LdifError::Write(path.clone(), e))?;
+                        Ok::<_, LdifError>(Some((path, count)))
+                    },
+                    |result| match result {
+                        Ok(exported) => Message::LdifExported(exported),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::LdifExported(Some((path, count))) => {
+                self.status_message = format!("Exported {} users to {}", count, path.display());
+            }
+            Message::LdifExported(None) => self.status_message = "Cancelled".to_string(),
+            Message::ImportLdif => {
+                let Some(services) = self.services.clone() else {
+                    self.status_message = "Service not initialized".to_string();
+                    return Task::none();
+                };
+                let options = self.preferences.ldif.clone();
+                return Task::perform(
+                    async move {
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_title("Import LDIF")
+                            .add_filter("LDIF file", &["ldif"])
+                            .pick_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        let path = handle.path().to_path_buf();
+                        let content = std::fs::read_to_string(&path)
+                            .map_err(|e| LdifError::Read(path.clone(), e))?;
+                        let entries = parse_ldif(&content)?;
+                        let plan = LdifPlan::new(
+                            &entries,
+                            &options,
+                            &services
+                                .user
+                                .get_all_users()
+                                .await
+                                .map_err(CommandError::from)?,
+                            &services
+                                .job
+                                .get_all_jobs()
+                                .await
+                                .map_err(CommandError::from)?,
+                            &services
+                                .organization
+                                .get_all_organizations()
+                                .await
+                                .map_err(CommandError::from)?,
+                        );
+                        Ok::<_, LdifError>(Some((path, plan)))
+                    },
+                    |result| match result {
+                        Ok(planned) => Message::LdifPlanned(planned),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::LdifPlanned(Some((path, plan))) => {
+                self.status_message = format!(
+                    "Read {}: {} users to change, {} unchanged, {} skipped",
+                    path.display(),
+                    plan.changes.len(),
+                    plan.unchanged,
+                    plan.skipped.len()
+                );
+                self.ldif.planned(path, plan);
+            }
+            Message::LdifPlanned(None) => self.status_message = "Cancelled".to_string(),
+            Message::LdifApply => {
+                let (Some(services), Some(plan)) = (self.services.clone(), self.ldif.plan.clone())
+                else {
+                    return Task::none();
+                };
+                if self.read_only {
+                    self.status_message = READ_ONLY_MESSAGE.to_string();
+                    return Task::none();
+                }
+                self.ldif.is_running = true;
+                return Task::perform(
+                    async move { apply_ldif(&services, &plan).await },
+                    |result| match result {
+                        Ok(summary) => Message::LdifApplied(summary),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                );
+            }
+            Message::LdifApplied(summary) => {
+                log::info!("{}", summary);
+                self.status_message = summary.to_string();
+                self.ldif.clear();
+                return self.reload(&DomainEntity::ALL);
+            }
+            Message::LdifDiscard => self.ldif.clear(),
+            Message::CheckIntegrity => return self.check_integrity(),
+            Message::IntegrityChecked(report) => {
+                self.integrity.is_running = false;
+                self.status_message = if report.is_healthy() {
+                    "No integrity problems found".to_string()
+                } else {
+                    "Integrity problems found".to_string()
+                };
+                self.integrity.report = Some(report);
+            }
+            Message::IntegrityReplacementJobSelected(job) => {
+                self.integrity.replacement_job = Some(job);
+            }
+            Message::IntegrityReplacementOrganizationSelected(organization) => {
+                self.integrity.replacement_organization = Some(organization);
+            }
+            Message::RepairReassignUser(id) => {
+                let Some(orphan) = self.integrity.orphan(id) else {
+                    return Task::none();
+                };
+                match self.integrity.reassign(orphan) {
+                    Some(command) => {
+                        let description = format!("Reassign user {}", orphan.user.name());
+                        return self.execute(description, command);
+                    }
+                    None => self.status_message = "Choose a replacement first".to_string(),
+                }
+            }
+            Message::RepairReassignAll => match self.integrity.reassign_all() {
+                Some(command) => {
+                    return self.execute("Reassign orphaned users".to_string(), command)
+                }
+                None => self.status_message = "Choose a replacement first".to_string(),
+            },
+            Message::RepairDeleteUser(id) => {
+                if let Some(orphan) = self.integrity.orphan(id) {
+                    let description = format!("Delete user {}", orphan.user.name());
+                    return self.execute(description, Command::DeleteUser(id));
+                }
+            }
+            Message::RepairDeleteAll => {
+                if let Some(command) = self.integrity.delete_all() {
+                    return self.execute("Delete orphaned users".to_string(), command);
+                }
+            }
+            Message::GeneratorOrganizationsChanged(value) => self.generator.organizations = value,
+            Message::GeneratorJobsChanged(value) => self.generator.jobs = value,
+            Message::GeneratorUsersChanged(value) => self.generator.users = value,
+            Message::GeneratorSeedChanged(value) => self.generator.seed = value,
+            Message::GenerateData => match self.generator.options() {
+                Ok(options) => return self.generate(options),
+                Err(e) => self.status_message = e,
+            },
+            Message::DataGenerated(generated) => {
+                self.generator.is_running = false;
+                self.status_message = format!("Generated {}", generated);
+                return self.reload(&DomainEntity::ALL);
+            }
+            Message::InitializationError(err) => {
+                log::error!("Initialization failed: {}", err);
+                self.status_message = err;
+            }
+            Message::OperationFailed(err) => {
+                log::warn!("Operation failed: {}", err);
+                self.generator.is_running = false;
+                self.import.is_running = false;
+                self.ldif.is_running = false;
+                self.integrity.is_running = false;
+                self.status_message = format!("Error: {}", err);
+            }
+        }
+        Task::none()
+    }
+
+    pub fn set_current_page(&mut self, page: Page) {
+        // The import wizard needs a file, so it is never reopened on start.
+        if self.current_page != page && page != Page::Import {
+            self.preferences.page = Some(page.as_str().to_string());
+            self.preferences_changed();
+        }
+        self.audit.history.clear();
+        self.conflict = None;
+        match page {
+            Page::User => {
+                self.users.cancel_edit();
+                self.current_page = Page::User;
+                self.active_entity = DomainEntity::User;
+            }
+            Page::Job => {
+                self.jobs.cancel_edit();
+                self.current_page = Page::Job;
+                self.active_entity = DomainEntity::Job;
+            }
+            Page::Organization => {
+                self.organizations.cancel_edit();
+                self.current_page = Page::Organization;
+                self.active_entity = DomainEntity::Organization;
+            }
+            Page::Audit => {
+                self.current_page = Page::Audit;
+                self.active_entity = DomainEntity::None;
+            }
+            Page::Settings => {
+                self.current_page = Page::Settings;
+                self.active_entity = DomainEntity::None;
+            }
+            Page::Import => {
+                self.current_page = Page::Import;
+                self.active_entity = DomainEntity::User;
+            }
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let shortcuts = keyboard::listen().filter_map(|event| match event {
+            keyboard::Event::KeyPressed { key, modifiers, .. } if modifiers.command() => {
+                match key.as_ref() {
+                    Key::Character("z") if modifiers.shift() => Some(Message::Redo),
+                    Key::Character("z") => Some(Message::Undo),
+                    Key::Character("y") => Some(Message::Redo),
+                    _ => None,
+                }
+            }
+            _ => None,
+        });
+
+        let window_events = window::events().filter_map(|(_, event)| match event {
+            window::Event::Resized(size) => Some(Message::WindowResized(size)),
+            window::Event::Moved(position) => Some(Message::WindowMoved(position)),
+            window::Event::CloseRequested => Some(Message::WindowCloseRequested),
+            _ => None,
+        });
+
+        let mut subscriptions = vec![shortcuts, window_events];
+        // Only a database file can be changed by another process.
+        if self.database.is_some() && self.tracks_changes {
+            subscriptions.push(time::every(CHANGE_POLL_INTERVAL).map(|_| Message::PollChanges));
+        }
+        if self.preferences_changed_at.is_some() {
+            subscriptions
+                .push(time::every(PREFERENCES_SAVE_DELAY).map(|_| Message::SavePreferences));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    /// Marks the preferences for saving once they have been left alone for
+    /// `PREFERENCES_SAVE_DELAY`, so dragging the window does not write the
+    /// file on every frame.
+    fn preferences_changed(&mut self) {
+        self.preferences_changed_at = Some(Instant::now());
+    }
+
+    fn save_preferences(&mut self) {
+        if self.preferences_changed_at.take().is_none() {
+            return;
+        }
+        if let Err(e) = self.preferences.save() {
+            log::warn!("Could not save preferences: {}", e);
+            self.status_message = format!("Error saving preferences: {}", e);
+        }
+    }
+
+    fn window_geometry(&mut self) -> &mut WindowGeometry {
+        self.preferences
+            .window
+            .get_or_insert_with(WindowGeometry::default)
+    }
+
+    pub fn title(&self) -> String {
+        match &self.database {
+            Some(database) => format!(
+                "User Management - {}{}",
+                database
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                if database.read_only {
+                    " (read-only)"
+                } else {
+                    ""
+                }
+            ),
+            None if self.is_demo => "User Management - Demo".to_string(),
+            None => "User Management".to_string(),
+        }
+    }
+
+    /// Switches to a newly opened database, rebuilding the services and
+    /// discarding everything loaded from the previous one.
+    fn use_database(&mut self, database: Database) -> Task<Message> {
+        let services = database.services();
+        self.recent_databases.add(&database.path);
+        if let Err(e) = self.recent_databases.save() {
+            self.status_message = format!("Error saving recent databases: {}", e);
+        }
+        self.database = Some(database);
+        self.is_demo = false;
+        self.tracks_changes = true;
+        self.use_services(services)
+    }
+
+    /// Swaps in a new set of services and resets all loaded state.
+    fn use_services(&mut self, services: Services) -> Task<Message> {
+        self.services = Some(services);
+
+        self.users = EntityState::new();
+        self.user_page.reset();
+        self.user_table.scroll_to_top();
+        self.jobs = EntityState::new();
+        self.organizations = EntityState::new();
+        self.audit = AuditState::new();
+        self.integrity = IntegrityState::new();
+        self.search = SearchState::new();
+        self.undo_stack.clear();
+        self.conflict = None;
+        self.change_counters = None;
+
+        let mut tasks = vec![
+            self.load_users(),
+            self.load_jobs(),
+            self.load_organizations(),
+        ];
+        if self.current_page == Page::Audit {
+            tasks.push(self.load_audit_entries());
+        }
+        Task::batch(tasks)
+    }
+
+    fn generate_pending(&mut self) -> Task<Message> {
+        match self.pending_generation.take() {
+            Some(options) => self.generate(options),
+            None => Task::none(),
+        }
+    }
+
+    /// Adds generated sample data. It is not recorded for undo, but every
+    /// record goes through the services and shows up in the audit log.
+    fn generate(&mut self, options: GeneratorOptions) -> Task<Message> {
+        let Some(services) = self.services.clone() else {
+            self.status_message = "Service not initialized".to_string();
+            return Task::none();
+        };
+        if self.read_only {
+            self.status_message = READ_ONLY_MESSAGE.to_string();
+            return Task::none();
+        }
+
+        self.generator.is_running = true;
+        self.status_message = format!("Generating {} users...", options.users);
+        Task::perform(
+            async move { generate_data(&services, options).await },
+            |result| match result {
+                Ok(generated) => Message::DataGenerated(generated),
+                Err(e) => Message::OperationFailed(e.to_string()),
+            },
+        )
+    }
+
+    /// Runs a new data-changing command and records its inverse for undo.
+    fn execute(&mut self, description: String, command: Command) -> Task<Message> {
+        self.run_command(UndoAction::Perform, description, command)
+    }
+
+    fn run_command(
+        &mut self,
+        action: UndoAction,
+        description: String,
+        command: Command,
+    ) -> Task<Message> {
+        let services = match self.services.clone() {
+            Some(_) if self.read_only => Err(READ_ONLY_MESSAGE),
+            Some(services) => Ok(services),
+            None => Err("Service not initialized"),
+        };
+        let services = match services {
+            Ok(services) => services,
+            Err(message) => {
+                self.status_message = message.to_string();
+                if action != UndoAction::Perform {
+                    self.undo_stack.restore();
+                }
+                return Task::none();
+            }
+        };
+
+        self.undo_stack.is_busy = true;
+        let target = command.update_target();
+        Task::perform(
+            async move { command.execute(&services).await },
+            move |result| match result {
+                Ok(inverse) => Message::CommandExecuted(action, description.clone(), inverse),
+                Err(e) => match target {
+                    Some((entity, id)) if e.is_conflict() => {
+                        Message::ConflictDetected(action, entity, id)
+                    }
+                    _ => Message::CommandFailed(action, e.to_string()),
+                },
+            },
+        )
+    }
+
+    /// Fetches the stored version of a conflicting record to compare with the
+    /// form, or with what an undo or redo was writing.
+    fn load_conflict(&self, action: UndoAction, entity: DomainEntity, id: i64) -> Task<Message> {
+        let Some(services) = self.services.clone() else {
+            return Task::none();
+        };
+        let command = match self.undo_stack.running() {
+            Some((_, entry)) if action != UndoAction::Perform => Some(&entry.command),
+            _ => None,
+        };
+        // A failed undo or redo puts its entry back.
+        let failed = move |message: String| match action {
+            UndoAction::Perform => Message::OperationFailed(message),
+            action => Message::CommandFailed(action, message),
+        };
+
+        match entity {
+            DomainEntity::User => {
+                let mine = match command {
+                    Some(Command::UpdateUser(user)) => user.clone(),
+                    _ => self.users.current.clone(),
+                };
+                Task::perform(
+                    async move { services.user.get_user_by_id(id).await },
+                    move |result| match result {
+                        Ok(Some(theirs)) => Message::ConflictLoaded(Conflict::User {
+                            mine: mine.clone(),
+                            theirs,
+                        }),
+                        Ok(None) => failed("User was deleted by someone else".to_string()),
+                        Err(e) => failed(e.to_string()),
+                    },
+                )
+            }
+            DomainEntity::Job => {
+                let mine = match command {
+                    Some(Command::UpdateJob(job)) => job.clone(),
+                    _ => self.jobs.current.clone(),
+                };
+                Task::perform(
+                    async move { services.job.get_job_by_id(id).await },
+                    move |result| match result {
+                        Ok(Some(theirs)) => Message::ConflictLoaded(Conflict::Job {
+                            mine: mine.clone(),
+                            theirs,
+                        }),
+                        Ok(None) => failed("Job was deleted by someone else".to_string()),
+                        Err(e) => failed(e.to_string()),
+                    },
+                )
+            }
+            DomainEntity::Organization => {
+                let mine = match command {
+                    Some(Command::UpdateOrganization(organization)) => organization.clone(),
+                    _ => self.organizations.current.clone(),
+                };
+                Task::perform(
+                    async move { services.organization.get_organization_by_id(id).await },
+                    move |result| match result {
+                        Ok(Some(theirs)) => Message::ConflictLoaded(Conflict::Organization {
+                            mine: mine.clone(),
+                            theirs,
+                        }),
+                        Ok(None) => failed("Organization was deleted by someone else".to_string()),
+                        Err(e) => failed(e.to_string()),
+                    },
+                )
+            }
+            DomainEntity::None => Task::none(),
+        }
+    }
+
+    /// Reloads lists that another process changed. Unlike `reload` it keeps
+    /// the forms, but warns when the record being edited changed underneath.
+    fn refresh(&mut self, affected: &[DomainEntity]) -> Task<Message> {
+        let mut tasks = Vec::new();
+        for entity in affected {
+            match entity {
+                DomainEntity::User => {
+                    tasks.push(self.load_users());
+                    if self.users.is_edit {
+                        tasks.push(self.check_edited(*entity, &self.users.current));
+                    }
+                }
+                DomainEntity::Job => {
+                    tasks.push(self.load_jobs());
+                    if self.jobs.is_edit {
+                        tasks.push(self.check_edited(*entity, &self.jobs.current));
+                    }
+                }
+                DomainEntity::Organization => {
+                    tasks.push(self.load_organizations());
+                    if self.organizations.is_edit {
+                        tasks.push(self.check_edited(*entity, &self.organizations.current));
+                    }
+                }
+                DomainEntity::None => {}
+            }
+        }
+        if self.current_page == Page::Audit {
+            tasks.push(self.load_audit_entries());
+        }
+        Task::batch(tasks)
+    }
+
+    /// Compares the stored version of the record in a form with the one the
+    /// form was loaded from.
+    fn check_edited(&self, entity: DomainEntity, current: &impl Entity) -> Task<Message> {
+        let Some(services) = self.services.clone() else {
+            return Task::none();
+        };
+        let (id, version) = (current.id(), current.version());
+
+        Task::perform(
+            async move {
+                match entity {
+                    DomainEntity::User => services
+                        .user
+                        .get_user_by_id(id)
+                        .await
+                        .map(|user| user.map(|user| user.version()))
+                        .map_err(|e| e.to_string()),
+                    DomainEntity::Job => services
+                        .job
+                        .get_job_by_id(id)
+                        .await
+                        .map(|job| job.map(|job| job.version()))
+                        .map_err(|e| e.to_string()),
+                    DomainEntity::Organization => services
+                        .organization
+                        .get_organization_by_id(id)
+                        .await
+                        .map(|organization| organization.map(|organization| organization.version()))
+                        .map_err(|e| e.to_string()),
+                    DomainEntity::None => Ok(Some(version)),
+                }
+            },
+            move |result| match result {
+                Ok(Some(stored)) if stored == version => None,
+                Ok(Some(_)) => Some(Message::EditedRecordChanged(entity, id)),
+                Ok(None) => Some(Message::EditedRecordDeleted(entity)),
+                Err(e) => Some(Message::OperationFailed(e)),
+            },
+        )
+        .and_then(Task::done)
+    }
+
+    /// Reloads the given entity lists and leaves any edit on them.
+    fn reload(&mut self, affected: &[DomainEntity]) -> Task<Message> {
+        // The app's own write moved the counters; take them again as the
+        // baseline rather than refreshing for it at the next poll.
+        self.change_counters = None;
+        let mut tasks = vec![Task::done(Message::PollChanges)];
+        for entity in affected {
+            match entity {
+                DomainEntity::User => {
+                    self.users.cancel_edit();
+                    tasks.push(self.load_users());
+                }
+                DomainEntity::Job => {
+                    self.jobs.cancel_edit();
+                    tasks.push(self.load_jobs());
+                }
+                DomainEntity::Organization => {
+                    self.organizations.cancel_edit();
+                    tasks.push(self.load_organizations());
+                }
+                DomainEntity::None => {}
+            }
+        }
+        self.audit.history.clear();
+        if self.current_page == Page::Audit {
+            tasks.push(self.load_audit_entries());
+        }
+        if self.integrity.report.is_some() {
+            tasks.push(self.check_integrity());
+        }
+        Task::batch(tasks)
+    }
+
+    fn check_integrity(&mut self) -> Task<Message> {
+        let Some(database) = self.database.clone() else {
+            self.status_message = "Integrity checks need a database file".to_string();
+            return Task::none();
+        };
+
+        self.integrity.is_running = true;
+        Task::perform(
+            async move { database.check_integrity().await },
+            |result| match result {
+                Ok(report) => Message::IntegrityChecked(report),
+                Err(e) => Message::OperationFailed(e.to_string()),
+            },
+        )
+    }
+
+    /// Loads the user table after its filters, order or page changed,
+    /// scrolling back to the first row.
+    fn load_users_from_top(&mut self) -> Task<Message> {
+        self.user_table.scroll_to_top();
+        Task::batch([
+            operation::snap_to(USER_TABLE, RelativeOffset::START),
+            self.load_users(),
+        ])
+    }
+
+    fn load_users(&self) -> Task<Message> {
+        match &self.services {
+            Some(services) => {
+                let service = services.user.clone();
+                let query = self.user_page.query.clone();
+                Task::perform(
+                    async move { service.get_user_page(&query).await },
+                    |result| match result {
+                        Ok(page) => Message::UserPageLoaded(page),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                )
+            }
+            None => Task::none(),
+        }
+    }
+
+    fn load_jobs(&self) -> Task<Message> {
+        match &self.services {
+            Some(services) => {
+                let service = services.job.clone();
+                Task::perform(
+                    async move { service.get_all_jobs().await },
+                    |result| match result {
+                        Ok(jobs) => Message::JobsLoaded(jobs),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                )
+            }
+            None => Task::none(),
+        }
+    }
+
+    fn load_organizations(&self) -> Task<Message> {
+        match &self.services {
+            Some(services) => {
+                let service = services.organization.clone();
+                Task::perform(
+                    async move { service.get_all_organizations().await },
+                    |result| match result {
+                        Ok(organizations) => Message::OrganizationsLoaded(organizations),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                )
+            }
+            None => Task::none(),
+        }
+    }
+
+    fn load_audit_entries(&self) -> Task<Message> {
+        match &self.services {
+            Some(services) => {
+                let service = services.audit.clone();
+                let filter = self.audit.filter.clone();
+                Task::perform(async move { service.get_entries(filter).await }, |result| {
+                    match result {
+                        Ok(entries) => Message::AuditEntriesLoaded(entries),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    }
+                })
+            }
+            None => Task::none(),
+        }
+    }
+
+    fn load_history(&self, entity_type: DomainEntity, entity_id: i64) -> Task<Message> {
+        match &self.services {
+            Some(services) => {
+                let service = services.audit.clone();
+                Task::perform(
+                    async move { service.get_history(entity_type, entity_id).await },
+                    |result| match result {
+                        Ok(entries) => Message::AuditHistoryLoaded(entries),
+                        Err(e) => Message::OperationFailed(e.to_string()),
+                    },
+                )
+            }
+            None => Task::none(),
+        }
+    }
+
+    pub fn get_user_name(&self, user_id: i64) -> String {
+        self.users
+            .get(user_id)
+            .map(|u| u.name().to_string())
+            .unwrap_or_else(|| "None".to_string())
+    }
+
+    pub fn get_job_name(&self, job_id: i64) -> String {
+        self.jobs
+            .get(job_id)
+            .map(|j| j.name().to_string())
+            .unwrap_or_else(|| "None".to_string())
+    }
+
+    pub fn get_organization_name(&self, organization_id: i64) -> String {
+        self.organizations
+            .get(organization_id)
+            .map(|o| o.name().to_string())
+            .unwrap_or_else(|| "None".to_string())
+    }
+}
+
+fn open_database(path: PathBuf, read_only: bool) -> Task<Message> {
+    Task::perform(
+        async move { Database::open(&path.to_string_lossy(), read_only).await },
+        |result| match result {
+            Ok(database) => Message::DatabaseOpened(database),
+            Err(e) => Message::OperationFailed(e.to_string()),
+        },
+    )
+}
+
+fn parse_date_input(value: &str) -> Result<Option<NaiveDate>, chrono::ParseError> {
+    let value = value.trim();
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").map(Some)
+    }
+}
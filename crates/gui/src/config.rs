@@ -0,0 +1,264 @@
+//! Startup options from the command line, the environment and the config
+//! file.
+//!
+//! Each option is taken from the first of these that sets it:
+//!
+//! 1. a command-line argument, such as `--database fixtures/qa.db`;
+//! 2. an environment variable, such as `USERMGMT_DATABASE`;
+//! 3. `config.toml` in the config directory, or the file given by `--config`;
+//!    `USERMGMT_CONFIG_DIR` moves the config directory;
+//! 4. for the page and theme, the last ones used, from the saved preferences;
+//! 5. the built-in default.
+
+use clap::Parser;
+use iced::Theme;
+use log::LevelFilter;
+use serde::Deserialize;
+#[cfg(feature = "scim")]
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::infrastructure::{get_database_path, GeneratorOptions};
+use crate::message::Page;
+use crate::state::config_dir;
+#[cfg(feature = "scim")]
+use usermgmt_server::scim::ScimOptions;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Manage users, jobs and organizations")]
+pub struct Args {
+    /// Database file to open instead of the default one
+    #[arg(long, env = "USERMGMT_DATABASE", value_name = "PATH")]
+    pub database: Option<PathBuf>,
+
+    /// Open the database without allowing any changes
+    #[arg(
+        long,
+        env = "USERMGMT_READ_ONLY",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL"
+    )]
+    pub read_only: Option<bool>,
+
+    /// Page shown at startup: users, jobs, organizations, audit or settings
+    #[arg(long, env = "USERMGMT_PAGE")]
+    pub page: Option<String>,
+
+    /// Theme name, as listed on the Settings page
+    #[arg(long, env = "USERMGMT_THEME")]
+    pub theme: Option<String>,
+
+    /// One of off, error, warn, info, debug or trace
+    #[arg(long, env = "USERMGMT_LOG_LEVEL", value_name = "LEVEL")]
+    pub log_level: Option<String>,
+
+    /// Config file to read instead of config.toml in the config directory
+    #[arg(long, env = "USERMGMT_CONFIG", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Start on in-memory sample data instead of a database file
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Add this many generated users to the database at startup
+    #[arg(long, value_name = "N")]
+    pub generate_users: Option<usize>,
+
+    /// Add this many generated jobs at startup
+    #[arg(long, value_name = "N")]
+    pub generate_jobs: Option<usize>,
+
+    /// Add this many generated organizations at startup
+    #[arg(long, value_name = "N")]
+    pub generate_organizations: Option<usize>,
+
+    /// Seed for the generated data; the same seed gives the same data
+    #[arg(long, value_name = "SEED")]
+    pub generate_seed: Option<u64>,
+
+    /// Serve SCIM 2.0 on this address, such as 127.0.0.1:8080, instead of
+    /// opening the window
+    #[cfg(feature = "scim")]
+    #[arg(long, env = "USERMGMT_SCIM_LISTEN", value_name = "ADDR")]
+    pub scim: Option<SocketAddr>,
+
+    /// Bearer token SCIM clients must send
+    #[cfg(feature = "scim")]
+    #[arg(
+        long,
+        env = "USERMGMT_SCIM_TOKEN",
+        value_name = "TOKEN",
+        hide_env_values = true
+    )]
+    pub scim_token: Option<String>,
+}
+
+/// The contents of `config.toml`. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Relative paths are resolved against the directory of the config file.
+    pub database: Option<PathBuf>,
+    pub read_only: Option<bool>,
+    pub page: Option<String>,
+    pub theme: Option<String>,
+    pub log_level: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Could not read config file {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Invalid config file {0}: {1}")]
+    Parse(PathBuf, toml::de::Error),
+    #[error("Unknown page '{0}', expected users, jobs, organizations, audit or settings")]
+    UnknownPage(String),
+    #[error("Unknown theme '{0}'")]
+    UnknownTheme(String),
+    #[error("Unknown log level '{0}', expected off, error, warn, info, debug or trace")]
+    UnknownLogLevel(String),
+    #[error("--scim needs a bearer token, set --scim-token or USERMGMT_SCIM_TOKEN")]
+    MissingScimToken,
+}
+
+/// The options the app starts with, after applying the precedence rules.
+#[derive(Debug, Clone)]
+pub struct StartupOptions {
+    pub database_path: PathBuf,
+    pub read_only: bool,
+    pub demo: bool,
+    /// `None` unless set explicitly, so the saved preferences apply.
+    pub page: Option<Page>,
+    pub theme: Option<Theme>,
+    pub log_level: LevelFilter,
+    /// Sample data to add once the database is open, if any `--generate-*`
+    /// flag was given. Sizes not given fall back to the generator defaults.
+    pub generate: Option<GeneratorOptions>,
+    /// Serve SCIM instead of opening the window, when set.
+    #[cfg(feature = "scim")]
+    pub scim: Option<ScimOptions>,
+}
+
+impl Default for StartupOptions {
+    fn default() -> Self {
+        Self {
+            database_path: get_database_path(),
+            read_only: false,
+            demo: false,
+            page: None,
+            theme: None,
+            log_level: LevelFilter::Warn,
+            generate: None,
+            #[cfg(feature = "scim")]
+            scim: None,
+        }
+    }
+}
+
+impl StartupOptions {
+    /// Reads the process arguments, the environment and the config file.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_args(Args::parse())
+    }
+
+    pub fn from_args(args: Args) -> Result<Self, ConfigError> {
+        // A missing file only matters when it was asked for explicitly.
+        let (file, file_dir) = match &args.config {
+            Some(path) => (
+                read_config_file(path)?,
+                path.parent().map(Path::to_path_buf),
+            ),
+            None => {
+                let path = config_dir().join("config.toml");
+                let file = if path.exists() {
+                    read_config_file(&path)?
+                } else {
+                    ConfigFile::default()
+                };
+                (file, Some(config_dir()))
+            }
+        };
+
+        let defaults = Self::default();
+        let generator_defaults = GeneratorOptions::default();
+        let generate = (args.generate_users.is_some()
+            || args.generate_jobs.is_some()
+            || args.generate_organizations.is_some()
+            || args.generate_seed.is_some())
+        .then(|| GeneratorOptions {
+            organizations: args
+                .generate_organizations
+                .unwrap_or(generator_defaults.organizations),
+            jobs: args.generate_jobs.unwrap_or(generator_defaults.jobs),
+            users: args.generate_users.unwrap_or(generator_defaults.users),
+            seed: args.generate_seed.unwrap_or(generator_defaults.seed),
+        });
+        let database_path = match (args.database, file.database) {
+            (Some(path), _) => path,
+            (None, Some(path)) => match file_dir {
+                Some(dir) if path.is_relative() => dir.join(path),
+                _ => path,
+            },
+            (None, None) => defaults.database_path,
+        };
+
+        Ok(Self {
+            database_path,
+            read_only: args
+                .read_only
+                .or(file.read_only)
+                .unwrap_or(defaults.read_only),
+            demo: args.demo,
+            page: args
+                .page
+                .or(file.page)
+                .map(|name| Page::from_name(&name).ok_or(ConfigError::UnknownPage(name)))
+                .transpose()?,
+            theme: args
+                .theme
+                .or(file.theme)
+                .map(|name| theme_named(&name).ok_or(ConfigError::UnknownTheme(name)))
+                .transpose()?,
+            log_level: match args.log_level.or(file.log_level) {
+                Some(level) => level
+                    .parse()
+                    .map_err(|_| ConfigError::UnknownLogLevel(level))?,
+                None => defaults.log_level,
+            },
+            generate,
+            #[cfg(feature = "scim")]
+            scim: match (args.scim, args.scim_token) {
+                (Some(address), Some(token)) if !token.trim().is_empty() => Some(ScimOptions {
+                    address,
+                    token: token.trim().to_string(),
+                }),
+                (Some(_), _) => return Err(ConfigError::MissingScimToken),
+                (None, _) => None,
+            },
+        })
+    }
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile, ConfigError> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+    toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+}
+
+/// Finds a theme by its display name, ignoring case, spaces and dashes so
+/// that `tokyo-night` finds "Tokyo Night".
+pub fn theme_named(name: &str) -> Option<Theme> {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let wanted = normalize(name);
+    Theme::ALL
+        .iter()
+        .find(|theme| normalize(&theme.to_string()) == wanted)
+        .cloned()
+}
@@ -0,0 +1,7 @@
+pub mod app;
+pub mod config;
+pub mod message;
+pub mod state;
+mod view;
+
+use usermgmt_core::{domain, infrastructure};
@@ -0,0 +1,51 @@
+use iced::window;
+use iced::{Point, Size};
+use iced_user_management::app::AppState;
+use iced_user_management::config::StartupOptions;
+use iced_user_management::state::Preferences;
+
+pub fn main() -> iced::Result {
+    let options = match StartupOptions::load() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    env_logger::Builder::new()
+        .filter_level(options.log_level)
+        .init();
+
+    #[cfg(feature = "scim")]
+    if let Some(scim) = &options.scim {
+        let database_path = (!options.demo).then_some(options.database_path.as_path());
+        if let Err(e) = usermgmt_server::scim::run(database_path, options.read_only, scim) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // The window is created before the app boots, so its last geometry is
+    // read here rather than in AppState::new.
+    let geometry = Preferences::load().window.unwrap_or_default();
+    let size = Size::new(geometry.width, geometry.height);
+    let position = match geometry.x.zip(geometry.y) {
+        Some((x, y)) => window::Position::Specific(Point::new(x, y)),
+        None => window::Position::Default,
+    };
+
+    iced::application(
+        move || AppState::new(options.clone()),
+        AppState::update,
+        AppState::view,
+    )
+    .theme(|state: &AppState| state.theme.clone())
+    .subscription(AppState::subscription)
+    .title(AppState::title)
+    .window_size(size)
+    .position(position)
+    // Pending preferences are saved before the app exits.
+    .exit_on_close_request(false)
+    .run()
+}
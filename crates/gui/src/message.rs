@@ -0,0 +1,192 @@
+use crate::domain::{
+    AuditAction, AuditEntry, Command, DomainEntity, Job, Organization, User, UserPage, UserSortKey,
+};
+use crate::infrastructure::{
+    ChangeCounters, ChartCollapse, ContactScope, CsvColumn, CsvFile, Database, GeneratedData,
+    ImportField, ImportSummary, IntegrityReport, LdifField, LdifPlan, LdifSummary, MemoryDatabase,
+    SnapshotSummary,
+};
+use crate::state::{Conflict, RecentDatabase, SearchResults, UndoAction};
+use iced::{Point, Size, Theme};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Navigate(Page),
+    SearchChanged(String),
+    SearchCompleted(String, SearchResults),
+    SearchResultSelected(DomainEntity, i64),
+    CancelEdit,
+    ThemeChanged(Theme),
+    WindowResized(Size),
+    WindowMoved(Point),
+    WindowCloseRequested,
+    SavePreferences,
+    AppInitialized(Database),
+    DemoInitialized(MemoryDatabase),
+    InitializationError(String),
+    OperationFailed(String),
+
+    Undo,
+    Redo,
+    CommandExecuted(UndoAction, String, Command),
+    CommandFailed(UndoAction, String),
+
+    ConflictDetected(UndoAction, DomainEntity, i64),
+    ConflictLoaded(Conflict),
+    ConflictKeepMine,
+    ConflictUseStored,
+    ConflictDismiss,
+
+    PollChanges,
+    ChangesPolled(Option<ChangeCounters>),
+    EditedRecordChanged(DomainEntity, i64),
+    EditedRecordDeleted(DomainEntity),
+
+    NewDatabase,
+    OpenDatabase,
+    DatabaseSelected(Option<PathBuf>),
+    OpenRecentDatabase(RecentDatabase),
+    DatabaseOpened(Database),
+
+    BackupDatabase,
+    BackupCompleted(Option<PathBuf>),
+    RestoreDatabase,
+    RestoreCompleted(Option<PathBuf>),
+    ExportSnapshot,
+    SnapshotExported(Option<(PathBuf, SnapshotSummary)>),
+    ImportSnapshot,
+    SnapshotImported(Option<(PathBuf, SnapshotSummary)>),
+    LdifOptionChanged(LdifField, String),
+    ExportLdif,
+    LdifExported(Option<(PathBuf, usize)>),
+    ImportLdif,
+    LdifPlanned(Option<(PathBuf, LdifPlan)>),
+    LdifApply,
+    LdifApplied(LdifSummary),
+    LdifDiscard,
+    CheckIntegrity,
+    IntegrityChecked(IntegrityReport),
+    IntegrityReplacementJobSelected(Job),
+    IntegrityReplacementOrganizationSelected(Organization),
+    RepairReassignUser(i64),
+    RepairReassignAll,
+    RepairDeleteUser(i64),
+    RepairDeleteAll,
+    GeneratorOrganizationsChanged(String),
+    GeneratorJobsChanged(String),
+    GeneratorUsersChanged(String),
+    GeneratorSeedChanged(String),
+    GenerateData,
+    DataGenerated(GeneratedData),
+
+    JobClicked(i64),
+    OrganizationClicked(i64),
+
+    UserNameChanged(String),
+    UserJobSelected(Job),
+    UserOrganizationSelected(Organization),
+    UserEmailChanged(String),
+    UserPhoneChanged(String),
+    UserCreate,
+    UserUpdate,
+    UserDelete(i64),
+    UserLoad(i64),
+    UserLoaded(User),
+    UserNotFound,
+    UserLoadError(String),
+    UserPageLoaded(UserPage),
+    UserFilterNameChanged(String),
+    UserFilterJobSelected(Job),
+    UserFilterOrganizationSelected(Organization),
+    UserFilterClear,
+    UserSortBy(UserSortKey),
+    UserNextPage,
+    UserPreviousPage,
+    UserPageSizeSelected(i64),
+    UserTableScrolled(f32, f32),
+    UserColumnWidthChanged(UserSortKey, u16),
+    UserToggleSelected(i64, bool),
+    UserDeleteSelected,
+
+    ImportCsv,
+    ImportFileLoaded(Option<CsvFile>),
+    ImportColumnMapped(ImportField, CsvColumn),
+    ImportColumnCleared(ImportField),
+    ImportCreateMissingToggled(bool),
+    ImportCommit,
+    ImportCompleted(ImportSummary),
+    ImportSaveRejected,
+    RejectedRowsSaved(Option<(PathBuf, usize)>),
+    ImportCancel,
+
+    ExportList(DomainEntity),
+    ListExported(Option<(PathBuf, usize)>),
+    ExportContacts(ContactScope),
+    ContactsExported(Option<(PathBuf, usize)>),
+    OrgChartCollapseChanged(ChartCollapse),
+    ExportOrgChart,
+    OrgChartExported(Option<(PathBuf, usize)>),
+
+    JobNameChanged(String),
+    JobCreate,
+    JobUpdate,
+    JobDelete(i64),
+    JobLoad(i64),
+    JobLoaded(Job),
+    JobsLoaded(Vec<Job>),
+
+    OrganizationNameChanged(String),
+    OrganizationCreate,
+    OrganizationUpdate,
+    OrganizationDelete(i64),
+    OrganizationLoad(i64),
+    OrganizationLoaded(Organization),
+    OrganizationsLoaded(Vec<Organization>),
+
+    AuditEntityFilterSelected(DomainEntity),
+    AuditActionFilterSelected(AuditAction),
+    AuditFromChanged(String),
+    AuditToChanged(String),
+    AuditApplyFilter,
+    AuditClearFilter,
+    AuditEntriesLoaded(Vec<AuditEntry>),
+    AuditHistoryLoaded(Vec<AuditEntry>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Page {
+    User,
+    Organization,
+    Job,
+    Audit,
+    Settings,
+    Import,
+}
+
+impl Page {
+    /// The name used for the page in config files and preferences.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Page::User => "users",
+            Page::Organization => "organizations",
+            Page::Job => "jobs",
+            Page::Audit => "audit",
+            Page::Settings => "settings",
+            Page::Import => "import",
+        }
+    }
+
+    /// Looks a page up by name, accepting singular and plural forms. The
+    /// import wizard is only reached from the Users page, so it has none.
+    pub fn from_name(name: &str) -> Option<Page> {
+        match name.trim().to_lowercase().as_str() {
+            "user" | "users" => Some(Page::User),
+            "job" | "jobs" => Some(Page::Job),
+            "organization" | "organizations" => Some(Page::Organization),
+            "audit" => Some(Page::Audit),
+            "settings" => Some(Page::Settings),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::domain::{AuditEntry, AuditFilter};
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditState {
+    pub entries: Vec<AuditEntry>,
+    pub filter: AuditFilter,
+    pub from_input: String,
+    pub to_input: String,
+    pub history: Vec<AuditEntry>,
+}
+
+impl AuditState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter = AuditFilter::default();
+        self.from_input.clear();
+        self.to_input.clear();
+    }
+}
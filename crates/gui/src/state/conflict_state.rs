@@ -0,0 +1,59 @@
+use crate::domain::{Command, DomainEntity, Entity, Job, Organization, User};
+
+/// The user's unsaved edits alongside the latest stored values of a record
+/// that was changed by someone else while it was being edited.
+#[derive(Debug, Clone)]
+pub enum Conflict {
+    User {
+        mine: User,
+        theirs: User,
+    },
+    Job {
+        mine: Job,
+        theirs: Job,
+    },
+    Organization {
+        mine: Organization,
+        theirs: Organization,
+    },
+}
+
+impl Conflict {
+    pub fn entity(&self) -> DomainEntity {
+        match self {
+            Conflict::User { .. } => DomainEntity::User,
+            Conflict::Job { .. } => DomainEntity::Job,
+            Conflict::Organization { .. } => DomainEntity::Organization,
+        }
+    }
+
+    /// Builds an update that overwrites the stored record with the user's edits.
+    pub fn keep_mine(&self) -> (String, Command) {
+        match self {
+            Conflict::User { mine, theirs } => {
+                let mut user = mine.clone();
+                user.set_version(theirs.version());
+                (
+                    format!("Update user {}", user.name()),
+                    Command::UpdateUser(user),
+                )
+            }
+            Conflict::Job { mine, theirs } => {
+                let mut job = mine.clone();
+                job.set_version(theirs.version());
+                (
+                    format!("Update job {}", job.name()),
+                    Command::UpdateJob(job),
+                )
+            }
+            Conflict::Organization { mine, theirs } => {
+                let mut organization = mine.clone();
+                organization.set_version(theirs.version());
+                (
+                    format!("Update organization {}", organization.name()),
+                    Command::UpdateOrganization(organization),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::domain::Entity;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct EntityState<T: Entity> {
+    pub current: T,
+    list: Vec<T>,
+    /// Position of each record in `list`, keyed by id.
+    index: HashMap<i64, usize>,
+    pub is_edit: bool,
+    pub selected: BTreeSet<i64>,
+}
+
+impl<T: Entity> EntityState<T> {
+    pub fn new() -> Self {
+        Self {
+            current: T::default(),
+            list: Vec::new(),
+            index: HashMap::new(),
+            is_edit: false,
+            selected: BTreeSet::new(),
+        }
+    }
+
+    pub fn list(&self) -> &[T] {
+        &self.list
+    }
+
+    /// Replaces the list and rebuilds the id index.
+    pub fn set_list(&mut self, list: Vec<T>) {
+        self.index = list
+            .iter()
+            .enumerate()
+            .map(|(position, item)| (item.id(), position))
+            .collect();
+        self.list = list;
+    }
+
+    pub fn get(&self, id: i64) -> Option<&T> {
+        self.index.get(&id).map(|&position| &self.list[position])
+    }
+
+    pub fn cancel_edit(&mut self) {
+        self.current = T::default();
+        self.is_edit = false;
+        self.current.clear_errors();
+    }
+
+    pub fn toggle_selected(&mut self, id: i64, selected: bool) {
+        if selected {
+            self.selected.insert(id);
+        } else {
+            self.selected.remove(&id);
+        }
+    }
+
+    /// Drops selections for records that are no longer in the list.
+    pub fn retain_selection(&mut self) {
+        let index = &self.index;
+        self.selected.retain(|id| index.contains_key(id));
+    }
+}
+
+impl<T: Entity> Default for EntityState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,50 @@
+use crate::infrastructure::GeneratorOptions;
+
+/// The sample data form on the Settings page. Sizes are kept as typed and
+/// only parsed when the generator runs.
+#[derive(Debug, Clone)]
+pub struct GeneratorState {
+    pub organizations: String,
+    pub jobs: String,
+    pub users: String,
+    pub seed: String,
+    pub is_running: bool,
+}
+
+impl GeneratorState {
+    pub fn new() -> Self {
+        let defaults = GeneratorOptions::default();
+        Self {
+            organizations: defaults.organizations.to_string(),
+            jobs: defaults.jobs.to_string(),
+            users: defaults.users.to_string(),
+            seed: defaults.seed.to_string(),
+            is_running: false,
+        }
+    }
+
+    pub fn options(&self) -> Result<GeneratorOptions, String> {
+        let count = |label: &str, value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("{} must be a whole number", label))
+        };
+        Ok(GeneratorOptions {
+            organizations: count("Organizations", &self.organizations)?,
+            jobs: count("Jobs", &self.jobs)?,
+            users: count("Users", &self.users)?,
+            seed: self
+                .seed
+                .trim()
+                .parse()
+                .map_err(|_| "Seed must be a whole number".to_string())?,
+        })
+    }
+}
+
+impl Default for GeneratorState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
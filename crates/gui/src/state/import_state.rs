@@ -0,0 +1,58 @@
+use crate::domain::{Job, Organization};
+
+use crate::infrastructure::{ColumnMapping, CsvFile, ImportField, ImportPreview};
+
+/// The CSV import wizard: the file, how its columns map to user fields and
+/// the resulting preview.
+#[derive(Debug, Clone, Default)]
+pub struct ImportState {
+    pub file: Option<CsvFile>,
+    pub mapping: ColumnMapping,
+    pub create_missing: bool,
+    pub preview: ImportPreview,
+    pub is_running: bool,
+}
+
+impl ImportState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts over on a new file, guessing the mapping from its headers.
+    pub fn load(&mut self, file: CsvFile, jobs: &[Job], organizations: &[Organization]) {
+        self.mapping = ColumnMapping::guess(&file.headers);
+        self.file = Some(file);
+        self.refresh(jobs, organizations);
+    }
+
+    pub fn map_column(
+        &mut self,
+        field: ImportField,
+        column: Option<usize>,
+        jobs: &[Job],
+        organizations: &[Organization],
+    ) {
+        self.mapping.set(field, column);
+        self.refresh(jobs, organizations);
+    }
+
+    pub fn set_create_missing(
+        &mut self,
+        create_missing: bool,
+        jobs: &[Job],
+        organizations: &[Organization],
+    ) {
+        self.create_missing = create_missing;
+        self.refresh(jobs, organizations);
+    }
+
+    /// Re-checks every row, after the mapping or the database changed.
+    pub fn refresh(&mut self, jobs: &[Job], organizations: &[Organization]) {
+        self.preview = match &self.file {
+            Some(file) => {
+                ImportPreview::new(file, self.mapping, jobs, organizations, self.create_missing)
+            }
+            None => ImportPreview::default(),
+        };
+    }
+}
@@ -0,0 +1,64 @@
+use crate::domain::{Command, Entity, Job, Organization};
+
+use crate::infrastructure::{IntegrityReport, OrphanedUser};
+
+/// The last integrity report and the replacements chosen for repairing it.
+#[derive(Debug, Default)]
+pub struct IntegrityState {
+    pub report: Option<IntegrityReport>,
+    pub replacement_job: Option<Job>,
+    pub replacement_organization: Option<Organization>,
+    pub is_running: bool,
+}
+
+impl IntegrityState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn orphaned_users(&self) -> &[OrphanedUser] {
+        self.report
+            .as_ref()
+            .map(|report| report.orphaned_users.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn orphan(&self, id: i64) -> Option<&OrphanedUser> {
+        self.orphaned_users()
+            .iter()
+            .find(|orphan| orphan.user.id() == id)
+    }
+
+    /// Moves an orphaned user onto the chosen job and organization, or
+    /// `None` until a replacement is picked for everything it is missing.
+    pub fn reassign(&self, orphan: &OrphanedUser) -> Option<Command> {
+        let mut user = orphan.user.clone();
+        if orphan.missing_job {
+            user.set_job_id(self.replacement_job.as_ref()?.id());
+        }
+        if orphan.missing_organization {
+            user.set_organization_id(self.replacement_organization.as_ref()?.id());
+        }
+        Some(Command::UpdateUser(user))
+    }
+
+    /// Reassigns every orphaned user, or `None` if any of them lacks a
+    /// replacement.
+    pub fn reassign_all(&self) -> Option<Command> {
+        let commands = self
+            .orphaned_users()
+            .iter()
+            .map(|orphan| self.reassign(orphan))
+            .collect::<Option<Vec<_>>>()?;
+        (!commands.is_empty()).then_some(Command::Batch(commands))
+    }
+
+    pub fn delete_all(&self) -> Option<Command> {
+        let commands: Vec<Command> = self
+            .orphaned_users()
+            .iter()
+            .map(|orphan| Command::DeleteUser(orphan.user.id()))
+            .collect();
+        (!commands.is_empty()).then_some(Command::Batch(commands))
+    }
+}
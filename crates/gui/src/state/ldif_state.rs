@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use crate::infrastructure::LdifPlan;
+
+/// A directory dump that was read and checked but not applied yet.
+#[derive(Debug, Clone, Default)]
+pub struct LdifState {
+    pub path: Option<PathBuf>,
+    pub plan: Option<LdifPlan>,
+    pub is_running: bool,
+}
+
+impl LdifState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn planned(&mut self, path: PathBuf, plan: LdifPlan) {
+        self.path = Some(path);
+        self.plan = Some(plan);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
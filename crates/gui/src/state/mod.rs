@@ -0,0 +1,30 @@
+//! What the window shows and remembers between sessions, kept apart from
+//! the core library so the servers and the CLI do not carry it.
+
+mod audit_state;
+mod conflict_state;
+mod entity_state;
+mod generator_state;
+mod import_state;
+mod integrity_state;
+mod ldif_state;
+mod preferences;
+mod search_state;
+mod undo_stack;
+mod user_page_state;
+mod virtual_list;
+mod workspace;
+
+pub use audit_state::AuditState;
+pub use conflict_state::Conflict;
+pub use entity_state::EntityState;
+pub use generator_state::GeneratorState;
+pub use import_state::ImportState;
+pub use integrity_state::IntegrityState;
+pub use ldif_state::LdifState;
+pub use preferences::{Preferences, UserColumnWidths, WindowGeometry};
+pub use search_state::{SearchResults, SearchState};
+pub use undo_stack::{UndoAction, UndoStack};
+pub use user_page_state::{UserPageState, PAGE_SIZES};
+pub use virtual_list::VirtualList;
+pub use workspace::{config_dir, RecentDatabase, RecentDatabases};
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::domain::{SortDirection, UserSortKey, DEFAULT_PAGE_SIZE};
+
+use super::workspace::config_dir;
+use crate::infrastructure::ChartCollapse;
+use crate::infrastructure::LdifOptions;
+
+/// Where the window was and how big it was when last moved or resized.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+}
+
+impl Default for WindowGeometry {
+    /// iced's default window size, placed by the window manager.
+    fn default() -> Self {
+        Self {
+            width: 1024.0,
+            height: 768.0,
+            x: None,
+            y: None,
+        }
+    }
+}
+
+/// Relative widths of the sortable user table columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserColumnWidths {
+    pub id: u16,
+    pub name: u16,
+    pub job: u16,
+    pub organization: u16,
+}
+
+impl UserColumnWidths {
+    pub const MIN: u16 = 1;
+    pub const MAX: u16 = 6;
+
+    pub fn get(&self, column: UserSortKey) -> u16 {
+        match column {
+            UserSortKey::Id => self.id,
+            UserSortKey::Name => self.name,
+            UserSortKey::Job => self.job,
+            UserSortKey::Organization => self.organization,
+        }
+    }
+
+    pub fn set(&mut self, column: UserSortKey, width: u16) {
+        let width = width.clamp(Self::MIN, Self::MAX);
+        match column {
+            UserSortKey::Id => self.id = width,
+            UserSortKey::Name => self.name = width,
+            UserSortKey::Job => self.job = width,
+            UserSortKey::Organization => self.organization = width,
+        }
+    }
+}
+
+impl Default for UserColumnWidths {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            name: 2,
+            job: 2,
+            organization: 2,
+        }
+    }
+}
+
+/// Choices the user made in the UI, persisted in the config directory so
+/// they survive a restart. Unknown or missing keys fall back to defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// The theme's display name, as `Theme` itself cannot be serialized.
+    pub theme: Option<String>,
+    pub page: Option<String>,
+    pub window: Option<WindowGeometry>,
+    pub user_columns: UserColumnWidths,
+    pub user_sort: UserSortKey,
+    pub user_sort_direction: SortDirection,
+    pub user_page_size: i64,
+    pub ldif: LdifOptions,
+    pub org_chart: ChartCollapse,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            page: None,
+            window: None,
+            user_columns: UserColumnWidths::default(),
+            user_sort: UserSortKey::default(),
+            user_sort_direction: SortDirection::default(),
+            user_page_size: DEFAULT_PAGE_SIZE,
+            ldif: LdifOptions::default(),
+            org_chart: ChartCollapse::default(),
+        }
+    }
+}
+
+impl Preferences {
+    pub fn load() -> Self {
+        std::fs::read_to_string(preferences_file())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = preferences_file();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, contents)
+    }
+}
+
+fn preferences_file() -> PathBuf {
+    config_dir().join("preferences.json")
+}
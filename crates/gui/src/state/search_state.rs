@@ -0,0 +1,32 @@
+use crate::domain::{Job, Organization, User};
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    pub users: Vec<User>,
+    pub jobs: Vec<Job>,
+    pub organizations: Vec<Organization>,
+}
+
+impl SearchResults {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.jobs.is_empty() && self.organizations.is_empty()
+    }
+}
+
+/// The global search box and the results for what it currently holds.
+#[derive(Debug, Default)]
+pub struct SearchState {
+    pub query: String,
+    pub results: SearchResults,
+}
+
+impl SearchState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.results = SearchResults::default();
+    }
+}
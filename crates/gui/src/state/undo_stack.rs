@@ -0,0 +1,109 @@
+use crate::domain::Command;
+
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoAction {
+    Perform,
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub description: String,
+    pub command: Command,
+}
+
+/// Holds the inverse commands of completed operations.
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+    /// The entry an undo or redo is running, until it succeeds or is put back.
+    running: Option<(UndoAction, UndoEntry)>,
+    pub is_busy: bool,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a new operation, which invalidates anything that could be redone.
+    pub fn record(&mut self, description: String, inverse: Command) {
+        self.redo.clear();
+        Self::push(&mut self.undo, description, inverse);
+    }
+
+    pub fn push_undo(&mut self, description: String, inverse: Command) {
+        Self::push(&mut self.undo, description, inverse);
+    }
+
+    pub fn push_redo(&mut self, description: String, inverse: Command) {
+        Self::push(&mut self.redo, description, inverse);
+    }
+
+    /// Takes the newest entry to undo or redo. It is held until `finish`,
+    /// or put back by `restore` if its command fails.
+    pub fn take(&mut self, action: UndoAction) -> Option<UndoEntry> {
+        let entry = match action {
+            UndoAction::Perform => None,
+            UndoAction::Undo => self.undo.pop(),
+            UndoAction::Redo => self.redo.pop(),
+        }?;
+        self.running = Some((action, entry.clone()));
+        Some(entry)
+    }
+
+    /// The undo or redo in progress, and the entry it was taken from.
+    pub fn running(&self) -> Option<&(UndoAction, UndoEntry)> {
+        self.running.as_ref()
+    }
+
+    /// Forgets the entry of an undo or redo that is done with.
+    pub fn finish(&mut self) {
+        self.running = None;
+    }
+
+    /// Puts the entry of a failed undo or redo back where it was taken from.
+    pub fn restore(&mut self) {
+        match self.running.take() {
+            Some((UndoAction::Undo, entry)) => self.undo.push(entry),
+            Some((UndoAction::Redo, entry)) => self.redo.push(entry),
+            Some((UndoAction::Perform, _)) | None => {}
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.is_busy && self.running.is_none() && !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.is_busy && self.running.is_none() && !self.redo.is_empty()
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.undo.last().map(|entry| entry.description.as_str())
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.redo.last().map(|entry| entry.description.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.running = None;
+    }
+
+    fn push(stack: &mut Vec<UndoEntry>, description: String, command: Command) {
+        stack.push(UndoEntry {
+            description,
+            command,
+        });
+        if stack.len() > MAX_ENTRIES {
+            stack.remove(0);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::domain::{
+    SortDirection, UserCursor, UserPage, UserQuery, UserSortKey, DEFAULT_PAGE_SIZE,
+};
+
+/// The page sizes offered by the user table. The table only builds the rows
+/// in view, so even the largest stays responsive.
+pub const PAGE_SIZES: [i64; 4] = [DEFAULT_PAGE_SIZE, 1_000, 10_000, 100_000];
+
+/// Filters, sort order and position of the paged user table.
+#[derive(Debug, Default)]
+pub struct UserPageState {
+    pub query: UserQuery,
+    pub next: Option<UserCursor>,
+    pub total: i64,
+    /// Where each earlier page started, so the table can page back.
+    previous: Vec<Option<UserCursor>>,
+}
+
+impl UserPageState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts over on another database: clears the filters and position but
+    /// keeps the sort order and page size.
+    pub fn reset(&mut self) {
+        *self = Self {
+            query: UserQuery {
+                sort: self.query.sort,
+                direction: self.query.direction,
+                limit: self.query.limit,
+                ..UserQuery::default()
+            },
+            ..Self::default()
+        };
+    }
+
+    /// Goes back to the first page, after the filters or sort order changed.
+    pub fn rewind(&mut self) {
+        self.query.after = None;
+        self.previous.clear();
+    }
+
+    pub fn next_page(&mut self) -> bool {
+        match self.next.take() {
+            Some(next) => {
+                self.previous.push(self.query.after.replace(next));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn previous_page(&mut self) -> bool {
+        match self.previous.pop() {
+            Some(after) => {
+                self.query.after = after;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_previous(&self) -> bool {
+        !self.previous.is_empty()
+    }
+
+    /// Sorts by `key`, flipping the direction if it already is the sort key.
+    pub fn sort_by(&mut self, key: UserSortKey) {
+        if self.query.sort == key {
+            self.query.direction = self.query.direction.reversed();
+        } else {
+            self.query.sort = key;
+            self.query.direction = SortDirection::Ascending;
+        }
+        self.rewind();
+    }
+
+    pub fn set_page_size(&mut self, limit: i64) {
+        self.query.limit = limit;
+        self.rewind();
+    }
+
+    pub fn loaded(&mut self, page: &UserPage) {
+        self.next = page.next.clone();
+        self.total = page.total;
+    }
+
+    pub fn page_number(&self) -> usize {
+        self.previous.len() + 1
+    }
+
+    pub fn page_count(&self) -> i64 {
+        let limit = self.query.limit.max(1);
+        ((self.total + limit - 1) / limit).max(1)
+    }
+}
@@ -0,0 +1,55 @@
+use std::ops::Range;
+
+/// Scroll position of a list whose rows all share one height, so the view
+/// only has to build the rows that are on screen.
+#[derive(Debug, Clone, Copy)]
+pub struct VirtualList {
+    row_height: f32,
+    offset: f32,
+    viewport_height: f32,
+}
+
+impl VirtualList {
+    /// Rows built above and below the viewport so fast scrolling does not
+    /// show blank space before the next update.
+    pub const OVERSCAN: usize = 5;
+
+    /// A guess at the viewport until the first scroll event reports it.
+    const INITIAL_VIEWPORT_HEIGHT: f32 = 800.0;
+
+    pub fn new(row_height: f32) -> Self {
+        Self {
+            row_height,
+            offset: 0.0,
+            viewport_height: Self::INITIAL_VIEWPORT_HEIGHT,
+        }
+    }
+
+    pub fn row_height(&self) -> f32 {
+        self.row_height
+    }
+
+    pub fn scrolled(&mut self, offset: f32, viewport_height: f32) {
+        self.offset = offset.max(0.0);
+        self.viewport_height = viewport_height.max(0.0);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.offset = 0.0;
+    }
+
+    /// The rows of a list of `len` rows that are in view, plus the overscan.
+    pub fn visible_range(&self, len: usize) -> Range<usize> {
+        let first = (self.offset / self.row_height) as usize;
+        let count = (self.viewport_height / self.row_height).ceil() as usize + 1;
+        let start = first.saturating_sub(Self::OVERSCAN).min(len);
+        let end = (first + count + Self::OVERSCAN).min(len);
+        start..end
+    }
+
+    /// The height taken up by `rows` rows, for the spacers around the
+    /// visible range.
+    pub fn height_of(&self, rows: usize) -> f32 {
+        rows as f32 * self.row_height
+    }
+}
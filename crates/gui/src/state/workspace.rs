@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const MAX_RECENT: usize = 10;
+
+/// A database file shown in the File > Recent list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentDatabase(pub PathBuf);
+
+impl std::fmt::Display for RecentDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// Recently opened database files, most recent first, persisted in the
+/// config directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentDatabases {
+    paths: Vec<RecentDatabase>,
+}
+
+impl RecentDatabases {
+    pub fn load() -> Self {
+        std::fs::read_to_string(recent_file())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = recent_file();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, contents)
+    }
+
+    pub fn add(&mut self, path: &Path) {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.paths.retain(|recent| recent.0 != path);
+        self.paths.insert(0, RecentDatabase(path));
+        self.paths.truncate(MAX_RECENT);
+    }
+
+    pub fn list(&self) -> &[RecentDatabase] {
+        &self.paths
+    }
+}
+
+/// Where preferences, recent databases and `config.toml` live.
+/// `USERMGMT_CONFIG_DIR` replaces the platform's directory, so benchmarks
+/// and scripted runs can leave the user's own settings alone.
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("USERMGMT_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("iced-user-management")
+}
+
+fn recent_file() -> PathBuf {
+    config_dir().join("recent_databases.json")
+}
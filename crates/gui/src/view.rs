@@ -0,0 +1,1187 @@
+use iced::{
+    widget::{
+        button, checkbox, column, container, pick_list, row, scrollable, slider, space, text,
+        text_input, tooltip, Button, Column, Container, Row,
+    },
+    Border, Color, Element, Fill, FillPortion, Length, Theme,
+};
+
+use crate::app::AppState;
+use crate::domain::{AuditAction, AuditEntry, DomainEntity, Entity, SortDirection, UserSortKey};
+use crate::infrastructure::{ChartCollapse, ContactScope, ImportField, LdifField, Reference};
+use crate::message::{Message, Page};
+use crate::state::{Conflict, RecentDatabase, UndoAction, UserColumnWidths, PAGE_SIZES};
+
+/// The scrollable holding the user rows, so updates can scroll it.
+pub const USER_TABLE: &str = "user-table";
+/// Every user row has this height, which lets the table work out which
+/// rows are on screen without laying them all out.
+pub const USER_ROW_HEIGHT: f32 = 40.0;
+/// The import preview lists this many rows; the counts cover the rest.
+const IMPORT_PREVIEW_ROWS: usize = 500;
+
+impl AppState {
+    pub fn view(&self) -> Element<'_, Message> {
+        let search_box = text_input("Search...", &self.search.query)
+            .on_input(Message::SearchChanged)
+            .width(Length::Fill);
+
+        let navigation = container(
+            column![
+                search_box,
+                self.search_results(),
+                row![button(container("Users").center_x(30).center_y(30))
+                    .width(Length::Fill)
+                    .on_press(Message::Navigate(Page::User))],
+                row![button(container("Organizations").center_x(30).center_y(30))
+                    .width(Length::Fill)
+                    .on_press(Message::Navigate(Page::Organization))],
+                row![button(container("Jobs").center_x(30).center_y(30))
+                    .width(Length::Fill)
+                    .on_press(Message::Navigate(Page::Job))],
+                row![button(container("Audit").center_x(30).center_y(30))
+                    .width(Length::Fill)
+                    .on_press(Message::Navigate(Page::Audit))],
+                row![button(container("Settings").center_x(30).center_y(30))
+                    .width(Length::Fill)
+                    .on_press(Message::Navigate(Page::Settings))],
+            ]
+            .spacing(10)
+            .height(Fill),
+        )
+        .padding(10)
+        .style(|_theme: &Theme| container::Style {
+            border: Border {
+                color: Color::from_rgb(0.5, 0.5, 0.5),
+                width: 1.0,
+                radius: 0.into(),
+            },
+            ..Default::default()
+        })
+        .width(FillPortion(1));
+
+        let status_bar = container(text(&self.status_message).size(12))
+            .padding(5)
+            .width(Length::Fill)
+            .style(|theme: &Theme| container::Style {
+                text_color: Some(
+                    if self.status_message.contains("error")
+                        || self.status_message.contains("Error")
+                    {
+                        theme.palette().danger
+                    } else if self.status_message.contains("connected") {
+                        theme.palette().success
+                    } else {
+                        theme.palette().text
+                    },
+                ),
+                ..Default::default()
+            });
+
+        let file_bar = row![
+            text("File"),
+            button("New...")
+                .style(button::secondary)
+                .on_press(Message::NewDatabase),
+            button("Open...")
+                .style(button::secondary)
+                .on_press(Message::OpenDatabase),
+            pick_list(
+                self.recent_databases.list(),
+                None::<RecentDatabase>,
+                Message::OpenRecentDatabase,
+            )
+            .placeholder("Recent"),
+            text(
+                self.database
+                    .as_ref()
+                    .map(|database| database.path.display().to_string())
+                    .unwrap_or_default()
+            )
+            .size(12),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        container(column![
+            file_bar,
+            row![navigation, self.current_page()].spacing(10),
+            row![status_bar, self.undo_buttons()]
+        ])
+        .padding(10)
+        .into()
+    }
+
+    fn undo_buttons(&self) -> Row<'_, Message> {
+        let undo = tooltip(
+            button("Undo")
+                .style(button::secondary)
+                .on_press_maybe(self.undo_stack.can_undo().then_some(Message::Undo)),
+            text(match self.undo_stack.undo_description() {
+                Some(description) => format!("Undo {} (Ctrl+Z)", description),
+                None => "Nothing to undo".to_string(),
+            })
+            .size(12),
+            tooltip::Position::Top,
+        );
+        let redo = tooltip(
+            button("Redo")
+                .style(button::secondary)
+                .on_press_maybe(self.undo_stack.can_redo().then_some(Message::Redo)),
+            text(match self.undo_stack.redo_description() {
+                Some(description) => format!("Redo {} (Ctrl+Shift+Z)", description),
+                None => "Nothing to redo".to_string(),
+            })
+            .size(12),
+            tooltip::Position::Top,
+        );
+
+        row![undo, redo].spacing(5)
+    }
+
+    fn current_page(&self) -> Container<'_, Message> {
+        if let Some(conflict) = &self.conflict {
+            return self.conflict_form(conflict);
+        }
+
+        match self.current_page {
+            Page::Organization => self.organization_form(),
+            Page::User => self.user_form(),
+            Page::Job => self.job_form(),
+            Page::Audit => self.audit_form(),
+            Page::Settings => self.settings_form(),
+            Page::Import => self.import_form(),
+        }
+    }
+
+    fn conflict_form(&self, conflict: &Conflict) -> Container<'_, Message> {
+        let fields: Vec<(&str, String, String)> = match conflict {
+            Conflict::User { mine, theirs } => vec![
+                ("Name", mine.name().to_string(), theirs.name().to_string()),
+                (
+                    "Job",
+                    self.get_job_name(mine.job_id()),
+                    self.get_job_name(theirs.job_id()),
+                ),
+                (
+                    "Organization",
+                    self.get_organization_name(mine.organization_id()),
+                    self.get_organization_name(theirs.organization_id()),
+                ),
+                (
+                    "Email",
+                    mine.email().to_string(),
+                    theirs.email().to_string(),
+                ),
+                (
+                    "Phone",
+                    mine.phone().to_string(),
+                    theirs.phone().to_string(),
+                ),
+            ],
+            Conflict::Job { mine, theirs } => {
+                vec![("Name", mine.name().to_string(), theirs.name().to_string())]
+            }
+            Conflict::Organization { mine, theirs } => {
+                vec![("Name", mine.name().to_string(), theirs.name().to_string())]
+            }
+        };
+
+        let header_row = row![
+            text("Field").width(Length::FillPortion(1)),
+            text("Your edits").width(Length::FillPortion(2)),
+            text("Stored values").width(Length::FillPortion(2)),
+        ];
+        let comparison = fields.into_iter().fold(
+            column![header_row].spacing(2),
+            |col, (field, mine, theirs)| {
+                let changed = mine != theirs;
+                col.push(
+                    row![
+                        text(field).width(Length::FillPortion(1)),
+                        text(mine).width(Length::FillPortion(2)),
+                        text(theirs)
+                            .width(Length::FillPortion(2))
+                            .style(move |theme: &Theme| text::Style {
+                                color: changed.then(|| theme.palette().danger),
+                            }),
+                    ]
+                    .spacing(10)
+                    .padding(5),
+                )
+            },
+        );
+
+        container(
+            column![
+                text(match self.undo_stack.running() {
+                    Some((action, entry)) => format!(
+                        "This {} was changed by someone else, so \"{}\" could not be {}.",
+                        conflict.entity().as_str(),
+                        entry.description,
+                        if *action == UndoAction::Redo {
+                            "redone"
+                        } else {
+                            "undone"
+                        }
+                    ),
+                    None => format!(
+                        "This {} was changed by someone else while you were editing it.",
+                        conflict.entity().as_str()
+                    ),
+                }),
+                comparison,
+                row![
+                    button("Keep my edits").on_press(Message::ConflictKeepMine),
+                    button("Use stored values")
+                        .style(button::secondary)
+                        .on_press(Message::ConflictUseStored),
+                    button("Cancel")
+                        .style(button::danger)
+                        .on_press(Message::ConflictDismiss),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(FillPortion(4))
+    }
+
+    fn job_form(&self) -> Container<'_, Message> {
+        let name_input = column![
+            text_input("Job", self.jobs.current.name()).on_input(Message::JobNameChanged),
+            if let Some(error) = self.jobs.current.errors().get("name") {
+                text(error.to_string())
+                    .size(12)
+                    .style(|_theme| text::Style {
+                        color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                    })
+            } else {
+                text("").height(0)
+            }
+        ];
+        let header_row = row![
+            text("ID").width(Length::FillPortion(1)),
+            text("Name").width(Length::FillPortion(2)),
+            text("Action")
+        ];
+        let job_list = scrollable(self.jobs.list().iter().fold(
+            column![header_row].spacing(2),
+            |col, job| {
+                col.push(
+                    row![
+                        text(job.id()).width(Length::FillPortion(1)),
+                        text(job.name().to_string()).width(Length::FillPortion(2)),
+                        button("Edit")
+                            .style(button::primary)
+                            .on_press(Message::JobLoad(job.id())),
+                        button("Delete")
+                            .style(button::danger)
+                            .on_press(Message::JobDelete(job.id())),
+                    ]
+                    .spacing(10)
+                    .padding(5),
+                )
+            },
+        ))
+        .height(Length::Fill);
+
+        container(
+            column![
+                name_input,
+                self.get_form_buttons(self.jobs.is_edit, Message::JobCreate, Message::JobUpdate),
+                self.history_panel(self.jobs.is_edit),
+                button("Export...").on_press(Message::ExportList(DomainEntity::Job)),
+                job_list
+            ]
+            .spacing(10),
+        )
+        .width(FillPortion(4))
+    }
+
+    fn organization_form(&self) -> Container<'_, Message> {
+        let name_input = column![
+            text_input("Organization", self.organizations.current.name())
+                .on_input(Message::OrganizationNameChanged),
+            if let Some(error) = self.organizations.current.errors().get("name") {
+                text(error.to_string())
+                    .size(12)
+                    .style(|_theme| text::Style {
+                        color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                    })
+            } else {
+                text("").height(0)
+            }
+        ];
+        let header_row = row![
+            text("ID").width(Length::FillPortion(1)),
+            text("Name").width(Length::FillPortion(2)),
+            text("Action")
+        ];
+        let organization_list = scrollable(self.organizations.list().iter().fold(
+            column![header_row].spacing(2),
+            |col, organization| {
+                col.push(
+                    row![
+                        text(organization.id()).width(Length::FillPortion(1)),
+                        text(organization.name().to_string()).width(Length::FillPortion(2)),
+                        button("Edit")
+                            .style(button::primary)
+                            .on_press(Message::OrganizationLoad(organization.id())),
+                        button("Contacts").on_press(Message::ExportContacts(
+                            ContactScope::Organization(organization.id())
+                        )),
+                        button("Delete")
+                            .style(button::danger)
+                            .on_press(Message::OrganizationDelete(organization.id())),
+                    ]
+                    .spacing(10)
+                    .padding(5),
+                )
+            },
+        ))
+        .height(Length::Fill);
+
+        container(
+            column![
+                name_input,
+                self.get_form_buttons(
+                    self.organizations.is_edit,
+                    Message::OrganizationCreate,
+                    Message::OrganizationUpdate
+                ),
+                self.history_panel(self.organizations.is_edit),
+                row![
+                    button("Export...").on_press(Message::ExportList(DomainEntity::Organization)),
+                    pick_list(
+                        ChartCollapse::ALL,
+                        Some(self.preferences.org_chart),
+                        Message::OrgChartCollapseChanged,
+                    ),
+                    tooltip(
+                        button("Org chart...").on_press(Message::ExportOrgChart),
+                        text(
+                            "Users have no managers, so the chart only lists each \
+                             organization's users, or their jobs, below it"
+                        )
+                        .size(12),
+                        tooltip::Position::Top,
+                    ),
+                ]
+                .spacing(10),
+                organization_list
+            ]
+            .spacing(10),
+        )
+        .width(FillPortion(4))
+    }
+
+    fn user_form(&self) -> Container<'_, Message> {
+        let name_input = column![
+            text_input("User", self.users.current.name()).on_input(Message::UserNameChanged),
+            if let Some(error) = self.users.current.errors().get("name") {
+                text(error.to_string())
+                    .size(12)
+                    .style(|_theme| text::Style {
+                        color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                    })
+            } else {
+                text("").height(0)
+            }
+        ];
+        let job_input = column![
+            pick_list(
+                self.jobs.list(),
+                self.jobs.get(self.users.current.job_id()),
+                Message::UserJobSelected,
+            ),
+            if let Some(error) = self.users.current.errors().get("job_id") {
+                text(error.to_string())
+                    .size(12)
+                    .style(|_theme| text::Style {
+                        color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                    })
+            } else {
+                text("").height(0)
+            }
+        ];
+        let organization_input = column![
+            pick_list(
+                self.organizations.list(),
+                self.organizations.get(self.users.current.organization_id()),
+                Message::UserOrganizationSelected,
+            ),
+            if let Some(error) = self.users.current.errors().get("organization_id") {
+                text(error.to_string())
+                    .size(12)
+                    .style(|_theme| text::Style {
+                        color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                    })
+            } else {
+                text("").height(0)
+            }
+        ];
+        let contact_input = row![
+            column![
+                text_input("Email", self.users.current.email()).on_input(Message::UserEmailChanged),
+                if let Some(error) = self.users.current.errors().get("email") {
+                    text(error.to_string())
+                        .size(12)
+                        .style(|_theme| text::Style {
+                            color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                        })
+                } else {
+                    text("").height(0)
+                }
+            ],
+            column![
+                text_input("Phone", self.users.current.phone()).on_input(Message::UserPhoneChanged),
+                if let Some(error) = self.users.current.errors().get("phone") {
+                    text(error.to_string())
+                        .size(12)
+                        .style(|_theme| text::Style {
+                            color: Some(Color::from_rgb(0.8, 0.2, 0.2)),
+                        })
+                } else {
+                    text("").height(0)
+                }
+            ],
+        ]
+        .spacing(10);
+        let query = &self.user_page.query;
+        let filter_row = row![
+            text_input(
+                "Filter by name",
+                query.name_contains.as_deref().unwrap_or("")
+            )
+            .on_input(Message::UserFilterNameChanged)
+            .width(Length::FillPortion(2)),
+            pick_list(
+                self.jobs.list(),
+                query.job_id.and_then(|id| self.jobs.get(id)),
+                Message::UserFilterJobSelected,
+            )
+            .placeholder("Any job"),
+            pick_list(
+                self.organizations.list(),
+                query
+                    .organization_id
+                    .and_then(|id| self.organizations.get(id)),
+                Message::UserFilterOrganizationSelected,
+            )
+            .placeholder("Any organization"),
+            button("Clear filters")
+                .style(button::secondary)
+                .on_press(Message::UserFilterClear),
+        ]
+        .spacing(10);
+        let columns = self.preferences.user_columns;
+        let header_row = row![
+            text("").width(Length::Fixed(20.0)),
+            self.sort_header(UserSortKey::Id)
+                .width(Length::FillPortion(columns.get(UserSortKey::Id))),
+            self.sort_header(UserSortKey::Name)
+                .width(Length::FillPortion(columns.get(UserSortKey::Name))),
+            self.sort_header(UserSortKey::Job)
+                .width(Length::FillPortion(columns.get(UserSortKey::Job))),
+            self.sort_header(UserSortKey::Organization)
+                .width(Length::FillPortion(columns.get(UserSortKey::Organization))),
+            text("Action").width(Length::FillPortion(2)),
+        ];
+        let user_list = self.user_table();
+        let mut selection_actions = row![
+            button("Import CSV...").on_press(Message::ImportCsv),
+            button("Export...").on_press(Message::ExportList(DomainEntity::User)),
+            button(text(if self.users.selected.is_empty() {
+                "Export contacts".to_string()
+            } else {
+                format!("Export contacts ({})", self.users.selected.len())
+            }))
+            .on_press(Message::ExportContacts(self.contact_scope())),
+        ]
+        .spacing(10);
+        if !self.users.selected.is_empty() {
+            selection_actions = selection_actions.push(
+                button(text(format!(
+                    "Delete selected ({})",
+                    self.users.selected.len()
+                )))
+                .style(button::danger)
+                .on_press(Message::UserDeleteSelected),
+            );
+        }
+        container(
+            column![
+                name_input,
+                job_input,
+                organization_input,
+                contact_input,
+                if self.users.is_edit {
+                    self.get_form_buttons(true, Message::UserCreate, Message::UserUpdate)
+                        .push(button("Export vCard").on_press(Message::ExportContacts(
+                            ContactScope::User(self.users.current.id()),
+                        )))
+                } else {
+                    self.get_form_buttons(false, Message::UserCreate, Message::UserUpdate)
+                },
+                self.history_panel(self.users.is_edit),
+                filter_row,
+                selection_actions,
+                header_row,
+                user_list,
+                self.pager()
+            ]
+            .spacing(10),
+        )
+        .width(FillPortion(4))
+    }
+
+    /// The user rows, of which only those in view are built. Spacers above
+    /// and below stand in for the rest so the scrollbar stays true.
+    fn user_table(&self) -> Element<'_, Message> {
+        let users = self.users.list();
+        let columns = self.preferences.user_columns;
+        let range = self.user_table.visible_range(users.len());
+        let above = space().height(self.user_table.height_of(range.start));
+        let below = space().height(self.user_table.height_of(users.len() - range.end));
+
+        let rows = users[range].iter().fold(column![above], |col, user| {
+            let job_name = self.get_job_name(user.job_id());
+            let organization_name = self.get_organization_name(user.organization_id());
+
+            let user_id = user.id();
+
+            col.push(
+                row![
+                    checkbox(self.users.selected.contains(&user_id))
+                        .on_toggle(move |selected| Message::UserToggleSelected(user_id, selected))
+                        .width(Length::Fixed(20.0)),
+                    text(user.id()).width(Length::FillPortion(columns.get(UserSortKey::Id))),
+                    text(user.name().to_string())
+                        .width(Length::FillPortion(columns.get(UserSortKey::Name))),
+                    button(text(job_name))
+                        .style(button::text)
+                        .on_press(Message::JobClicked(user.job_id()))
+                        .width(Length::FillPortion(columns.get(UserSortKey::Job))),
+                    button(text(organization_name))
+                        .style(button::text)
+                        .on_press(Message::OrganizationClicked(user.organization_id()))
+                        .width(Length::FillPortion(columns.get(UserSortKey::Organization))),
+                    button("Edit")
+                        .style(button::primary)
+                        .on_press(Message::UserLoad(user.id()))
+                        .width(Length::FillPortion(1)),
+                    button("Delete")
+                        .style(button::danger)
+                        .on_press(Message::UserDelete(user.id()))
+                        .width(Length::FillPortion(1)),
+                ]
+                .spacing(10)
+                .padding(5)
+                .height(Length::Fixed(USER_ROW_HEIGHT))
+                .align_y(iced::Alignment::Center),
+            )
+        });
+
+        scrollable(rows.push(below))
+            .id(USER_TABLE)
+            .on_scroll(|viewport| {
+                Message::UserTableScrolled(viewport.absolute_offset().y, viewport.bounds().height)
+            })
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn sort_header(&self, key: UserSortKey) -> Button<'_, Message> {
+        let query = &self.user_page.query;
+        let label = if query.sort == key {
+            match query.direction {
+                SortDirection::Ascending => format!("{} ▲", key),
+                SortDirection::Descending => format!("{} ▼", key),
+            }
+        } else {
+            key.to_string()
+        };
+        button(text(label))
+            .style(button::text)
+            .padding(0)
+            .on_press(Message::UserSortBy(key))
+    }
+
+    fn pager(&self) -> Row<'_, Message> {
+        row![
+            button("Previous").on_press_maybe(
+                self.user_page
+                    .has_previous()
+                    .then_some(Message::UserPreviousPage)
+            ),
+            text(format!(
+                "Page {} of {} ({} users)",
+                self.user_page.page_number(),
+                self.user_page.page_count(),
+                self.user_page.total
+            )),
+            button("Next").on_press_maybe(
+                self.user_page
+                    .next
+                    .is_some()
+                    .then_some(Message::UserNextPage)
+            ),
+            text("Page size"),
+            pick_list(
+                PAGE_SIZES,
+                Some(self.user_page.query.limit),
+                Message::UserPageSizeSelected
+            ),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center)
+    }
+
+    /// The contacts the Users page exports: the selected users, else the
+    /// filtered organization, else everyone as one address book.
+    fn contact_scope(&self) -> ContactScope {
+        if !self.users.selected.is_empty() {
+            ContactScope::Selection(self.users.selected.iter().copied().collect())
+        } else if let Some(organization_id) = self.user_page.query.organization_id {
+            ContactScope::Organization(organization_id)
+        } else {
+            ContactScope::All
+        }
+    }
+
+    fn get_form_buttons(
+        &self,
+        is_edit: bool,
+        create: Message,
+        update: Message,
+    ) -> Row<'_, Message> {
+        if is_edit {
+            row![
+                button("Update").on_press(update),
+                button("Cancel")
+                    .style(button::danger)
+                    .on_press(Message::CancelEdit)
+            ]
+            .spacing(10)
+        } else {
+            row![button("Create").on_press(create)]
+        }
+    }
+
+    fn history_panel(&self, is_edit: bool) -> Column<'_, Message> {
+        if !is_edit {
+            return column![];
+        }
+
+        let entries = self.audit.history.iter().fold(
+            column![text("History").size(16)].spacing(4),
+            |col, entry| col.push(audit_entry_view(entry, false)),
+        );
+
+        column![scrollable(entries).height(Length::Fixed(150.0))]
+    }
+
+    fn audit_form(&self) -> Container<'_, Message> {
+        let filters = row![
+            pick_list(
+                &DomainEntity::ALL[..],
+                self.audit.filter.entity_type,
+                Message::AuditEntityFilterSelected,
+            )
+            .placeholder("Entity"),
+            pick_list(
+                &AuditAction::ALL[..],
+                self.audit.filter.action,
+                Message::AuditActionFilterSelected,
+            )
+            .placeholder("Action"),
+            text_input("From (YYYY-MM-DD)", &self.audit.from_input)
+                .on_input(Message::AuditFromChanged)
+                .width(160),
+            text_input("To (YYYY-MM-DD)", &self.audit.to_input)
+                .on_input(Message::AuditToChanged)
+                .width(160),
+            button("Apply").on_press(Message::AuditApplyFilter),
+            button("Clear")
+                .style(button::secondary)
+                .on_press(Message::AuditClearFilter),
+        ]
+        .spacing(10);
+
+        let entries = scrollable(
+            self.audit
+                .entries
+                .iter()
+                .fold(column![].spacing(4), |col, entry| {
+                    col.push(audit_entry_view(entry, true))
+                }),
+        )
+        .height(Length::Fill);
+
+        container(column![filters, entries].spacing(10)).width(FillPortion(4))
+    }
+
+    fn search_results(&self) -> Element<'_, Message> {
+        let results = &self.search.results;
+        if self.search.query.trim().is_empty() {
+            return column![].into();
+        }
+        if results.is_empty() {
+            return text("No matches").size(12).into();
+        }
+
+        let result_button = |label: String, entity: DomainEntity, id: i64| {
+            button(text(label).size(12))
+                .style(button::text)
+                .width(Length::Fill)
+                .on_press(Message::SearchResultSelected(entity, id))
+        };
+
+        let mut col = column![].spacing(2);
+        for user in &results.users {
+            col = col.push(result_button(
+                format!("User: {}", user.name()),
+                DomainEntity::User,
+                user.id(),
+            ));
+        }
+        for job in &results.jobs {
+            col = col.push(result_button(
+                format!("Job: {}", job.name()),
+                DomainEntity::Job,
+                job.id(),
+            ));
+        }
+        for organization in &results.organizations {
+            col = col.push(result_button(
+                format!("Organization: {}", organization.name()),
+                DomainEntity::Organization,
+                organization.id(),
+            ));
+        }
+
+        container(scrollable(col)).max_height(300).into()
+    }
+
+    fn settings_form(&self) -> Container<'_, Message> {
+        let theme_input =
+            pick_list(Theme::ALL, Some(&self.theme), Message::ThemeChanged).width(220);
+        let database_actions = row![
+            button("Back up database").on_press(Message::BackupDatabase),
+            button("Restore from backup")
+                .style(button::danger)
+                .on_press(Message::RestoreDatabase),
+            button("Export snapshot").on_press(Message::ExportSnapshot),
+            button("Import snapshot").on_press(Message::ImportSnapshot),
+        ]
+        .spacing(10);
+        let check_button = button(if self.integrity.is_running {
+            "Checking..."
+        } else {
+            "Run integrity check"
+        })
+        .on_press_maybe((!self.integrity.is_running).then_some(Message::CheckIntegrity));
+
+        let column_widths = [
+            UserSortKey::Id,
+            UserSortKey::Name,
+            UserSortKey::Job,
+            UserSortKey::Organization,
+        ]
+        .into_iter()
+        .fold(column![].spacing(5), |col, column| {
+            col.push(
+                row![
+                    text(column.to_string()).width(120),
+                    slider(
+                        UserColumnWidths::MIN..=UserColumnWidths::MAX,
+                        self.preferences.user_columns.get(column),
+                        move |width| Message::UserColumnWidthChanged(column, width),
+                    )
+                    .width(220),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+            )
+        });
+
+        let generator = &self.generator;
+        let generator_form = column![
+            row![
+                text("Organizations").width(120),
+                text_input("10", &generator.organizations)
+                    .on_input(Message::GeneratorOrganizationsChanged)
+                    .width(100),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            row![
+                text("Jobs").width(120),
+                text_input("25", &generator.jobs)
+                    .on_input(Message::GeneratorJobsChanged)
+                    .width(100),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            row![
+                text("Users").width(120),
+                text_input("1000", &generator.users)
+                    .on_input(Message::GeneratorUsersChanged)
+                    .width(100),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            row![
+                text("Seed").width(120),
+                text_input("42", &generator.seed)
+                    .on_input(Message::GeneratorSeedChanged)
+                    .width(100),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            button(if generator.is_running {
+                "Generating..."
+            } else {
+                "Generate sample data"
+            })
+            .on_press_maybe((!generator.is_running).then_some(Message::GenerateData)),
+        ]
+        .spacing(5);
+
+        container(scrollable(
+            column![
+                text("Theme").size(16),
+                theme_input,
+                text("User table columns").size(16),
+                column_widths,
+                text("Database").size(16),
+                database_actions,
+                text("Data integrity").size(16),
+                check_button,
+                self.integrity_report(),
+                text("Sample data").size(16),
+                generator_form,
+                text("LDAP directory").size(16),
+                self.ldif_form(),
+            ]
+            .spacing(10),
+        ))
+        .width(FillPortion(4))
+    }
+
+    fn ldif_form(&self) -> Column<'_, Message> {
+        let options = &self.preferences.ldif;
+        let fields = LdifField::ALL
+            .iter()
+            .fold(column![].spacing(5), |col, &field| {
+                col.push(
+                    row![
+                        text(field.to_string()).width(180),
+                        text_input("", options.get(field))
+                            .on_input(move |value| Message::LdifOptionChanged(field, value)),
+                    ]
+                    .spacing(10),
+                )
+            });
+        let actions = row![
+            button("Export LDIF...").on_press(Message::ExportLdif),
+            button("Import LDIF...").on_press(Message::ImportLdif),
+        ]
+        .spacing(10);
+
+        let mut col = column![
+            fields,
+            text("Templates can use {name}, {job}, {organization}, {uid} and {id}.").size(12),
+            actions,
+        ]
+        .spacing(10);
+
+        let Some(plan) = &self.ldif.plan else {
+            return col;
+        };
+        let diff = plan.diff(self.jobs.list(), self.organizations.list());
+        let lines =
+            diff.iter()
+                .take(IMPORT_PREVIEW_ROWS)
+                .fold(column![].spacing(2), |col, line| {
+                    let line_text = text(line.clone()).size(12);
+                    col.push(if line.starts_with('!') {
+                        line_text.style(text::danger)
+                    } else {
+                        line_text
+                    })
+                });
+        col = col
+            .push(text(format!(
+                "Dry run of {}: {} new jobs, {} new organizations, {} users to change, \
+                 {} unchanged, {} skipped.",
+                self.ldif
+                    .path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+                plan.new_jobs.len(),
+                plan.new_organizations.len(),
+                plan.changes.len(),
+                plan.unchanged,
+                plan.skipped.len()
+            )))
+            .push(scrollable(lines).height(300))
+            .push(
+                row![
+                    button("Apply").on_press_maybe(
+                        (!self.ldif.is_running && !plan.is_empty()).then_some(Message::LdifApply)
+                    ),
+                    button("Discard")
+                        .on_press_maybe((!self.ldif.is_running).then_some(Message::LdifDiscard)),
+                ]
+                .spacing(10),
+            );
+        col
+    }
+
+    fn import_form(&self) -> Container<'_, Message> {
+        let import = &self.import;
+        let Some(file) = &import.file else {
+            return container(text("No file loaded.")).width(FillPortion(4));
+        };
+        let preview = &import.preview;
+        let columns = file.columns();
+
+        let mapping = ImportField::ALL
+            .iter()
+            .fold(column![].spacing(5), |col, &field| {
+                let selected = import
+                    .mapping
+                    .get(field)
+                    .and_then(|index| columns.iter().find(|column| column.index == index))
+                    .cloned();
+                col.push(
+                    row![
+                        text(field.to_string()).width(120),
+                        pick_list(columns.clone(), selected, move |column| {
+                            Message::ImportColumnMapped(field, column)
+                        })
+                        .placeholder("Not mapped")
+                        .width(250),
+                        button("Clear").on_press(Message::ImportColumnCleared(field)),
+                    ]
+                    .spacing(10),
+                )
+            });
+
+        let rejected = preview.rejected().count();
+        let mut summary = format!(
+            "{} rows: {} valid, {} rejected.",
+            preview.rows.len(),
+            preview.valid_count(),
+            rejected
+        );
+        if !preview.new_jobs.is_empty() || !preview.new_organizations.is_empty() {
+            summary.push_str(&format!(
+                " Creates {} jobs and {} organizations.",
+                preview.new_jobs.len(),
+                preview.new_organizations.len()
+            ));
+        }
+
+        let actions = row![
+            button(text(format!("Import {} valid rows", preview.valid_count()))).on_press_maybe(
+                (!import.is_running && preview.valid_count() > 0).then_some(Message::ImportCommit)
+            ),
+            button("Save rejected rows...")
+                .on_press_maybe((rejected > 0).then_some(Message::ImportSaveRejected)),
+            button("Cancel").on_press_maybe((!import.is_running).then_some(Message::ImportCancel)),
+        ]
+        .spacing(10);
+
+        let header_row = row![
+            text("Line").width(Length::FillPortion(1)),
+            text("Name").width(Length::FillPortion(2)),
+            text("Job").width(Length::FillPortion(2)),
+            text("Organization").width(Length::FillPortion(2)),
+            text("Email").width(Length::FillPortion(2)),
+            text("Phone").width(Length::FillPortion(2)),
+            text("Problems").width(Length::FillPortion(3)),
+        ];
+        let rows = preview.rows.iter().take(IMPORT_PREVIEW_ROWS).fold(
+            column![].spacing(5),
+            |col, import_row| {
+                let reference = |name: &str, reference: &Option<Reference>| match reference {
+                    Some(Reference::New(_)) => format!("{} (new)", name),
+                    _ => name.to_string(),
+                };
+                col.push(row![
+                    text(import_row.line).width(Length::FillPortion(1)),
+                    text(import_row.name.clone()).width(Length::FillPortion(2)),
+                    text(reference(&import_row.job_name, &import_row.job))
+                        .width(Length::FillPortion(2)),
+                    text(reference(
+                        &import_row.organization_name,
+                        &import_row.organization
+                    ))
+                    .width(Length::FillPortion(2)),
+                    text(import_row.email.clone()).width(Length::FillPortion(2)),
+                    text(import_row.phone.clone()).width(Length::FillPortion(2)),
+                    text(import_row.errors.join("; "))
+                        .style(text::danger)
+                        .width(Length::FillPortion(3)),
+                ])
+            },
+        );
+        let more = if preview.rows.len() > IMPORT_PREVIEW_ROWS {
+            text(format!("Showing the first {} rows.", IMPORT_PREVIEW_ROWS))
+        } else {
+            text("")
+        };
+
+        container(
+            column![
+                text(format!("Import users from {}", file.path.display())).size(16),
+                mapping,
+                checkbox(import.create_missing)
+                    .label("Create missing jobs and organizations")
+                    .on_toggle(Message::ImportCreateMissingToggled),
+                text(summary),
+                actions,
+                header_row,
+                scrollable(rows).height(Fill),
+                more,
+            ]
+            .spacing(10),
+        )
+        .width(FillPortion(4))
+    }
+
+    fn integrity_report(&self) -> Column<'_, Message> {
+        let Some(report) = &self.integrity.report else {
+            return column![];
+        };
+        if report.is_healthy() {
+            return column![text("No problems found.")];
+        }
+
+        let mut col = column![].spacing(10);
+
+        if !report.problems.is_empty() {
+            col = col.push(text(
+                "The database file is damaged. Restore it from a backup to repair it.",
+            ));
+            col = report
+                .problems
+                .iter()
+                .fold(col, |col, problem| col.push(text(problem).size(12)));
+        }
+
+        if !report.foreign_key_violations.is_empty() {
+            col = col.push(text(format!(
+                "{} rows reference missing records:",
+                report.foreign_key_violations.len()
+            )));
+            col = report
+                .foreign_key_violations
+                .iter()
+                .fold(col, |col, violation| {
+                    col.push(
+                        text(format!(
+                            "    {} row {} references a missing {} record",
+                            violation.table,
+                            violation
+                                .rowid
+                                .map(|rowid| rowid.to_string())
+                                .unwrap_or_else(|| "?".to_string()),
+                            violation.parent
+                        ))
+                        .size(12),
+                    )
+                });
+        }
+
+        if !report.orphaned_users.is_empty() {
+            let replacements = row![
+                pick_list(
+                    self.jobs.list(),
+                    self.integrity.replacement_job.clone(),
+                    Message::IntegrityReplacementJobSelected,
+                )
+                .placeholder("Replacement job"),
+                pick_list(
+                    self.organizations.list(),
+                    self.integrity.replacement_organization.clone(),
+                    Message::IntegrityReplacementOrganizationSelected,
+                )
+                .placeholder("Replacement organization"),
+                button("Reassign all").on_press_maybe(
+                    self.integrity
+                        .reassign_all()
+                        .map(|_| Message::RepairReassignAll)
+                ),
+                button("Delete all")
+                    .style(button::danger)
+                    .on_press(Message::RepairDeleteAll),
+            ]
+            .spacing(10);
+
+            col = col
+                .push(text(format!(
+                    "{} users point at a job or organization that no longer exists. \
+                     Pick replacements to reassign them, or delete them.",
+                    report.orphaned_users.len()
+                )))
+                .push(replacements);
+
+            col = report.orphaned_users.iter().fold(col, |col, orphan| {
+                let missing = match (orphan.missing_job, orphan.missing_organization) {
+                    (true, true) => "missing job and organization",
+                    (true, false) => "missing job",
+                    _ => "missing organization",
+                };
+                col.push(
+                    row![
+                        text(orphan.user.name()).width(200),
+                        text(missing).width(220),
+                        button("Reassign").on_press_maybe(
+                            self.integrity
+                                .reassign(orphan)
+                                .map(|_| Message::RepairReassignUser(orphan.user.id()))
+                        ),
+                        button("Delete")
+                            .style(button::danger)
+                            .on_press(Message::RepairDeleteUser(orphan.user.id())),
+                    ]
+                    .spacing(10),
+                )
+            });
+        }
+
+        col
+    }
+}
+
+fn audit_entry_view(entry: &AuditEntry, show_record: bool) -> Column<'_, Message> {
+    let mut summary = format!(
+        "{}  {}  by {}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        entry.action,
+        entry.operator
+    );
+    if show_record {
+        summary = format!("{}  {} #{}", summary, entry.entity_type, entry.entity_id);
+    }
+
+    entry.changes().into_iter().fold(
+        column![text(summary).size(14)].padding(5),
+        |col, (field, before, after)| {
+            col.push(
+                text(format!(
+                    "    {}: {} -> {}",
+                    field,
+                    before.unwrap_or_else(|| "-".to_string()),
+                    after.unwrap_or_else(|| "-".to_string())
+                ))
+                .size(12),
+            )
+        },
+    )
+}
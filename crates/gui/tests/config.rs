@@ -0,0 +1,199 @@
+//! Resolves startup options from arguments, the environment and a config
+//! file, checking that each layer overrides the ones below it.
+
+use clap::Parser;
+use iced::Theme;
+use log::LevelFilter;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use iced_user_management::config::{Args, ConfigError, StartupOptions};
+use iced_user_management::message::Page;
+use usermgmt_core::infrastructure::get_database_path;
+
+/// The environment is shared by every test in the process.
+static ENVIRONMENT: Mutex<()> = Mutex::new(());
+
+/// Parses `args` with only the given `USERMGMT_*` variables set.
+fn options(environment: &[(&str, &str)], args: &[&str]) -> Result<StartupOptions, ConfigError> {
+    let _lock = ENVIRONMENT.lock().unwrap_or_else(|e| e.into_inner());
+    let clear = || {
+        for (name, _) in std::env::vars() {
+            if name.starts_with("USERMGMT_") {
+                std::env::remove_var(name);
+            }
+        }
+    };
+    clear();
+    for (name, value) in environment {
+        std::env::set_var(name, value);
+    }
+    let args =
+        Args::try_parse_from(std::iter::once("iced-user-management").chain(args.iter().copied()))
+            .expect("valid arguments");
+    let options = StartupOptions::from_args(args);
+    clear();
+    options
+}
+
+/// Writes a config file that sets every key.
+fn config_file(dir: &Path) -> PathBuf {
+    let path = dir.join("config.toml");
+    std::fs::write(
+        &path,
+        r#"
+database = "fixtures/file.db"
+read_only = true
+page = "jobs"
+theme = "Dracula"
+log_level = "info"
+"#,
+    )
+    .expect("config file is written");
+    path
+}
+
+#[test]
+fn defaults_apply_when_nothing_is_set() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let path = dir.path().join("empty.toml");
+    std::fs::write(&path, "").unwrap();
+
+    let options = options(&[], &["--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(options.database_path, get_database_path());
+    assert!(!options.read_only);
+    assert!(!options.demo);
+    assert_eq!(options.page, None);
+    assert_eq!(options.theme, None);
+    assert_eq!(options.log_level, LevelFilter::Warn);
+    assert!(options.generate.is_none());
+}
+
+#[test]
+fn the_config_file_overrides_the_defaults() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let path = config_file(dir.path());
+
+    let options = options(&[], &["--config", path.to_str().unwrap()]).unwrap();
+    // Relative to the config file, not the working directory.
+    assert_eq!(options.database_path, dir.path().join("fixtures/file.db"));
+    assert!(options.read_only);
+    assert_eq!(options.page, Some(Page::Job));
+    assert_eq!(options.theme, Some(Theme::Dracula));
+    assert_eq!(options.log_level, LevelFilter::Info);
+
+    // The file can also be chosen through the environment.
+    let from_environment =
+        self::options(&[("USERMGMT_CONFIG", path.to_str().unwrap())], &[]).unwrap();
+    assert_eq!(from_environment.database_path, options.database_path);
+    assert_eq!(from_environment.page, Some(Page::Job));
+
+    // Without either, config.toml is looked for in the config directory.
+    let from_config_dir = self::options(
+        &[("USERMGMT_CONFIG_DIR", dir.path().to_str().unwrap())],
+        &[],
+    )
+    .unwrap();
+    assert_eq!(from_config_dir.database_path, options.database_path);
+    assert_eq!(from_config_dir.theme, Some(Theme::Dracula));
+}
+
+#[test]
+fn the_environment_overrides_the_config_file() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let path = config_file(dir.path());
+
+    let options = options(
+        &[
+            ("USERMGMT_DATABASE", "/data/env.db"),
+            ("USERMGMT_READ_ONLY", "false"),
+            ("USERMGMT_PAGE", "audit"),
+            ("USERMGMT_LOG_LEVEL", "debug"),
+        ],
+        &["--config", path.to_str().unwrap()],
+    )
+    .unwrap();
+    assert_eq!(options.database_path, PathBuf::from("/data/env.db"));
+    assert!(!options.read_only);
+    assert_eq!(options.page, Some(Page::Audit));
+    assert_eq!(options.log_level, LevelFilter::Debug);
+    // Not set in the environment, so still from the file.
+    assert_eq!(options.theme, Some(Theme::Dracula));
+}
+
+#[test]
+fn arguments_override_the_environment() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let path = config_file(dir.path());
+
+    let options = options(
+        &[
+            ("USERMGMT_DATABASE", "/data/env.db"),
+            ("USERMGMT_READ_ONLY", "false"),
+            ("USERMGMT_PAGE", "audit"),
+            ("USERMGMT_THEME", "Nord"),
+            ("USERMGMT_LOG_LEVEL", "debug"),
+        ],
+        &[
+            "--config",
+            path.to_str().unwrap(),
+            "--database",
+            "fixtures/qa.db",
+            "--read-only",
+            "--page",
+            "settings",
+            "--theme",
+            "tokyo-night",
+            "--log-level",
+            "trace",
+        ],
+    )
+    .unwrap();
+    // Relative to the working directory, as any other argument.
+    assert_eq!(options.database_path, PathBuf::from("fixtures/qa.db"));
+    assert!(options.read_only);
+    assert_eq!(options.page, Some(Page::Settings));
+    assert_eq!(options.theme, Some(Theme::TokyoNight));
+    assert_eq!(options.log_level, LevelFilter::Trace);
+}
+
+#[test]
+fn invalid_settings_are_reported() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let missing = dir.path().join("missing.toml");
+    assert!(matches!(
+        options(&[], &["--config", missing.to_str().unwrap()]),
+        Err(ConfigError::Read(path, _)) if path == missing
+    ));
+
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "databse = \"typo.db\"\n").unwrap();
+    assert!(matches!(
+        options(&[], &["--config", path.to_str().unwrap()]),
+        Err(ConfigError::Parse(..))
+    ));
+
+    std::fs::write(&path, "page = \"reports\"\n").unwrap();
+    assert!(matches!(
+        options(&[], &["--config", path.to_str().unwrap()]),
+        Err(ConfigError::UnknownPage(page)) if page == "reports"
+    ));
+    // A valid argument hides an invalid value below it.
+    assert_eq!(
+        options(
+            &[],
+            &["--config", path.to_str().unwrap(), "--page", "users"]
+        )
+        .unwrap()
+        .page,
+        Some(Page::User)
+    );
+    assert!(matches!(
+        options(&[("USERMGMT_THEME", "Neon")], &["--config", path.to_str().unwrap(), "--page", "users"]),
+        Err(ConfigError::UnknownTheme(theme)) if theme == "Neon"
+    ));
+    assert!(matches!(
+        options(&[("USERMGMT_LOG_LEVEL", "loud")], &["--config", path.to_str().unwrap(), "--page", "users"]),
+        Err(ConfigError::UnknownLogLevel(level)) if level == "loud"
+    ));
+}
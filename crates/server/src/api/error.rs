@@ -0,0 +1,157 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::domain::services::{JobServiceError, OrganizationServiceError};
+use crate::domain::{RepositoryError, UserServiceError};
+
+/// Every error is answered with the same body:
+///
+/// ```json
+/// {"error": {"status": 422, "code": "validation_failed", "message": "...", "fields": {"name": "Name is required"}}}
+/// ```
+///
+/// `code` is stable for scripts to match on; `message` is for people.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    /// Validation messages by field, for `validation_failed`.
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: ErrorDetail<'a>,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail<'a> {
+    status: u16,
+    code: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: &'a BTreeMap<String, String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn validation(fields: BTreeMap<String, String>) -> Self {
+        Self {
+            fields,
+            ..Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                "Validation failed",
+            )
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.status.as_u16(),
+            self.code,
+            self.message
+        )
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: ErrorDetail {
+                status: self.status.as_u16(),
+                code: self.code,
+                message: &self.message,
+                fields: &self.fields,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<RepositoryError> for ApiError {
+    fn from(error: RepositoryError) -> Self {
+        match error {
+            RepositoryError::NotFound => Self::not_found(error.to_string()),
+            RepositoryError::Conflict => {
+                Self::new(StatusCode::CONFLICT, "conflict", error.to_string())
+            }
+            RepositoryError::ConstraintViolation(_) => Self::new(
+                StatusCode::CONFLICT,
+                "constraint_violation",
+                error.to_string(),
+            ),
+            RepositoryError::DatabaseError(detail) => {
+                log::error!("API request failed: {}", detail);
+                Self::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "database_error",
+                    "Database error",
+                )
+            }
+        }
+    }
+}
+
+impl From<UserServiceError> for ApiError {
+    fn from(error: UserServiceError) -> Self {
+        let code = match error {
+            UserServiceError::RepositoryError(error) => return error.into(),
+            UserServiceError::UserNotFound => return Self::not_found(error.to_string()),
+            UserServiceError::ValidationError => "validation_failed",
+            UserServiceError::JobNotFound => "job_not_found",
+            UserServiceError::OrganizationNotFound => "organization_not_found",
+        };
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, code, error.to_string())
+    }
+}
+
+impl From<JobServiceError> for ApiError {
+    fn from(error: JobServiceError) -> Self {
+        match error {
+            JobServiceError::RepositoryError(error) => error.into(),
+            JobServiceError::ValidationError => Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                error.to_string(),
+            ),
+        }
+    }
+}
+
+impl From<OrganizationServiceError> for ApiError {
+    fn from(error: OrganizationServiceError) -> Self {
+        match error {
+            OrganizationServiceError::RepositoryError(error) => error.into(),
+            OrganizationServiceError::ValidationError => Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                error.to_string(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,532 @@
+//! A JSON API over the same services the window uses, served by the
+//! `usermgmt-api` binary for scripts.
+//!
+//! Users, jobs and organizations each have list, create, read, replace and
+//! delete endpoints under `/api`. The OpenAPI document describing them is
+//! served at `/api/openapi.json`, and every error has the body described on
+//! [`ApiError`].
+
+mod error;
+mod openapi;
+
+use axum::body::Bytes;
+use axum::extract::rejection::{PathRejection, QueryRejection};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::domain::{
+    Entity, Job, Organization, Services, SortDirection, User, UserCursor, UserQuery, UserSortKey,
+    DEFAULT_PAGE_SIZE,
+};
+
+pub use error::ApiError;
+
+/// Pages of users hold at most this many, whatever `limit` asks for.
+pub const MAX_PAGE_SIZE: i64 = 1_000;
+
+struct ApiState {
+    services: Services,
+    read_only: bool,
+}
+
+/// Serves the API on `listener` until the process is interrupted.
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    services: Services,
+    read_only: bool,
+) -> std::io::Result<()> {
+    log::info!("Serving the API on http://{}/api", listener.local_addr()?);
+    axum::serve(listener, router(services, read_only))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+pub fn router(services: Services, read_only: bool) -> Router {
+    let state = Arc::new(ApiState {
+        services,
+        read_only,
+    });
+    let api = Router::new()
+        .route("/openapi.json", get(|| async { Json(openapi::document()) }))
+        .route("/users", get(list_users).post(create_user))
+        .route(
+            "/users/{id}",
+            get(get_user).put(update_user).delete(delete_user),
+        )
+        .route("/jobs", get(list_jobs).post(create_job))
+        .route(
+            "/jobs/{id}",
+            get(get_job).put(update_job).delete(delete_job),
+        )
+        .route(
+            "/organizations",
+            get(list_organizations).post(create_organization),
+        )
+        .route(
+            "/organizations/{id}",
+            get(get_organization)
+                .put(update_organization)
+                .delete(delete_organization),
+        )
+        .method_not_allowed_fallback(|| async {
+            ApiError::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "method_not_allowed",
+                "Method not allowed",
+            )
+        });
+    Router::new()
+        .nest("/api", api)
+        .fallback(|| async { ApiError::not_found("No such endpoint") })
+        .layer(middleware::from_fn_with_state(state.clone(), refuse_writes))
+        .with_state(state)
+}
+
+/// Refuses every change to a database opened read-only.
+async fn refuse_writes(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if state.read_only && request.method() != Method::GET {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "read_only",
+            "The database is open read-only",
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserBody {
+    pub id: i64,
+    pub name: String,
+    pub job_id: i64,
+    pub organization_id: i64,
+    pub email: String,
+    pub phone: String,
+    pub version: i64,
+}
+
+impl From<&User> for UserBody {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id(),
+            name: user.name().to_string(),
+            job_id: user.job_id(),
+            organization_id: user.organization_id(),
+            email: user.email().to_string(),
+            phone: user.phone().to_string(),
+            version: user.version(),
+        }
+    }
+}
+
+/// A job or an organization.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NamedBody {
+    pub id: i64,
+    pub name: String,
+    pub version: i64,
+}
+
+impl NamedBody {
+    fn from_entity<T: Entity>(entity: &T) -> Self {
+        Self {
+            id: entity.id(),
+            name: entity.name().to_string(),
+            version: entity.version(),
+        }
+    }
+}
+
+/// The body of a user create or replace. `version` is the one the change is
+/// based on; when it is stale the request fails with `conflict`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserInput {
+    name: String,
+    job_id: i64,
+    organization_id: i64,
+    #[serde(default)]
+    email: String,
+    #[serde(default)]
+    phone: String,
+    #[serde(default)]
+    version: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NamedInput {
+    name: String,
+    #[serde(default)]
+    version: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct List<T> {
+    items: Vec<T>,
+    total: i64,
+    /// Pass as `cursor` to get the following page; absent on the last one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserListParams {
+    q: Option<String>,
+    job_id: Option<i64>,
+    organization_id: Option<i64>,
+    sort: Option<String>,
+    direction: Option<String>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SearchParams {
+    q: Option<String>,
+}
+
+fn query<T>(query: Result<Query<T>, QueryRejection>) -> Result<T, ApiError> {
+    query
+        .map(|Query(params)| params)
+        .map_err(|e| ApiError::bad_request("invalid_query", e.body_text()))
+}
+
+/// Ids that are not numbers cannot name anything, so they are not found
+/// rather than bad requests.
+fn resource_id(id: Result<Path<i64>, PathRejection>) -> Result<i64, ApiError> {
+    id.map(|Path(id)| id)
+        .map_err(|_| ApiError::not_found("No such resource"))
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &Bytes) -> Result<T, ApiError> {
+    serde_json::from_slice(body).map_err(|e| ApiError::bad_request("invalid_body", e.to_string()))
+}
+
+/// Checks an entity before it is handed to a service, so the error can say
+/// which fields are wrong.
+fn validate<T: Entity>(entity: &mut T) -> Result<(), ApiError> {
+    entity.validate().map_err(|errors| {
+        ApiError::validation(
+            errors
+                .iter()
+                .map(|(field, message)| (field.to_string(), message.to_string()))
+                .collect(),
+        )
+    })
+}
+
+fn created(location: String, body: impl Serialize) -> Response {
+    (
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(body),
+    )
+        .into_response()
+}
+
+/// Cursors are opaque to clients: the sort value and id of the last user of
+/// a page, base64 encoded.
+fn encode_cursor(cursor: &UserCursor) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", cursor.id, cursor.sort_value))
+}
+
+fn decode_cursor(text: &str) -> Result<UserCursor, ApiError> {
+    let invalid = || ApiError::bad_request("invalid_query", "Invalid cursor");
+    let decoded = URL_SAFE_NO_PAD.decode(text).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (id, sort_value) = decoded.split_once(':').ok_or_else(invalid)?;
+    Ok(UserCursor {
+        id: id.parse().map_err(|_| invalid())?,
+        sort_value: sort_value.to_string(),
+    })
+}
+
+async fn list_users(
+    State(state): State<Arc<ApiState>>,
+    params: Result<Query<UserListParams>, QueryRejection>,
+) -> Result<Json<List<UserBody>>, ApiError> {
+    let params = query(params)?;
+    let sort = match params.sort.as_deref() {
+        None | Some("name") => UserSortKey::Name,
+        Some("id") => UserSortKey::Id,
+        Some("job") => UserSortKey::Job,
+        Some("organization") => UserSortKey::Organization,
+        Some(other) => {
+            return Err(ApiError::bad_request(
+                "invalid_query",
+                format!(
+                    "Unknown sort '{}', expected id, name, job or organization",
+                    other
+                ),
+            ))
+        }
+    };
+    let direction = match params.direction.as_deref() {
+        None | Some("asc") => SortDirection::Ascending,
+        Some("desc") => SortDirection::Descending,
+        Some(other) => {
+            return Err(ApiError::bad_request(
+                "invalid_query",
+                format!("Unknown direction '{}', expected asc or desc", other),
+            ))
+        }
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ApiError::bad_request(
+            "invalid_query",
+            format!("limit must be between 1 and {}", MAX_PAGE_SIZE),
+        ));
+    }
+    let page = state
+        .services
+        .user
+        .get_user_page(&UserQuery {
+            organization_id: params.organization_id,
+            job_id: params.job_id,
+            name_contains: params.q,
+            sort,
+            direction,
+            after: params.cursor.as_deref().map(decode_cursor).transpose()?,
+            limit,
+        })
+        .await?;
+    Ok(Json(List {
+        items: page.users.iter().map(UserBody::from).collect(),
+        total: page.total,
+        next_cursor: page.next.as_ref().map(encode_cursor),
+    }))
+}
+
+async fn find_user(state: &ApiState, id: i64) -> Result<User, ApiError> {
+    state
+        .services
+        .user
+        .get_user_by_id(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("User {} not found", id)))
+}
+
+async fn get_user(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<UserBody>, ApiError> {
+    let id = resource_id(id)?;
+    Ok(Json(UserBody::from(&find_user(&state, id).await?)))
+}
+
+fn apply_user_input(user: &mut User, input: UserInput) -> Result<(), ApiError> {
+    user.set_name(input.name);
+    user.set_job_id(input.job_id);
+    user.set_organization_id(input.organization_id);
+    user.set_email(input.email);
+    user.set_phone(input.phone);
+    if let Some(version) = input.version {
+        user.set_version(version);
+    }
+    validate(user)
+}
+
+async fn create_user(
+    State(state): State<Arc<ApiState>>,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let mut user = User::new();
+    apply_user_input(&mut user, parse_body(&body)?)?;
+    let user = state.services.user.create_user(user).await?;
+    Ok(created(
+        format!("/api/users/{}", user.id()),
+        UserBody::from(&user),
+    ))
+}
+
+async fn update_user(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+    body: Bytes,
+) -> Result<Json<UserBody>, ApiError> {
+    let id = resource_id(id)?;
+    let input: UserInput = parse_body(&body)?;
+    let mut user = find_user(&state, id).await?;
+    apply_user_input(&mut user, input)?;
+    state.services.user.update_user(user).await?;
+    Ok(Json(UserBody::from(&find_user(&state, id).await?)))
+}
+
+async fn delete_user(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let id = resource_id(id)?;
+    state.services.user.delete_user(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn named_list<T: Entity>(mut list: Vec<T>) -> Json<List<NamedBody>> {
+    list.sort_by(|a, b| a.name().cmp(b.name()).then(a.id().cmp(&b.id())));
+    Json(List {
+        total: list.len() as i64,
+        items: list.iter().map(NamedBody::from_entity).collect(),
+        next_cursor: None,
+    })
+}
+
+fn apply_named_input<T: Entity>(entity: &mut T, input: NamedInput) -> Result<(), ApiError> {
+    entity.set_name(input.name);
+    if let Some(version) = input.version {
+        entity.set_version(version);
+    }
+    validate(entity)
+}
+
+async fn list_jobs(
+    State(state): State<Arc<ApiState>>,
+    params: Result<Query<SearchParams>, QueryRejection>,
+) -> Result<Json<List<NamedBody>>, ApiError> {
+    let jobs = match query(params)?.q.filter(|q| !q.trim().is_empty()) {
+        Some(q) => state.services.job.search_jobs(&q).await?,
+        None => state.services.job.get_all_jobs().await?,
+    };
+    Ok(named_list(jobs))
+}
+
+async fn find_job(state: &ApiState, id: i64) -> Result<Job, ApiError> {
+    state
+        .services
+        .job
+        .get_job_by_id(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Job {} not found", id)))
+}
+
+async fn get_job(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<NamedBody>, ApiError> {
+    let id = resource_id(id)?;
+    Ok(Json(NamedBody::from_entity(&find_job(&state, id).await?)))
+}
+
+async fn create_job(State(state): State<Arc<ApiState>>, body: Bytes) -> Result<Response, ApiError> {
+    let mut job = Job::new();
+    apply_named_input(&mut job, parse_body(&body)?)?;
+    let job = state.services.job.create_job(job).await?;
+    Ok(created(
+        format!("/api/jobs/{}", job.id()),
+        NamedBody::from_entity(&job),
+    ))
+}
+
+async fn update_job(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+    body: Bytes,
+) -> Result<Json<NamedBody>, ApiError> {
+    let id = resource_id(id)?;
+    let input: NamedInput = parse_body(&body)?;
+    let mut job = find_job(&state, id).await?;
+    apply_named_input(&mut job, input)?;
+    state.services.job.update_job(job).await?;
+    Ok(Json(NamedBody::from_entity(&find_job(&state, id).await?)))
+}
+
+async fn delete_job(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let id = resource_id(id)?;
+    state.services.job.delete_job(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_organizations(
+    State(state): State<Arc<ApiState>>,
+    params: Result<Query<SearchParams>, QueryRejection>,
+) -> Result<Json<List<NamedBody>>, ApiError> {
+    let organizations = match query(params)?.q.filter(|q| !q.trim().is_empty()) {
+        Some(q) => state.services.organization.search_organizations(&q).await?,
+        None => state.services.organization.get_all_organizations().await?,
+    };
+    Ok(named_list(organizations))
+}
+
+async fn find_organization(state: &ApiState, id: i64) -> Result<Organization, ApiError> {
+    state
+        .services
+        .organization
+        .get_organization_by_id(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Organization {} not found", id)))
+}
+
+async fn get_organization(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<NamedBody>, ApiError> {
+    let id = resource_id(id)?;
+    Ok(Json(NamedBody::from_entity(
+        &find_organization(&state, id).await?,
+    )))
+}
+
+async fn create_organization(
+    State(state): State<Arc<ApiState>>,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let mut organization = Organization::new();
+    apply_named_input(&mut organization, parse_body(&body)?)?;
+    let organization = state
+        .services
+        .organization
+        .create_organization(organization)
+        .await?;
+    Ok(created(
+        format!("/api/organizations/{}", organization.id()),
+        NamedBody::from_entity(&organization),
+    ))
+}
+
+async fn update_organization(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+    body: Bytes,
+) -> Result<Json<NamedBody>, ApiError> {
+    let id = resource_id(id)?;
+    let input: NamedInput = parse_body(&body)?;
+    let mut organization = find_organization(&state, id).await?;
+    apply_named_input(&mut organization, input)?;
+    state
+        .services
+        .organization
+        .update_organization(organization)
+        .await?;
+    Ok(Json(NamedBody::from_entity(
+        &find_organization(&state, id).await?,
+    )))
+}
+
+async fn delete_organization(
+    State(state): State<Arc<ApiState>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let id = resource_id(id)?;
+    state.services.organization.delete_organization(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
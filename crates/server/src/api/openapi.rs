@@ -0,0 +1,261 @@
+//! The OpenAPI 3.0 description of the API, served at `/api/openapi.json`.
+
+use serde_json::{json, Value};
+
+use super::MAX_PAGE_SIZE;
+use crate::domain::DEFAULT_PAGE_SIZE;
+
+pub fn document() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "User Management API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Users, jobs and organizations. Changes are validated \
+                and audited as in the app; every error has the Error body.",
+        },
+        "servers": [{ "url": "/api" }],
+        "paths": {
+            "/users": {
+                "get": {
+                    "summary": "List users a page at a time",
+                    "operationId": "listUsers",
+                    "parameters": [
+                        query("q", "string", "Only users whose name contains this"),
+                        query("job_id", "integer", "Only users with this job"),
+                        query("organization_id", "integer", "Only users in this organization"),
+                        {
+                            "name": "sort", "in": "query",
+                            "schema": { "type": "string", "enum": ["id", "name", "job", "organization"], "default": "name" },
+                        },
+                        {
+                            "name": "direction", "in": "query",
+                            "schema": { "type": "string", "enum": ["asc", "desc"], "default": "asc" },
+                        },
+                        {
+                            "name": "limit", "in": "query",
+                            "schema": { "type": "integer", "minimum": 1, "maximum": MAX_PAGE_SIZE, "default": DEFAULT_PAGE_SIZE },
+                        },
+                        query("cursor", "string", "The next_cursor of the previous page"),
+                    ],
+                    "responses": {
+                        "200": ok("A page of users", "UserList"),
+                        "400": error("Invalid query"),
+                    },
+                },
+                "post": {
+                    "summary": "Create a user",
+                    "operationId": "createUser",
+                    "requestBody": body("UserInput"),
+                    "responses": {
+                        "201": ok("The created user", "User"),
+                        "400": error("Invalid body"),
+                        "422": error("Validation failed, or the job or organization does not exist"),
+                    },
+                },
+            },
+            "/users/{id}": item("user", "User", "UserInput"),
+            "/jobs": collection("job", "Job", "Jobs"),
+            "/jobs/{id}": item("job", "Named", "NamedInput"),
+            "/organizations": collection("organization", "Organization", "Organizations"),
+            "/organizations/{id}": item("organization", "Named", "NamedInput"),
+            "/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "operationId": "openapi",
+                    "responses": { "200": { "description": "The OpenAPI document" } },
+                },
+            },
+        },
+        "components": {
+            "schemas": {
+                "User": {
+                    "type": "object",
+                    "required": ["id", "name", "job_id", "organization_id", "email", "phone", "version"],
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "job_id": { "type": "integer", "format": "int64" },
+                        "organization_id": { "type": "integer", "format": "int64" },
+                        "email": { "type": "string", "description": "Empty when not set" },
+                        "phone": { "type": "string", "description": "Empty when not set" },
+                        "version": version(),
+                    },
+                },
+                "UserInput": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["name", "job_id", "organization_id"],
+                    "properties": {
+                        "name": { "type": "string", "minLength": 3, "maxLength": 50 },
+                        "job_id": { "type": "integer", "format": "int64" },
+                        "organization_id": { "type": "integer", "format": "int64" },
+                        "email": { "type": "string", "default": "" },
+                        "phone": { "type": "string", "default": "" },
+                        "version": input_version(),
+                    },
+                },
+                "Named": {
+                    "type": "object",
+                    "description": "A job or an organization",
+                    "required": ["id", "name", "version"],
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "version": version(),
+                    },
+                },
+                "NamedInput": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "version": input_version(),
+                    },
+                },
+                "UserList": list("User", true),
+                "NamedList": list("Named", false),
+                "Error": {
+                    "type": "object",
+                    "required": ["error"],
+                    "properties": {
+                        "error": {
+                            "type": "object",
+                            "required": ["status", "code", "message"],
+                            "properties": {
+                                "status": { "type": "integer" },
+                                "code": {
+                                    "type": "string",
+                                    "enum": [
+                                        "not_found", "method_not_allowed", "invalid_query",
+                                        "invalid_body", "validation_failed", "job_not_found",
+                                        "organization_not_found", "conflict",
+                                        "constraint_violation", "read_only", "database_error",
+                                    ],
+                                },
+                                "message": { "type": "string" },
+                                "fields": {
+                                    "type": "object",
+                                    "description": "Validation messages by field name",
+                                    "additionalProperties": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+fn schema(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn query(name: &str, kind: &str, description: &str) -> Value {
+    json!({ "name": name, "in": "query", "description": description, "schema": { "type": kind } })
+}
+
+fn body(name: &str) -> Value {
+    json!({ "required": true, "content": { "application/json": { "schema": schema(name) } } })
+}
+
+fn ok(description: &str, name: &str) -> Value {
+    json!({ "description": description, "content": { "application/json": { "schema": schema(name) } } })
+}
+
+fn error(description: &str) -> Value {
+    ok(description, "Error")
+}
+
+fn version() -> Value {
+    json!({ "type": "integer", "format": "int64", "description": "Goes up with every change" })
+}
+
+fn input_version() -> Value {
+    json!({
+        "type": "integer",
+        "format": "int64",
+        "description": "The version the change is based on. A stale one fails with conflict; \
+            without it the change applies to the current version.",
+    })
+}
+
+fn list(item: &str, paged: bool) -> Value {
+    let mut list = json!({
+        "type": "object",
+        "required": ["items", "total"],
+        "properties": {
+            "items": { "type": "array", "items": schema(item) },
+            "total": { "type": "integer", "description": "Matches across all pages" },
+        },
+    });
+    if paged {
+        list["properties"]["next_cursor"] =
+            json!({ "type": "string", "description": "Absent on the last page" });
+    }
+    list
+}
+
+/// The list and create operations of jobs or organizations.
+fn collection(entity: &str, title: &str, plural: &str) -> Value {
+    json!({
+        "get": {
+            "summary": format!("List {}, by name", plural.to_lowercase()),
+            "operationId": format!("list{}", plural),
+            "parameters": [query("q", "string", "Only those matching these words as prefixes")],
+            "responses": { "200": ok(&format!("All matching {}", plural.to_lowercase()), "NamedList") },
+        },
+        "post": {
+            "summary": format!("Create a {}", entity),
+            "operationId": format!("create{}", title),
+            "requestBody": body("NamedInput"),
+            "responses": {
+                "201": ok(&format!("The created {}", entity), "Named"),
+                "400": error("Invalid body"),
+                "422": error("Validation failed"),
+            },
+        },
+    })
+}
+
+/// The read, replace and delete operations of one entity.
+fn item(entity: &str, schema_name: &str, input: &str) -> Value {
+    let title = format!("{}{}", entity[..1].to_uppercase(), &entity[1..]);
+    json!({
+        "parameters": [{
+            "name": "id", "in": "path", "required": true,
+            "schema": { "type": "integer", "format": "int64" },
+        }],
+        "get": {
+            "summary": format!("Get a {}", entity),
+            "operationId": format!("get{}", title),
+            "responses": {
+                "200": ok(&format!("The {}", entity), schema_name),
+                "404": error("Not found"),
+            },
+        },
+        "put": {
+            "summary": format!("Replace a {}", entity),
+            "operationId": format!("update{}", title),
+            "requestBody": body(input),
+            "responses": {
+                "200": ok(&format!("The {} as saved", entity), schema_name),
+                "400": error("Invalid body"),
+                "404": error("Not found"),
+                "409": error("Changed since the given version"),
+                "422": error("Validation failed"),
+            },
+        },
+        "delete": {
+            "summary": format!("Delete a {}", entity),
+            "operationId": format!("delete{}", title),
+            "responses": {
+                "204": { "description": "Deleted" },
+                "404": error("Not found"),
+                "409": error("Still referenced by users"),
+            },
+        },
+    })
+}
@@ -0,0 +1,73 @@
+//! Serves the JSON API on a database file, without opening a window.
+
+use clap::Parser;
+use log::LevelFilter;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use usermgmt_core::infrastructure::{get_database_path, Database};
+use usermgmt_server::api;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Serve users, jobs and organizations as a JSON API")]
+struct Args {
+    /// Database file to serve instead of the default one
+    #[arg(long, env = "USERMGMT_DATABASE", value_name = "PATH")]
+    database: Option<PathBuf>,
+
+    /// Address to listen on
+    #[arg(
+        long,
+        env = "USERMGMT_API_LISTEN",
+        value_name = "ADDR",
+        default_value = "127.0.0.1:8080"
+    )]
+    listen: SocketAddr,
+
+    /// Refuse every change to the database
+    #[arg(long, env = "USERMGMT_READ_ONLY")]
+    read_only: bool,
+
+    /// One of off, error, warn, info, debug or trace
+    #[arg(
+        long,
+        env = "USERMGMT_LOG_LEVEL",
+        value_name = "LEVEL",
+        default_value = "info"
+    )]
+    log_level: LevelFilter,
+}
+
+fn main() {
+    let args = Args::parse();
+    env_logger::Builder::new()
+        .filter_level(args.log_level)
+        .init();
+
+    let database_path = args.database.unwrap_or_else(get_database_path);
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Could not start the runtime: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let result = runtime.block_on(async {
+        let database = Database::open(&database_path.to_string_lossy(), args.read_only)
+            .await
+            .map_err(|e| format!("Could not open {}: {}", database_path.display(), e))?;
+        let listener = tokio::net::TcpListener::bind(args.listen)
+            .await
+            .map_err(|e| format!("Could not listen on {}: {}", args.listen, e))?;
+        api::serve(listener, database.services(), args.read_only)
+            .await
+            .map_err(|e| format!("API server failed: {}", e))
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
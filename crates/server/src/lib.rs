@@ -0,0 +1,4 @@
+pub mod api;
+pub mod scim;
+
+use usermgmt_core::{domain, infrastructure};
@@ -0,0 +1,143 @@
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+
+use crate::domain::services::{JobServiceError, OrganizationServiceError};
+use crate::domain::{CommandError, RepositoryError, UserServiceError};
+
+use super::{ERROR_SCHEMA, SCIM_CONTENT_TYPE};
+
+/// An error response in the shape RFC 7644 section 3.12 describes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScimError {
+    pub status: StatusCode,
+    /// One of the `scimType` keywords, for 400 and 409 responses.
+    pub scim_type: Option<&'static str>,
+    pub detail: String,
+}
+
+impl ScimError {
+    pub fn new(
+        status: StatusCode,
+        scim_type: Option<&'static str>,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            status,
+            scim_type,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn bad_request(scim_type: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, Some(scim_type), detail)
+    }
+
+    pub fn invalid_value(detail: impl Into<String>) -> Self {
+        Self::bad_request("invalidValue", detail)
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, None, detail)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(
+            StatusCode::UNAUTHORIZED,
+            None,
+            "A valid bearer token is required",
+        )
+    }
+}
+
+impl std::fmt::Display for ScimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.status.as_u16(), self.detail)
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        let mut body = json!({
+            "schemas": [ERROR_SCHEMA],
+            "status": self.status.as_u16().to_string(),
+            "detail": self.detail,
+        });
+        if let Some(scim_type) = self.scim_type {
+            body["scimType"] = json!(scim_type);
+        }
+        let mut response = (
+            self.status,
+            [(header::CONTENT_TYPE, SCIM_CONTENT_TYPE)],
+            body.to_string(),
+        )
+            .into_response();
+        if self.status == StatusCode::UNAUTHORIZED {
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                "Bearer".parse().expect("valid header"),
+            );
+        }
+        response
+    }
+}
+
+impl From<RepositoryError> for ScimError {
+    fn from(error: RepositoryError) -> Self {
+        match error {
+            RepositoryError::NotFound => Self::not_found("Resource not found"),
+            RepositoryError::Conflict => Self::new(
+                StatusCode::PRECONDITION_FAILED,
+                None,
+                "The resource was modified by someone else",
+            ),
+            RepositoryError::ConstraintViolation(detail) => {
+                Self::new(StatusCode::CONFLICT, Some("uniqueness"), detail)
+            }
+            RepositoryError::DatabaseError(detail) => {
+                log::error!("SCIM request failed: {}", detail);
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, None, "Database error")
+            }
+        }
+    }
+}
+
+impl From<UserServiceError> for ScimError {
+    fn from(error: UserServiceError) -> Self {
+        match error {
+            UserServiceError::RepositoryError(error) => error.into(),
+            UserServiceError::UserNotFound => Self::not_found(error.to_string()),
+            _ => Self::invalid_value(error.to_string()),
+        }
+    }
+}
+
+impl From<JobServiceError> for ScimError {
+    fn from(error: JobServiceError) -> Self {
+        match error {
+            JobServiceError::RepositoryError(error) => error.into(),
+            _ => Self::invalid_value(error.to_string()),
+        }
+    }
+}
+
+impl From<OrganizationServiceError> for ScimError {
+    fn from(error: OrganizationServiceError) -> Self {
+        match error {
+            OrganizationServiceError::RepositoryError(error) => error.into(),
+            _ => Self::invalid_value(error.to_string()),
+        }
+    }
+}
+
+impl From<CommandError> for ScimError {
+    fn from(error: CommandError) -> Self {
+        match error {
+            CommandError::NotFound(entity) => Self::not_found(format!("{} not found", entity)),
+            CommandError::RepositoryError(error) => error.into(),
+            CommandError::User(error) => error.into(),
+            CommandError::Job(error) => error.into(),
+            CommandError::Organization(error) => error.into(),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! SCIM filter expressions (RFC 7644 section 3.4.2.2), evaluated against
+//! the JSON form of a resource.
+
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+use super::ScimError;
+
+/// An attribute named in a filter or a PATCH path, such as `name.formatted`
+/// or `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:organization`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributePath {
+    /// An extension schema the attribute lives under, if any. Core schema
+    /// prefixes are dropped, as core attributes sit at the top level.
+    pub schema: Option<String>,
+    pub attribute: String,
+    pub sub_attribute: Option<String>,
+}
+
+impl AttributePath {
+    pub fn parse(text: &str) -> Result<Self, ScimError> {
+        let invalid =
+            || ScimError::bad_request("invalidPath", format!("Invalid attribute '{}'", text));
+        let (schema, path) = if text.to_ascii_lowercase().starts_with("urn:") {
+            let (schema, path) = text.rsplit_once(':').ok_or_else(invalid)?;
+            let schema = (!super::is_core_schema(schema)).then(|| schema.to_string());
+            (schema, path)
+        } else {
+            (None, text)
+        };
+        let (attribute, sub_attribute) = match path.split_once('.') {
+            Some((attribute, sub_attribute)) => (attribute, Some(sub_attribute.to_string())),
+            None => (path, None),
+        };
+        let is_name = |name: &str| {
+            name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '$')
+        };
+        if !is_name(attribute) || !sub_attribute.as_deref().is_none_or(is_name) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            schema,
+            attribute: attribute.to_string(),
+            sub_attribute,
+        })
+    }
+
+    /// The values the path points at in `resource`. Multi-valued attributes
+    /// contribute each of their values.
+    pub fn values<'a>(&self, resource: &'a Value) -> Vec<&'a Value> {
+        let container = match &self.schema {
+            Some(schema) => get(resource, schema),
+            None => Some(resource),
+        };
+        let Some(value) = container.and_then(|container| get(container, &self.attribute)) else {
+            return Vec::new();
+        };
+        let values: Vec<&Value> = match value {
+            Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        match &self.sub_attribute {
+            Some(sub_attribute) => values
+                .into_iter()
+                .filter_map(|value| get(value, sub_attribute))
+                .flat_map(|value| match value {
+                    Value::Array(items) => items.iter().collect(),
+                    value => vec![value],
+                })
+                .collect(),
+            None => values,
+        }
+    }
+}
+
+/// Looks up an object member, ignoring case as SCIM attribute names do.
+pub fn get<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .as_object()
+        .and_then(|object| key_of(object, name).and_then(|key| object.get(&key)))
+}
+
+/// The actual spelling of `name` among the keys of `object`.
+pub fn key_of(object: &Map<String, Value>, name: &str) -> Option<String> {
+    object
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Co,
+    Sw,
+    Ew,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Present(AttributePath),
+    Compare(AttributePath, Operator, Value),
+    /// `emails[type eq "work"]`: some value of the attribute matches.
+    ValuePath(AttributePath, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Self, ScimError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let filter = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(invalid_filter(format!(
+                "unexpected '{}'",
+                parser.tokens[parser.position]
+            )));
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, resource: &Value) -> bool {
+        match self {
+            Filter::Present(path) => path.values(resource).into_iter().any(|value| match value {
+                Value::Null => false,
+                Value::String(text) => !text.is_empty(),
+                Value::Array(items) => !items.is_empty(),
+                Value::Object(members) => !members.is_empty(),
+                _ => true,
+            }),
+            Filter::Compare(path, operator, expected) => path
+                .values(resource)
+                .into_iter()
+                .any(|value| compare(value, *operator, expected)),
+            Filter::ValuePath(path, filter) => path
+                .values(resource)
+                .into_iter()
+                .any(|value| filter.matches(value)),
+            Filter::And(left, right) => left.matches(resource) && right.matches(resource),
+            Filter::Or(left, right) => left.matches(resource) || right.matches(resource),
+            Filter::Not(filter) => !filter.matches(resource),
+        }
+    }
+}
+
+fn invalid_filter(detail: impl std::fmt::Display) -> ScimError {
+    ScimError::bad_request("invalidFilter", format!("Invalid filter: {}", detail))
+}
+
+/// Strings compare ignoring case, as none of the attributes served here
+/// are case-exact. Ids are served as strings but compare equal to numbers.
+fn compare(actual: &Value, operator: Operator, expected: &Value) -> bool {
+    let text = |value: &Value| match value {
+        Value::String(text) => Some(text.to_lowercase()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    };
+    if expected.is_null() {
+        return match operator {
+            Operator::Eq => actual.is_null(),
+            Operator::Ne => !actual.is_null(),
+            _ => false,
+        };
+    }
+    let (Some(actual), Some(expected)) = (text(actual), text(expected)) else {
+        return false;
+    };
+    let ordering = || match (actual.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(actual), Ok(expected)) => actual.partial_cmp(&expected).unwrap_or(Ordering::Equal),
+        _ => actual.cmp(&expected),
+    };
+    match operator {
+        Operator::Eq => actual == expected,
+        Operator::Ne => actual != expected,
+        Operator::Co => actual.contains(&expected),
+        Operator::Sw => actual.starts_with(&expected),
+        Operator::Ew => actual.ends_with(&expected),
+        Operator::Gt => ordering() == Ordering::Greater,
+        Operator::Ge => ordering() != Ordering::Less,
+        Operator::Lt => ordering() == Ordering::Less,
+        Operator::Le => ordering() != Ordering::Greater,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Word(String),
+    Value(Value),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ScimError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '[' => tokens.push(Token::OpenBracket),
+            ']' => tokens.push(Token::CloseBracket),
+            '"' => {
+                // Find the closing quote, skipping escaped ones, and let the
+                // JSON parser handle the escapes.
+                let mut escaped = false;
+                let mut end = None;
+                for (index, c) in chars.by_ref() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = Some(index);
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+                let end = end.ok_or_else(|| invalid_filter("unterminated string"))?;
+                let value: Value = serde_json::from_str(&text[start..=end])
+                    .map_err(|e| invalid_filter(format!("bad string: {}", e)))?;
+                tokens.push(Token::Value(value));
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(index, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Token::Word(text[start..end].to_string()));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ScimError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(invalid_filter(format!(
+                "expected '{}', found '{}'",
+                expected, token
+            ))),
+            None => Err(invalid_filter(format!("expected '{}'", expected))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter, ScimError> {
+        let mut filter = self.and()?;
+        while self.peek_keyword("or") {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter, ScimError> {
+        let mut filter = self.unary()?;
+        while self.peek_keyword("and") {
+            self.position += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter, ScimError> {
+        if self.peek_keyword("not") {
+            self.position += 1;
+            self.expect(Token::Open)?;
+            let filter = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(Filter::Not(Box::new(filter)));
+        }
+        match self.next() {
+            Some(Token::Open) => {
+                let filter = self.or()?;
+                self.expect(Token::Close)?;
+                Ok(filter)
+            }
+            Some(Token::Word(word)) => {
+                let path = AttributePath::parse(&word).map_err(|e| invalid_filter(e.detail))?;
+                if self.tokens.get(self.position) == Some(&Token::OpenBracket) {
+                    self.position += 1;
+                    let filter = self.or()?;
+                    self.expect(Token::CloseBracket)?;
+                    return Ok(Filter::ValuePath(path, Box::new(filter)));
+                }
+                self.comparison(path)
+            }
+            Some(token) => Err(invalid_filter(format!("unexpected '{}'", token))),
+            None => Err(invalid_filter("unexpected end")),
+        }
+    }
+
+    fn comparison(&mut self, path: AttributePath) -> Result<Filter, ScimError> {
+        let operator = match self.next() {
+            Some(Token::Word(word)) => word.to_ascii_lowercase(),
+            _ => return Err(invalid_filter("expected an operator")),
+        };
+        let operator = match operator.as_str() {
+            "pr" => return Ok(Filter::Present(path)),
+            "eq" => Operator::Eq,
+            "ne" => Operator::Ne,
+            "co" => Operator::Co,
+            "sw" => Operator::Sw,
+            "ew" => Operator::Ew,
+            "gt" => Operator::Gt,
+            "ge" => Operator::Ge,
+            "lt" => Operator::Lt,
+            "le" => Operator::Le,
+            other => return Err(invalid_filter(format!("unknown operator '{}'", other))),
+        };
+        let value = match self.next() {
+            Some(Token::Value(value)) => value,
+            Some(Token::Word(word)) => match word.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                number => serde_json::from_str::<serde_json::Number>(number)
+                    .map(Value::Number)
+                    .map_err(|_| invalid_filter(format!("bad value '{}'", number)))?,
+            },
+            _ => return Err(invalid_filter("expected a value")),
+        };
+        Ok(Filter::Compare(path, operator, value))
+    }
+}
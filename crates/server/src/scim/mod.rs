@@ -0,0 +1,891 @@
+//! An embedded SCIM 2.0 server (RFC 7643 and RFC 7644), so identity
+//! providers can provision users and groups. The app is built with it by its
+//! `scim` feature and starts it with `--scim`, in place of the window.
+//!
+//! Users map to SCIM Users and organizations to Groups, see [`resources`].
+//! Every request needs the bearer token given with `--scim-token`.
+
+mod error;
+mod filter;
+mod patch;
+mod resources;
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::domain::{CommandError, Entity, Services};
+use crate::infrastructure::{Database, MemoryDatabase};
+
+pub use error::ScimError;
+use filter::{AttributePath, Filter};
+use patch::PatchRequest;
+use resources::{save_group, save_user, Directory, GroupInput, UserInput};
+
+pub const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const ENTERPRISE_SCHEMA: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+pub const LIST_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// List responses hold at most this many resources, whatever `count` asks.
+const MAX_RESULTS: usize = 1_000;
+
+#[derive(Debug, Error)]
+pub enum ServeError {
+    #[error("Could not open the database: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Could not load the demo data: {0}")]
+    Demo(#[from] CommandError),
+    #[error("SCIM server failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Where to serve SCIM and the token clients authenticate with.
+#[derive(Debug, Clone)]
+pub struct ScimOptions {
+    pub address: SocketAddr,
+    pub token: String,
+}
+
+struct ScimState {
+    services: Services,
+    token: String,
+    read_only: bool,
+    /// The URL resources are served under, for `meta.location`.
+    base_url: String,
+}
+
+/// Opens the database, or the demo data when there is none, and serves SCIM
+/// until the process is interrupted.
+pub fn run(
+    database_path: Option<&std::path::Path>,
+    read_only: bool,
+    scim: &ScimOptions,
+) -> Result<(), ServeError> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let services = match database_path {
+            Some(path) => Database::open(&path.to_string_lossy(), read_only)
+                .await?
+                .services(),
+            None => MemoryDatabase::with_demo_data().await?.services(),
+        };
+        serve(services, scim, read_only).await
+    })
+}
+
+pub async fn serve(
+    services: Services,
+    scim: &ScimOptions,
+    read_only: bool,
+) -> Result<(), ServeError> {
+    let listener = tokio::net::TcpListener::bind(scim.address).await?;
+    let address = listener.local_addr()?;
+    log::info!("Serving SCIM on {}", base_url(address));
+    axum::serve(
+        listener,
+        router(services, scim.token.clone(), read_only, base_url(address)),
+    )
+    .with_graceful_shutdown(async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await?;
+    Ok(())
+}
+
+fn base_url(address: SocketAddr) -> String {
+    let host = if address.ip().is_unspecified() {
+        "localhost".to_string()
+    } else {
+        address.ip().to_string()
+    };
+    let host = match address {
+        SocketAddr::V6(_) if host != "localhost" => format!("[{}]", host),
+        _ => host,
+    };
+    format!("http://{}:{}/scim/v2", host, address.port())
+}
+
+/// The SCIM endpoints under `/scim/v2`.
+pub fn router(services: Services, token: String, read_only: bool, base_url: String) -> Router {
+    let state = Arc::new(ScimState {
+        services,
+        token,
+        read_only,
+        base_url,
+    });
+    let api = Router::new()
+        .route("/Users", get(list_users).post(create_user))
+        .route(
+            "/Users/{id}",
+            get(get_user)
+                .put(replace_user)
+                .patch(patch_user)
+                .delete(delete_user),
+        )
+        .route("/Groups", get(list_groups).post(create_group))
+        .route(
+            "/Groups/{id}",
+            get(get_group)
+                .put(replace_group)
+                .patch(patch_group)
+                .delete(delete_group),
+        )
+        .route("/ServiceProviderConfig", get(service_provider_config))
+        .route("/ResourceTypes", get(resource_types))
+        .route("/Schemas", get(schemas))
+        .fallback(|| async { ScimError::not_found("No such endpoint") });
+    Router::new()
+        .nest("/scim/v2", api)
+        .fallback(|| async { ScimError::not_found("No such endpoint") })
+        .layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// Checks the bearer token, and refuses writes to a read-only database.
+async fn authorize(
+    State(state): State<Arc<ScimState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ScimError> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+        .map(|(_, token)| token.trim());
+    if !token.is_some_and(|token| same_token(token, &state.token)) {
+        return Err(ScimError::unauthorized());
+    }
+    if state.read_only && request.method() != Method::GET {
+        return Err(ScimError::new(
+            StatusCode::FORBIDDEN,
+            None,
+            "The database is open read-only",
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Compares in time independent of where the tokens differ.
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListParams {
+    filter: Option<String>,
+    start_index: Option<String>,
+    count: Option<String>,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
+    #[serde(flatten)]
+    projection: Projection,
+}
+
+/// The `attributes` and `excludedAttributes` parameters.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Projection {
+    attributes: Option<String>,
+    excluded_attributes: Option<String>,
+}
+
+impl Projection {
+    fn apply(&self, resource: Value) -> Result<Value, ScimError> {
+        let paths = |list: &Option<String>| {
+            list.iter()
+                .flat_map(|list| list.split(','))
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(AttributePath::parse)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let attributes = paths(&self.attributes)?;
+        let excluded = paths(&self.excluded_attributes)?;
+        let mut resource = resource;
+        if !attributes.is_empty() {
+            // `schemas` and `id` are always returned.
+            let mut projected = json!({
+                "schemas": resource["schemas"].clone(),
+                "id": resource["id"].clone(),
+            });
+            for path in &attributes {
+                copy_attribute(&resource, &mut projected, path);
+            }
+            resource = projected;
+        }
+        for path in &excluded {
+            remove_attribute(&mut resource, path);
+        }
+        Ok(resource)
+    }
+}
+
+fn container<'a>(resource: &'a Value, path: &AttributePath) -> Option<&'a Value> {
+    match &path.schema {
+        Some(schema) => filter::get(resource, schema),
+        None => Some(resource),
+    }
+}
+
+fn copy_attribute(resource: &Value, projected: &mut Value, path: &AttributePath) {
+    let Some(value) =
+        container(resource, path).and_then(|container| filter::get(container, &path.attribute))
+    else {
+        return;
+    };
+    let value = match &path.sub_attribute {
+        None => value.clone(),
+        Some(sub_attribute) => {
+            let pick = |item: &Value| {
+                let mut picked = projected_member(projected, path, item);
+                if let Some(sub_value) = filter::get(item, sub_attribute) {
+                    picked[sub_attribute.as_str()] = sub_value.clone();
+                }
+                picked
+            };
+            match value {
+                Value::Array(items) => Value::Array(items.iter().map(pick).collect()),
+                item => pick(item),
+            }
+        }
+    };
+    let target = match &path.schema {
+        Some(schema) => {
+            if !projected[schema.as_str()].is_object() {
+                projected[schema.as_str()] = json!({});
+            }
+            &mut projected[schema.as_str()]
+        }
+        None => projected,
+    };
+    target[path.attribute.as_str()] = value;
+}
+
+/// What an earlier `attributes` entry already picked from a single-valued
+/// complex attribute, so `name.givenName,name.familyName` keeps both.
+fn projected_member(projected: &Value, path: &AttributePath, item: &Value) -> Value {
+    match (
+        item,
+        container(projected, path).and_then(|c| filter::get(c, &path.attribute)),
+    ) {
+        (Value::Object(_), Some(Value::Object(existing))) => Value::Object(existing.clone()),
+        _ => json!({}),
+    }
+}
+
+fn remove_attribute(resource: &mut Value, path: &AttributePath) {
+    if path.attribute.eq_ignore_ascii_case("id") || path.attribute.eq_ignore_ascii_case("schemas") {
+        return;
+    }
+    let container = match &path.schema {
+        Some(schema) => {
+            match filter::key_of(resource.as_object().expect("resources are objects"), schema) {
+                Some(key) => &mut resource[key.as_str()],
+                None => return,
+            }
+        }
+        None => resource,
+    };
+    let Some(object) = container.as_object_mut() else {
+        return;
+    };
+    let Some(key) = filter::key_of(object, &path.attribute) else {
+        return;
+    };
+    match &path.sub_attribute {
+        None => {
+            object.remove(&key);
+        }
+        Some(sub_attribute) => {
+            let remove = |item: &mut Value| {
+                if let Some(item) = item.as_object_mut() {
+                    if let Some(key) = filter::key_of(item, sub_attribute) {
+                        item.remove(&key);
+                    }
+                }
+            };
+            match &mut object[&key] {
+                Value::Array(items) => items.iter_mut().for_each(remove),
+                item => remove(item),
+            }
+        }
+    }
+}
+
+impl ListParams {
+    /// Filters, sorts and pages the resources into a ListResponse.
+    fn respond(&self, resources: Vec<Value>) -> Result<Response, ScimError> {
+        let number = |name: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse::<i64>()
+                        .map_err(|_| ScimError::invalid_value(format!("{} must be a number", name)))
+                })
+                .transpose()
+        };
+        // Both are clamped as RFC 7644 section 3.4.2.4 asks.
+        let start_index = number("startIndex", &self.start_index)?.unwrap_or(1).max(1) as usize;
+        let count = number("count", &self.count)?
+            .map(|count| count.max(0) as usize)
+            .unwrap_or(MAX_RESULTS)
+            .min(MAX_RESULTS);
+
+        let mut resources = match self.filter.as_deref().map(str::trim) {
+            Some(filter) if !filter.is_empty() => {
+                let filter = Filter::parse(filter)?;
+                resources
+                    .into_iter()
+                    .filter(|resource| filter.matches(resource))
+                    .collect()
+            }
+            _ => resources,
+        };
+        if let Some(sort_by) = &self.sort_by {
+            let path = AttributePath::parse(sort_by)?;
+            let key = |resource: &Value| {
+                path.values(resource).first().map(|value| match value {
+                    Value::String(text) => text.to_lowercase(),
+                    value => value.to_string(),
+                })
+            };
+            // Resources without the attribute sort last either way.
+            resources.sort_by_cached_key(|resource| (key(resource).is_none(), key(resource)));
+            if self
+                .sort_order
+                .as_deref()
+                .is_some_and(|order| order.eq_ignore_ascii_case("descending"))
+            {
+                let missing = resources
+                    .iter()
+                    .filter(|resource| key(resource).is_none())
+                    .count();
+                let present = resources.len() - missing;
+                resources[..present].reverse();
+            }
+        }
+
+        let total = resources.len();
+        let page = resources
+            .into_iter()
+            .skip(start_index - 1)
+            .take(count)
+            .map(|resource| self.projection.apply(resource))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(scim_response(
+            StatusCode::OK,
+            json!({
+                "schemas": [LIST_SCHEMA],
+                "totalResults": total,
+                "startIndex": start_index,
+                "itemsPerPage": page.len(),
+                "Resources": page,
+            }),
+        ))
+    }
+}
+
+fn scim_response(status: StatusCode, body: Value) -> Response {
+    let mut response = (
+        status,
+        [(header::CONTENT_TYPE, SCIM_CONTENT_TYPE)],
+        body.to_string(),
+    )
+        .into_response();
+    let version = body["meta"]["version"].as_str().map(str::to_string);
+    let location = body["meta"]["location"].as_str().map(str::to_string);
+    let headers = response.headers_mut();
+    if let Some(version) = version.and_then(|version| version.parse().ok()) {
+        headers.insert(header::ETAG, version);
+    }
+    if status == StatusCode::CREATED {
+        if let Some(location) = location.and_then(|location| location.parse().ok()) {
+            headers.insert(header::LOCATION, location);
+        }
+    }
+    response
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &Bytes) -> Result<T, ScimError> {
+    serde_json::from_slice(body).map_err(|e| {
+        ScimError::bad_request("invalidSyntax", format!("Invalid request body: {}", e))
+    })
+}
+
+/// Resource ids are the database ids; anything else cannot exist.
+fn parse_id(id: &str) -> Result<i64, ScimError> {
+    id.parse()
+        .map_err(|_| ScimError::not_found(format!("Resource {} not found", id)))
+}
+
+/// The version from an `If-Match` header, such as `W/"3"`. `*` matches any
+/// version and counts as no header.
+fn if_match(headers: &HeaderMap) -> Result<Option<i64>, ScimError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let value = value.to_str().unwrap_or_default().trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse()
+        .map(Some)
+        .map_err(|_| {
+            ScimError::new(
+                StatusCode::PRECONDITION_FAILED,
+                None,
+                format!("Unknown version {}", value),
+            )
+        })
+}
+
+fn check_version(expected: Option<i64>, actual: i64) -> Result<(), ScimError> {
+    match expected {
+        Some(expected) if expected != actual => Err(ScimError::new(
+            StatusCode::PRECONDITION_FAILED,
+            None,
+            "The resource was modified by someone else",
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn list_users(
+    State(state): State<Arc<ScimState>>,
+    Query(params): Query<ListParams>,
+) -> Result<Response, ScimError> {
+    let directory = Directory::load(&state.services).await?;
+    let resources = directory
+        .users
+        .iter()
+        .map(|user| directory.user_resource(user, &state.base_url))
+        .collect();
+    params.respond(resources)
+}
+
+async fn get_user(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    Query(projection): Query<Projection>,
+) -> Result<Response, ScimError> {
+    let id = parse_id(&id)?;
+    let directory = Directory::load(&state.services).await?;
+    let user = directory
+        .user(id)
+        .ok_or_else(|| ScimError::not_found(format!("User {} not found", id)))?;
+    let resource = projection.apply(directory.user_resource(user, &state.base_url))?;
+    Ok(scim_response(StatusCode::OK, resource))
+}
+
+async fn user_response(
+    state: &ScimState,
+    status: StatusCode,
+    id: i64,
+) -> Result<Response, ScimError> {
+    let directory = Directory::load(&state.services).await?;
+    let user = directory
+        .user(id)
+        .ok_or_else(|| ScimError::not_found(format!("User {} not found", id)))?;
+    Ok(scim_response(
+        status,
+        directory.user_resource(user, &state.base_url),
+    ))
+}
+
+async fn create_user(
+    State(state): State<Arc<ScimState>>,
+    body: Bytes,
+) -> Result<Response, ScimError> {
+    let input = UserInput::from_resource(&parse_body(&body)?)?;
+    if !input.active {
+        return Err(ScimError::invalid_value(
+            "New users must be active, as inactive users are deleted",
+        ));
+    }
+    let user = save_user(&state.services, None, input, None).await?;
+    log::info!("SCIM created user {} ({})", user.id(), user.name());
+    user_response(&state, StatusCode::CREATED, user.id()).await
+}
+
+async fn existing_user(state: &ScimState, id: &str) -> Result<crate::domain::User, ScimError> {
+    let id = parse_id(id)?;
+    state
+        .services
+        .user
+        .get_user_by_id(id)
+        .await?
+        .ok_or_else(|| ScimError::not_found(format!("User {} not found", id)))
+}
+
+async fn replace_user(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ScimError> {
+    let user = existing_user(&state, &id).await?;
+    let version = if_match(&headers)?;
+    check_version(version, user.version())?;
+    let input = UserInput::from_resource(&parse_body(&body)?)?;
+    if !input.active {
+        return deactivate_user(&state, user).await;
+    }
+    let user = save_user(&state.services, Some(user), input, version).await?;
+    user_response(&state, StatusCode::OK, user.id()).await
+}
+
+async fn patch_user(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ScimError> {
+    let user = existing_user(&state, &id).await?;
+    let version = if_match(&headers)?;
+    check_version(version, user.version())?;
+    let request: PatchRequest = parse_body(&body)?;
+    let mut resource = Directory::load(&state.services)
+        .await?
+        .user_resource(&user, &state.base_url);
+    patch::apply(&mut resource, &request.operations)?;
+    let input = UserInput::from_resource(&resource)?;
+    if !input.active {
+        return deactivate_user(&state, user).await;
+    }
+    let user = save_user(&state.services, Some(user), input, version).await?;
+    user_response(&state, StatusCode::OK, user.id()).await
+}
+
+/// Deprovisions a user a client set `active` to false on. Users have no
+/// disabled state, so the user is deleted and its last state returned,
+/// marked inactive.
+async fn deactivate_user(
+    state: &ScimState,
+    user: crate::domain::User,
+) -> Result<Response, ScimError> {
+    let mut resource = Directory::load(&state.services)
+        .await?
+        .user_resource(&user, &state.base_url);
+    resource["active"] = json!(false);
+    state.services.user.delete_user(user.id()).await?;
+    log::info!(
+        "SCIM deactivated and deleted user {} ({})",
+        user.id(),
+        user.name()
+    );
+    Ok(scim_response(StatusCode::OK, resource))
+}
+
+async fn delete_user(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ScimError> {
+    let user = existing_user(&state, &id).await?;
+    check_version(if_match(&headers)?, user.version())?;
+    state.services.user.delete_user(user.id()).await?;
+    log::info!("SCIM deleted user {} ({})", user.id(), user.name());
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn list_groups(
+    State(state): State<Arc<ScimState>>,
+    Query(params): Query<ListParams>,
+) -> Result<Response, ScimError> {
+    let directory = Directory::load(&state.services).await?;
+    let resources = directory
+        .organizations
+        .iter()
+        .map(|organization| directory.group_resource(organization, &state.base_url))
+        .collect();
+    params.respond(resources)
+}
+
+async fn get_group(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    Query(projection): Query<Projection>,
+) -> Result<Response, ScimError> {
+    let id = parse_id(&id)?;
+    let directory = Directory::load(&state.services).await?;
+    let organization = directory
+        .organization(id)
+        .ok_or_else(|| ScimError::not_found(format!("Group {} not found", id)))?;
+    let resource = projection.apply(directory.group_resource(organization, &state.base_url))?;
+    Ok(scim_response(StatusCode::OK, resource))
+}
+
+async fn group_response(
+    state: &ScimState,
+    status: StatusCode,
+    id: i64,
+) -> Result<Response, ScimError> {
+    let directory = Directory::load(&state.services).await?;
+    let organization = directory
+        .organization(id)
+        .ok_or_else(|| ScimError::not_found(format!("Group {} not found", id)))?;
+    Ok(scim_response(
+        status,
+        directory.group_resource(organization, &state.base_url),
+    ))
+}
+
+async fn existing_group(
+    state: &ScimState,
+    id: &str,
+) -> Result<crate::domain::Organization, ScimError> {
+    let id = parse_id(id)?;
+    state
+        .services
+        .organization
+        .get_organization_by_id(id)
+        .await?
+        .ok_or_else(|| ScimError::not_found(format!("Group {} not found", id)))
+}
+
+async fn create_group(
+    State(state): State<Arc<ScimState>>,
+    body: Bytes,
+) -> Result<Response, ScimError> {
+    let input = GroupInput::from_resource(&parse_body(&body)?)?;
+    let organization = save_group(&state.services, None, input, None).await?;
+    log::info!(
+        "SCIM created group {} ({})",
+        organization.id(),
+        organization.name()
+    );
+    group_response(&state, StatusCode::CREATED, organization.id()).await
+}
+
+async fn replace_group(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ScimError> {
+    let organization = existing_group(&state, &id).await?;
+    let version = if_match(&headers)?;
+    check_version(version, organization.version())?;
+    let input = GroupInput::from_resource(&parse_body(&body)?)?;
+    let organization = save_group(&state.services, Some(organization), input, version).await?;
+    group_response(&state, StatusCode::OK, organization.id()).await
+}
+
+async fn patch_group(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ScimError> {
+    let organization = existing_group(&state, &id).await?;
+    let version = if_match(&headers)?;
+    check_version(version, organization.version())?;
+    let request: PatchRequest = parse_body(&body)?;
+    let mut resource = Directory::load(&state.services)
+        .await?
+        .group_resource(&organization, &state.base_url);
+    patch::apply(&mut resource, &request.operations)?;
+    // Removing the whole attribute removes every member.
+    if filter::get(&resource, "members").is_none() {
+        resource["members"] = json!([]);
+    }
+    let input = GroupInput::from_resource(&resource)?;
+    let organization = save_group(&state.services, Some(organization), input, version).await?;
+    group_response(&state, StatusCode::OK, organization.id()).await
+}
+
+async fn delete_group(
+    State(state): State<Arc<ScimState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ScimError> {
+    let organization = existing_group(&state, &id).await?;
+    check_version(if_match(&headers)?, organization.version())?;
+    let directory = Directory::load(&state.services).await?;
+    let members = directory.members(organization.id()).len();
+    if members > 0 {
+        return Err(ScimError::new(
+            StatusCode::CONFLICT,
+            None,
+            format!(
+                "'{}' still has {} members, move them to another group first",
+                organization.name(),
+                members
+            ),
+        ));
+    }
+    state
+        .services
+        .organization
+        .delete_organization(organization.id())
+        .await?;
+    log::info!(
+        "SCIM deleted group {} ({})",
+        organization.id(),
+        organization.name()
+    );
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn service_provider_config(State(state): State<Arc<ScimState>>) -> Response {
+    scim_response(
+        StatusCode::OK,
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig"],
+            "patch": { "supported": true },
+            "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+            "filter": { "supported": true, "maxResults": MAX_RESULTS },
+            "changePassword": { "supported": false },
+            "sort": { "supported": true },
+            "etag": { "supported": true },
+            "authenticationSchemes": [{
+                "type": "oauthbearertoken",
+                "name": "Bearer token",
+                "description": "The token given to the server with --scim-token",
+                "primary": true,
+            }],
+            "meta": {
+                "resourceType": "ServiceProviderConfig",
+                "location": format!("{}/ServiceProviderConfig", state.base_url),
+            },
+        }),
+    )
+}
+
+async fn resource_types(State(state): State<Arc<ScimState>>) -> Response {
+    let resource_type = |name: &str, endpoint: &str, schema: &str, extensions: Value| {
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ResourceType"],
+            "id": name,
+            "name": name,
+            "endpoint": endpoint,
+            "schema": schema,
+            "schemaExtensions": extensions,
+            "meta": {
+                "resourceType": "ResourceType",
+                "location": format!("{}/ResourceTypes/{}", state.base_url, name),
+            },
+        })
+    };
+    list_response(vec![
+        resource_type(
+            "User",
+            "/Users",
+            USER_SCHEMA,
+            json!([{ "schema": ENTERPRISE_SCHEMA, "required": false }]),
+        ),
+        resource_type("Group", "/Groups", GROUP_SCHEMA, json!([])),
+    ])
+}
+
+async fn schemas(State(state): State<Arc<ScimState>>) -> Response {
+    let attribute = |name: &str,
+                     kind: &str,
+                     multi_valued: bool,
+                     required: bool,
+                     mutability: &str| {
+        json!({
+            "name": name,
+            "type": kind,
+            "multiValued": multi_valued,
+            "required": required,
+            "caseExact": false,
+            "mutability": mutability,
+            "returned": "default",
+            "uniqueness": if name == "userName" || name == "displayName" { "server" } else { "none" },
+        })
+    };
+    let schema = |id: &str, name: &str, attributes: Vec<Value>| {
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Schema"],
+            "id": id,
+            "name": name,
+            "attributes": attributes,
+            "meta": {
+                "resourceType": "Schema",
+                "location": format!("{}/Schemas/{}", state.base_url, id),
+            },
+        })
+    };
+    list_response(vec![
+        schema(
+            USER_SCHEMA,
+            "User",
+            vec![
+                attribute("userName", "string", false, true, "readWrite"),
+                attribute("displayName", "string", false, false, "readOnly"),
+                attribute("name", "complex", false, false, "readOnly"),
+                attribute("title", "string", false, true, "readWrite"),
+                attribute("active", "boolean", false, false, "readWrite"),
+                attribute("emails", "complex", true, false, "readWrite"),
+                attribute("phoneNumbers", "complex", true, false, "readWrite"),
+                attribute("groups", "complex", true, false, "readOnly"),
+            ],
+        ),
+        schema(
+            ENTERPRISE_SCHEMA,
+            "EnterpriseUser",
+            vec![attribute(
+                "organization",
+                "string",
+                false,
+                true,
+                "readWrite",
+            )],
+        ),
+        schema(
+            GROUP_SCHEMA,
+            "Group",
+            vec![
+                attribute("displayName", "string", false, true, "readWrite"),
+                attribute("members", "complex", true, false, "readWrite"),
+            ],
+        ),
+    ])
+}
+
+fn list_response(resources: Vec<Value>) -> Response {
+    scim_response(
+        StatusCode::OK,
+        json!({
+            "schemas": [LIST_SCHEMA],
+            "totalResults": resources.len(),
+            "startIndex": 1,
+            "itemsPerPage": resources.len(),
+            "Resources": resources,
+        }),
+    )
+}
+
+/// Whether a schema URN names a core schema, whose attributes sit at the top
+/// level of a resource.
+fn is_core_schema(schema: &str) -> bool {
+    schema.eq_ignore_ascii_case(USER_SCHEMA) || schema.eq_ignore_ascii_case(GROUP_SCHEMA)
+}
@@ -0,0 +1,309 @@
+//! PATCH operations (RFC 7644 section 3.5.2), applied to the JSON form of a
+//! resource. The patched resource is then saved like a PUT, so the rules
+//! about what can change live in one place.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::filter::{key_of, AttributePath, Filter};
+use super::ScimError;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchRequest {
+    #[serde(rename = "Operations", alias = "operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchOperation {
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+/// A PATCH path: `emails`, `name.formatted`, `emails[type eq "work"]` or
+/// `emails[type eq "work"].value`.
+#[derive(Debug)]
+struct PatchPath {
+    attribute: AttributePath,
+    filter: Option<Filter>,
+}
+
+impl PatchPath {
+    fn parse(text: &str) -> Result<Self, ScimError> {
+        let invalid = || ScimError::bad_request("invalidPath", format!("Invalid path '{}'", text));
+        let Some(open) = text.find('[') else {
+            return Ok(Self {
+                attribute: AttributePath::parse(text)?,
+                filter: None,
+            });
+        };
+        let close = text
+            .rfind(']')
+            .filter(|&close| close > open)
+            .ok_or_else(invalid)?;
+        let mut attribute = AttributePath::parse(&text[..open])?;
+        if attribute.sub_attribute.is_some() {
+            return Err(invalid());
+        }
+        let filter = Filter::parse(&text[open + 1..close])
+            .map_err(|e| ScimError::bad_request("invalidPath", e.detail))?;
+        let rest = &text[close + 1..];
+        if !rest.is_empty() {
+            let sub_attribute = rest.strip_prefix('.').ok_or_else(invalid)?;
+            let sub_path = AttributePath::parse(sub_attribute)?;
+            if sub_path.sub_attribute.is_some() || sub_path.schema.is_some() {
+                return Err(invalid());
+            }
+            attribute.sub_attribute = Some(sub_path.attribute);
+        }
+        Ok(Self {
+            attribute,
+            filter: Some(filter),
+        })
+    }
+}
+
+/// Applies the operations in order. Either all of them apply or the
+/// resource should be thrown away, as the first error stops the rest.
+pub fn apply(resource: &mut Value, operations: &[PatchOperation]) -> Result<(), ScimError> {
+    for operation in operations {
+        apply_operation(resource, operation)?;
+    }
+    Ok(())
+}
+
+fn apply_operation(resource: &mut Value, operation: &PatchOperation) -> Result<(), ScimError> {
+    let op = operation.op.to_ascii_lowercase();
+    let append = match op.as_str() {
+        "add" => true,
+        "replace" => false,
+        "remove" => {
+            let path = operation.path.as_deref().ok_or_else(|| {
+                ScimError::bad_request("noTarget", "A remove operation needs a path")
+            })?;
+            return remove(resource, &PatchPath::parse(path)?, operation.value.as_ref());
+        }
+        other => {
+            return Err(ScimError::bad_request(
+                "invalidSyntax",
+                format!("Unknown operation '{}'", other),
+            ))
+        }
+    };
+    let value = operation
+        .value
+        .clone()
+        .ok_or_else(|| ScimError::invalid_value(format!("The {} operation needs a value", op)))?;
+
+    let Some(path) = operation.path.as_deref() else {
+        // Without a path the value holds the attributes to set, with
+        // extension attributes nested under their schema.
+        let Value::Object(members) = value else {
+            return Err(ScimError::invalid_value(
+                "Without a path the value must be an object",
+            ));
+        };
+        let object = as_object(resource)?;
+        for (name, value) in members {
+            match value {
+                Value::Object(extension) if name.to_ascii_lowercase().starts_with("urn:") => {
+                    let container = child_object(object, &name)?;
+                    for (name, value) in extension {
+                        set(container, &name, value, append);
+                    }
+                }
+                value => set(object, &name, value, append),
+            }
+        }
+        return Ok(());
+    };
+
+    let path = PatchPath::parse(path)?;
+    let container =
+        container(resource, &path.attribute, true)?.expect("containers are created when asked to");
+    let attribute = &path.attribute.attribute;
+    match (&path.filter, &path.attribute.sub_attribute) {
+        (None, None) => set(container, attribute, value, append),
+        (None, Some(sub_attribute)) => {
+            let target = child_object(container, attribute)?;
+            set(target, sub_attribute, value, append);
+        }
+        (Some(filter), sub_attribute) => {
+            let targets = matching(container, attribute, filter);
+            if targets.is_empty() {
+                return Err(ScimError::bad_request(
+                    "noTarget",
+                    format!("No value of '{}' matches the filter", attribute),
+                ));
+            }
+            for target in targets {
+                match (sub_attribute, &value) {
+                    (Some(sub_attribute), value) => {
+                        set(as_object(target)?, sub_attribute, value.clone(), append)
+                    }
+                    (None, Value::Object(members)) => {
+                        let target = as_object(target)?;
+                        for (name, value) in members {
+                            set(target, name, value.clone(), false);
+                        }
+                    }
+                    (None, value) => *target = value.clone(),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remove(resource: &mut Value, path: &PatchPath, value: Option<&Value>) -> Result<(), ScimError> {
+    // Nothing to do when the attribute is not there.
+    let Some(container) = container(resource, &path.attribute, false)? else {
+        return Ok(());
+    };
+    let attribute = &path.attribute.attribute;
+    let Some(key) = key_of(container, attribute) else {
+        return Ok(());
+    };
+    match (&path.filter, &path.attribute.sub_attribute) {
+        (None, None) => match (container.get_mut(&key), value) {
+            // Some clients name the values to remove in the value rather
+            // than in a filter: `{"path": "members", "value": [{"value": "7"}]}`.
+            (Some(Value::Array(items)), Some(Value::Array(removed))) => {
+                let removed: Vec<&Value> = removed
+                    .iter()
+                    .map(|item| super::filter::get(item, "value").unwrap_or(item))
+                    .collect();
+                items.retain(|item| {
+                    let value = super::filter::get(item, "value").unwrap_or(item);
+                    !removed.iter().any(|removed| same_value(removed, value))
+                });
+            }
+            _ => {
+                container.remove(&key);
+            }
+        },
+        (None, Some(sub_attribute)) => match container.get_mut(&key) {
+            Some(Value::Object(object)) => remove_key(object, sub_attribute),
+            Some(Value::Array(items)) => {
+                for item in items.iter_mut() {
+                    if let Value::Object(object) = item {
+                        remove_key(object, sub_attribute);
+                    }
+                }
+            }
+            _ => {}
+        },
+        (Some(filter), None) => {
+            if let Some(Value::Array(items)) = container.get_mut(&key) {
+                items.retain(|item| !filter.matches(item));
+            }
+        }
+        (Some(filter), Some(sub_attribute)) => {
+            for target in matching(container, attribute, filter) {
+                if let Value::Object(object) = target {
+                    remove_key(object, sub_attribute);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ids are strings in SCIM, but some clients send them as numbers.
+fn same_value(a: &Value, b: &Value) -> bool {
+    let text = |value: &Value| match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    text(a) == text(b)
+}
+
+fn as_object(value: &mut Value) -> Result<&mut Map<String, Value>, ScimError> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| ScimError::bad_request("invalidPath", "The path does not name an object"))
+}
+
+/// The object holding the attribute: the resource itself, or the object of
+/// an extension schema.
+fn container<'a>(
+    resource: &'a mut Value,
+    path: &AttributePath,
+    create: bool,
+) -> Result<Option<&'a mut Map<String, Value>>, ScimError> {
+    let object = as_object(resource)?;
+    match &path.schema {
+        None => Ok(Some(object)),
+        Some(schema) if create => child_object(object, schema).map(Some),
+        Some(schema) => match key_of(object, schema) {
+            Some(key) => Ok(object.get_mut(&key).and_then(Value::as_object_mut)),
+            None => Ok(None),
+        },
+    }
+}
+
+/// The object under `name`, created when missing.
+fn child_object<'a>(
+    object: &'a mut Map<String, Value>,
+    name: &str,
+) -> Result<&'a mut Map<String, Value>, ScimError> {
+    let key = key_of(object, name).unwrap_or_else(|| name.to_string());
+    let child = object
+        .entry(key)
+        .or_insert_with(|| Value::Object(Map::new()));
+    if child.is_null() {
+        *child = Value::Object(Map::new());
+    }
+    child.as_object_mut().ok_or_else(|| {
+        ScimError::bad_request(
+            "invalidPath",
+            format!("'{}' is not a complex attribute", name),
+        )
+    })
+}
+
+/// The values of a multi-valued attribute matching `filter`.
+fn matching<'a>(
+    container: &'a mut Map<String, Value>,
+    attribute: &str,
+    filter: &Filter,
+) -> Vec<&'a mut Value> {
+    match key_of(container, attribute).and_then(|key| container.get_mut(&key)) {
+        Some(Value::Array(items)) => items
+            .iter_mut()
+            .filter(|item| filter.matches(item))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Sets an attribute. With `append`, values are added to a multi-valued
+/// attribute instead of replacing it, skipping ones already there.
+fn set(object: &mut Map<String, Value>, name: &str, value: Value, append: bool) {
+    let key = key_of(object, name).unwrap_or_else(|| name.to_string());
+    match (object.get_mut(&key), value) {
+        (Some(Value::Array(items)), value) if append => {
+            let added = match value {
+                Value::Array(added) => added,
+                value => vec![value],
+            };
+            for value in added {
+                if !items.contains(&value) {
+                    items.push(value);
+                }
+            }
+        }
+        (_, value) => {
+            object.insert(key, value);
+        }
+    }
+}
+
+fn remove_key(object: &mut Map<String, Value>, name: &str) {
+    if let Some(key) = key_of(object, name) {
+        object.remove(&key);
+    }
+}
@@ -0,0 +1,450 @@
+//! Maps users and organizations to SCIM User and Group resources and back.
+//!
+//! A user's job is its `title` and its organization the enterprise
+//! extension's `organization`. Every organization is also a Group whose
+//! members are its users, so moving a user between groups changes their
+//! organization. A user always belongs to exactly one organization.
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::{Entity, Job, Organization, Services, User};
+
+use super::filter::get;
+use super::{ScimError, ENTERPRISE_SCHEMA, GROUP_SCHEMA, USER_SCHEMA};
+
+/// Everything needed to build resources, read once per request.
+pub struct Directory {
+    pub users: Vec<User>,
+    pub jobs: HashMap<i64, String>,
+    pub organizations: Vec<Organization>,
+}
+
+impl Directory {
+    pub async fn load(services: &Services) -> Result<Self, ScimError> {
+        let mut users = services.user.get_all_users().await?;
+        users.sort_by_key(|user| user.id());
+        let mut organizations = services.organization.get_all_organizations().await?;
+        organizations.sort_by_key(|organization| organization.id());
+        let jobs = services
+            .job
+            .get_all_jobs()
+            .await?
+            .into_iter()
+            .map(|job| (job.id(), job.name().to_string()))
+            .collect();
+        Ok(Self {
+            users,
+            jobs,
+            organizations,
+        })
+    }
+
+    pub fn user(&self, id: i64) -> Option<&User> {
+        self.users.iter().find(|user| user.id() == id)
+    }
+
+    pub fn organization(&self, id: i64) -> Option<&Organization> {
+        self.organizations
+            .iter()
+            .find(|organization| organization.id() == id)
+    }
+
+    pub fn user_resource(&self, user: &User, base_url: &str) -> Value {
+        let organization = self.organization(user.organization_id());
+        let mut resource = json!({
+            "schemas": [USER_SCHEMA, ENTERPRISE_SCHEMA],
+            "id": user.id().to_string(),
+            "userName": user.name(),
+            "displayName": user.name(),
+            "name": { "formatted": user.name() },
+            "active": true,
+            "meta": meta("User", &format!("{}/Users/{}", base_url, user.id()), user.version()),
+        });
+        if let Some(job) = self.jobs.get(&user.job_id()) {
+            resource["title"] = json!(job);
+        }
+        if !user.email().is_empty() {
+            resource["emails"] =
+                json!([{ "value": user.email(), "type": "work", "primary": true }]);
+        }
+        if !user.phone().is_empty() {
+            resource["phoneNumbers"] = json!([{ "value": user.phone(), "type": "work" }]);
+        }
+        if let Some(organization) = organization {
+            resource[ENTERPRISE_SCHEMA] = json!({ "organization": organization.name() });
+            resource["groups"] = json!([{
+                "value": organization.id().to_string(),
+                "display": organization.name(),
+                "$ref": format!("{}/Groups/{}", base_url, organization.id()),
+            }]);
+        }
+        resource
+    }
+
+    pub fn group_resource(&self, organization: &Organization, base_url: &str) -> Value {
+        let members: Vec<Value> = self
+            .users
+            .iter()
+            .filter(|user| user.organization_id() == organization.id())
+            .map(|user| {
+                json!({
+                    "value": user.id().to_string(),
+                    "display": user.name(),
+                    "type": "User",
+                    "$ref": format!("{}/Users/{}", base_url, user.id()),
+                })
+            })
+            .collect();
+        json!({
+            "schemas": [GROUP_SCHEMA],
+            "id": organization.id().to_string(),
+            "displayName": organization.name(),
+            "members": members,
+            "meta": meta(
+                "Group",
+                &format!("{}/Groups/{}", base_url, organization.id()),
+                organization.version(),
+            ),
+        })
+    }
+
+    pub fn members(&self, organization_id: i64) -> HashSet<i64> {
+        self.users
+            .iter()
+            .filter(|user| user.organization_id() == organization_id)
+            .map(|user| user.id())
+            .collect()
+    }
+}
+
+fn meta(resource_type: &str, location: &str, version: i64) -> Value {
+    json!({
+        "resourceType": resource_type,
+        "location": location,
+        "version": etag(version),
+    })
+}
+
+pub fn etag(version: i64) -> String {
+    format!("W/\"{}\"", version)
+}
+
+/// The writable attributes of a User resource.
+#[derive(Debug)]
+pub struct UserInput {
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    /// The job name, created when no job has it.
+    pub title: Option<String>,
+    /// The organization name, created when no organization has it.
+    pub organization: Option<String>,
+    /// Users have no disabled state, so an inactive user is deleted.
+    pub active: bool,
+}
+
+impl UserInput {
+    pub fn from_resource(resource: &Value) -> Result<Self, ScimError> {
+        let text = |value: Option<&Value>| {
+            value
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+        let name = text(get(resource, "userName"))
+            .ok_or_else(|| ScimError::invalid_value("userName is required"))?;
+
+        // Some clients send booleans as strings.
+        let active = match get(resource, "active") {
+            None | Some(Value::Null) => true,
+            Some(Value::Bool(active)) => *active,
+            Some(Value::String(active)) if active.eq_ignore_ascii_case("true") => true,
+            Some(Value::String(active)) if active.eq_ignore_ascii_case("false") => false,
+            Some(_) => return Err(ScimError::invalid_value("active must be a boolean")),
+        };
+
+        let organization = get(resource, ENTERPRISE_SCHEMA)
+            .and_then(|extension| text(get(extension, "organization")));
+        Ok(Self {
+            name,
+            email: primary_value(resource, "emails"),
+            phone: primary_value(resource, "phoneNumbers"),
+            title: text(get(resource, "title")),
+            organization,
+            active,
+        })
+    }
+}
+
+/// The primary value of a multi-valued attribute, or its first value.
+fn primary_value(resource: &Value, attribute: &str) -> String {
+    let Some(Value::Array(items)) = get(resource, attribute) else {
+        return String::new();
+    };
+    items
+        .iter()
+        .find(|item| get(item, "primary").and_then(Value::as_bool) == Some(true))
+        .or_else(|| items.first())
+        .and_then(|item| get(item, "value"))
+        .and_then(Value::as_str)
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// The writable attributes of a Group resource.
+#[derive(Debug)]
+pub struct GroupInput {
+    pub name: String,
+    /// `None` when the resource has no `members`, so they stay as they are.
+    pub members: Option<Vec<i64>>,
+}
+
+impl GroupInput {
+    pub fn from_resource(resource: &Value) -> Result<Self, ScimError> {
+        let name = get(resource, "displayName")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| ScimError::invalid_value("displayName is required"))?
+            .to_string();
+        let members = match get(resource, "members") {
+            None | Some(Value::Null) => None,
+            Some(Value::Array(members)) => Some(
+                members
+                    .iter()
+                    .map(|member| {
+                        let value = get(member, "value").unwrap_or(member);
+                        match value {
+                            Value::String(id) => id.trim().parse().ok(),
+                            Value::Number(id) => id.as_i64(),
+                            _ => None,
+                        }
+                        .ok_or_else(|| {
+                            ScimError::invalid_value(format!("Invalid member {}", value))
+                        })
+                    })
+                    .collect::<Result<Vec<i64>, ScimError>>()?,
+            ),
+            Some(_) => return Err(ScimError::invalid_value("members must be a list")),
+        };
+        Ok(Self { name, members })
+    }
+}
+
+/// Creates or updates a user in one transaction, creating the job and the
+/// organization it names when needed. `version` is the one the client
+/// expects from `If-Match`, if it sent one.
+pub async fn save_user(
+    services: &Services,
+    existing: Option<User>,
+    input: UserInput,
+    version: Option<i64>,
+) -> Result<User, ScimError> {
+    let (transaction_services, transaction) = services.begin().await?;
+    let result = write_user(&transaction_services, existing, input, version).await;
+    let id = transaction.complete(result).await?;
+
+    services
+        .user
+        .get_user_by_id(id)
+        .await?
+        .ok_or_else(|| ScimError::not_found(format!("User {} not found", id)))
+}
+
+/// Checks the name is free and writes the user, all inside the caller's
+/// transaction so two requests cannot both claim the same name.
+async fn write_user(
+    services: &Services,
+    existing: Option<User>,
+    input: UserInput,
+    version: Option<i64>,
+) -> Result<i64, ScimError> {
+    let users = services.user.get_all_users().await?;
+    let existing_id = existing.as_ref().map(|user| user.id());
+    if users
+        .iter()
+        .any(|user| Some(user.id()) != existing_id && user.name().eq_ignore_ascii_case(&input.name))
+    {
+        return Err(ScimError::new(
+            axum::http::StatusCode::CONFLICT,
+            Some("uniqueness"),
+            format!("A user named '{}' already exists", input.name),
+        ));
+    }
+
+    let job_id = match (&input.title, &existing) {
+        (Some(title), _) => job_named(services, title).await?,
+        (None, Some(user)) => user.job_id(),
+        (None, None) => {
+            return Err(ScimError::invalid_value(
+                "title is required, it names the user's job",
+            ))
+        }
+    };
+    let organization_id = match (&input.organization, &existing) {
+        (Some(name), _) => organization_named(services, name).await?,
+        (None, Some(user)) => user.organization_id(),
+        (None, None) => {
+            return Err(ScimError::invalid_value(format!(
+                "{}:organization is required",
+                ENTERPRISE_SCHEMA
+            )))
+        }
+    };
+
+    let mut user = existing.unwrap_or_default();
+    user.set_name(input.name);
+    user.set_email(input.email);
+    user.set_phone(input.phone);
+    user.set_job_id(job_id);
+    user.set_organization_id(organization_id);
+    if let Some(version) = version {
+        user.set_version(version);
+    }
+    check(&mut user)?;
+    if user.id() == 0 {
+        Ok(services.user.create_user(user).await?.id())
+    } else {
+        let id = user.id();
+        services.user.update_user(user).await?;
+        Ok(id)
+    }
+}
+
+/// Creates or updates an organization and moves the listed members into it.
+/// Members cannot be removed, as every user needs an organization; they
+/// leave a group by being added to another one.
+pub async fn save_group(
+    services: &Services,
+    existing: Option<Organization>,
+    input: GroupInput,
+    version: Option<i64>,
+) -> Result<Organization, ScimError> {
+    let (transaction_services, transaction) = services.begin().await?;
+    let result = write_group(&transaction_services, existing, input, version).await;
+    let id = transaction.complete(result).await?;
+
+    services
+        .organization
+        .get_organization_by_id(id)
+        .await?
+        .ok_or_else(|| ScimError::not_found(format!("Group {} not found", id)))
+}
+
+/// Like `write_user`, checks the name and writes inside one transaction.
+async fn write_group(
+    services: &Services,
+    existing: Option<Organization>,
+    input: GroupInput,
+    version: Option<i64>,
+) -> Result<i64, ScimError> {
+    let organizations = services.organization.get_all_organizations().await?;
+    let existing_id = existing.as_ref().map(|organization| organization.id());
+    if organizations.iter().any(|organization| {
+        Some(organization.id()) != existing_id
+            && organization.name().eq_ignore_ascii_case(&input.name)
+    }) {
+        return Err(ScimError::new(
+            axum::http::StatusCode::CONFLICT,
+            Some("uniqueness"),
+            format!("A group named '{}' already exists", input.name),
+        ));
+    }
+
+    let mut organization = existing.unwrap_or_default();
+    let renamed = organization.name() != input.name;
+    organization.set_name(input.name);
+    if let Some(version) = version {
+        organization.set_version(version);
+    }
+    check(&mut organization)?;
+    if organization.id() == 0 {
+        organization = services
+            .organization
+            .create_organization(organization)
+            .await?;
+    } else if renamed || version.is_some() {
+        services
+            .organization
+            .update_organization(organization.clone())
+            .await?;
+    }
+
+    if let Some(members) = input.members {
+        let members: HashSet<i64> = members.into_iter().collect();
+        let current: Vec<User> = services
+            .user
+            .get_all_users()
+            .await?
+            .into_iter()
+            .filter(|user| user.organization_id() == organization.id())
+            .collect();
+        if let Some(user) = current.iter().find(|user| !members.contains(&user.id())) {
+            return Err(ScimError::bad_request(
+                "mutability",
+                format!(
+                    "{} cannot be removed from '{}', add them to another group instead",
+                    user.name(),
+                    organization.name()
+                ),
+            ));
+        }
+        let mut members: Vec<i64> = members.into_iter().collect();
+        members.sort_unstable();
+        for id in members {
+            let mut user =
+                services.user.get_user_by_id(id).await?.ok_or_else(|| {
+                    ScimError::invalid_value(format!("User {} does not exist", id))
+                })?;
+            if user.organization_id() != organization.id() {
+                user.set_organization_id(organization.id());
+                services.user.update_user(user).await?;
+            }
+        }
+    }
+    Ok(organization.id())
+}
+
+/// Validates an entity, reporting its messages rather than a bare
+/// "validation failed".
+fn check<T: Entity>(entity: &mut T) -> Result<(), ScimError> {
+    entity.validate().map_err(|errors| {
+        let mut messages: Vec<&str> = errors.values().copied().collect();
+        messages.sort_unstable();
+        ScimError::invalid_value(messages.join("; "))
+    })
+}
+
+async fn job_named(services: &Services, name: &str) -> Result<i64, ScimError> {
+    let jobs = services.job.get_all_jobs().await?;
+    if let Some(job) = jobs
+        .iter()
+        .find(|job| job.name().eq_ignore_ascii_case(name))
+    {
+        return Ok(job.id());
+    }
+    let mut job = Job::new();
+    job.set_name(name.to_string());
+    check(&mut job)?;
+    Ok(services.job.create_job(job).await?.id())
+}
+
+async fn organization_named(services: &Services, name: &str) -> Result<i64, ScimError> {
+    let organizations = services.organization.get_all_organizations().await?;
+    if let Some(organization) = organizations
+        .iter()
+        .find(|organization| organization.name().eq_ignore_ascii_case(name))
+    {
+        return Ok(organization.id());
+    }
+    let mut organization = Organization::new();
+    organization.set_name(name.to_string());
+    check(&mut organization)?;
+    Ok(services
+        .organization
+        .create_organization(organization)
+        .await?
+        .id())
+}
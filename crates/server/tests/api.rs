@@ -0,0 +1,505 @@
+//! Drives the JSON API against a temporary SQLite database.
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use usermgmt_core::infrastructure::Database;
+use usermgmt_server::api;
+
+struct TestApi {
+    router: Router,
+    // Removed, with the database, when the test ends.
+    _dir: TempDir,
+}
+
+struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Value,
+}
+
+impl TestApi {
+    async fn new() -> Self {
+        Self::open(false).await
+    }
+
+    async fn open(read_only: bool) -> Self {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let path = dir.path().join("api.db");
+        // Read-only databases are not migrated, so create the file first.
+        let database = Database::open(&path.to_string_lossy(), false)
+            .await
+            .expect("database opens");
+        let database = if read_only {
+            database.pool.close().await;
+            Database::open(&path.to_string_lossy(), true)
+                .await
+                .expect("database opens read-only")
+        } else {
+            database
+        };
+        Self {
+            router: api::router(database.services(), read_only),
+            _dir: dir,
+        }
+    }
+
+    async fn send(&self, method: Method, uri: &str, body: Option<Value>) -> TestResponse {
+        let request = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("valid request");
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("router answers");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body reads")
+            .to_bytes();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).expect("JSON body")
+        };
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Method::GET, uri, None).await
+    }
+
+    async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::POST, uri, Some(body)).await
+    }
+
+    async fn put(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::PUT, uri, Some(body)).await
+    }
+
+    async fn delete(&self, uri: &str) -> TestResponse {
+        self.send(Method::DELETE, uri, None).await
+    }
+
+    /// Creates a job and an organization and returns their ids.
+    async fn references(&self) -> (i64, i64) {
+        let job = self.post("/api/jobs", json!({ "name": "Developer" })).await;
+        let organization = self
+            .post("/api/organizations", json!({ "name": "Engineering" }))
+            .await;
+        (id(&job.body), id(&organization.body))
+    }
+}
+
+fn id(body: &Value) -> i64 {
+    body["id"].as_i64().expect("an id")
+}
+
+fn assert_error(response: &TestResponse, status: StatusCode, code: &str) {
+    assert_eq!(response.status, status, "{}", response.body);
+    assert_eq!(response.body["error"]["status"], status.as_u16());
+    assert_eq!(response.body["error"]["code"], code);
+    assert!(response.body["error"]["message"].is_string());
+}
+
+#[tokio::test]
+async fn users_can_be_created_read_updated_and_deleted() {
+    let api = TestApi::new().await;
+    let (job_id, organization_id) = api.references().await;
+
+    let created = api
+        .post(
+            "/api/users",
+            json!({
+                "name": "Ada Lovelace",
+                "job_id": job_id,
+                "organization_id": organization_id,
+                "email": "ada@example.com",
+            }),
+        )
+        .await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    let user_id = id(&created.body);
+    assert_eq!(
+        created.headers[header::LOCATION],
+        format!("/api/users/{}", user_id).as_str()
+    );
+    assert_eq!(created.body["email"], "ada@example.com");
+    assert_eq!(created.body["phone"], "");
+    assert_eq!(created.body["version"], 1);
+
+    let read = api.get(&format!("/api/users/{}", user_id)).await;
+    assert_eq!(read.status, StatusCode::OK);
+    assert_eq!(read.body, created.body);
+
+    let updated = api
+        .put(
+            &format!("/api/users/{}", user_id),
+            json!({
+                "name": "Ada King",
+                "job_id": job_id,
+                "organization_id": organization_id,
+                "phone": "+44 20 7946 0000",
+                "version": 1,
+            }),
+        )
+        .await;
+    assert_eq!(updated.status, StatusCode::OK, "{}", updated.body);
+    assert_eq!(updated.body["name"], "Ada King");
+    assert_eq!(updated.body["email"], "");
+    assert_eq!(updated.body["phone"], "+44 20 7946 0000");
+    assert_eq!(updated.body["version"], 2);
+
+    let deleted = api.delete(&format!("/api/users/{}", user_id)).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+    assert_error(
+        &api.get(&format!("/api/users/{}", user_id)).await,
+        StatusCode::NOT_FOUND,
+        "not_found",
+    );
+    assert_error(
+        &api.delete(&format!("/api/users/{}", user_id)).await,
+        StatusCode::NOT_FOUND,
+        "not_found",
+    );
+}
+
+#[tokio::test]
+async fn a_stale_version_is_a_conflict() {
+    let api = TestApi::new().await;
+    let created = api.post("/api/jobs", json!({ "name": "Tester" })).await;
+    let uri = format!("/api/jobs/{}", id(&created.body));
+
+    let renamed = api
+        .put(&uri, json!({ "name": "QA Engineer", "version": 1 }))
+        .await;
+    assert_eq!(renamed.status, StatusCode::OK);
+    assert_eq!(renamed.body["version"], 2);
+
+    let stale = api
+        .put(&uri, json!({ "name": "Test Engineer", "version": 1 }))
+        .await;
+    assert_error(&stale, StatusCode::CONFLICT, "conflict");
+    assert_eq!(api.get(&uri).await.body["name"], "QA Engineer");
+
+    // Without a version the change applies to the current one.
+    let unversioned = api.put(&uri, json!({ "name": "Test Engineer" })).await;
+    assert_eq!(unversioned.status, StatusCode::OK);
+    assert_eq!(unversioned.body["version"], 3);
+}
+
+#[tokio::test]
+async fn invalid_users_report_their_fields() {
+    let api = TestApi::new().await;
+    let (job_id, organization_id) = api.references().await;
+
+    let invalid = api
+        .post(
+            "/api/users",
+            json!({
+                "name": "Al",
+                "job_id": job_id,
+                "organization_id": organization_id,
+                "email": "not an address",
+            }),
+        )
+        .await;
+    assert_error(
+        &invalid,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+    );
+    assert_eq!(
+        invalid.body["error"]["fields"],
+        json!({
+            "email": "Email must look like name@example.com",
+            "name": "Name must be at least 3 characters",
+        })
+    );
+
+    let missing_job = api
+        .post(
+            "/api/users",
+            json!({ "name": "Alan Turing", "job_id": 999, "organization_id": organization_id }),
+        )
+        .await;
+    assert_error(
+        &missing_job,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "job_not_found",
+    );
+
+    let missing_organization = api
+        .post(
+            "/api/users",
+            json!({ "name": "Alan Turing", "job_id": job_id, "organization_id": 999 }),
+        )
+        .await;
+    assert_error(
+        &missing_organization,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "organization_not_found",
+    );
+
+    let unknown_field = api
+        .post(
+            "/api/users",
+            json!({ "name": "Alan Turing", "job_id": job_id, "organization_id": organization_id, "title": "x" }),
+        )
+        .await;
+    assert_error(&unknown_field, StatusCode::BAD_REQUEST, "invalid_body");
+
+    let malformed = api
+        .send(Method::POST, "/api/jobs", Some(json!("Developer")))
+        .await;
+    assert_error(&malformed, StatusCode::BAD_REQUEST, "invalid_body");
+
+    let empty_name = api.post("/api/organizations", json!({ "name": " " })).await;
+    assert_error(
+        &empty_name,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+    );
+    assert!(empty_name.body["error"]["fields"]["name"].is_string());
+
+    assert_eq!(api.get("/api/users").await.body["total"], 0);
+}
+
+#[tokio::test]
+async fn users_are_listed_a_page_at_a_time() {
+    let api = TestApi::new().await;
+    let (job_id, organization_id) = api.references().await;
+    let other_organization = id(&api
+        .post("/api/organizations", json!({ "name": "Sales" }))
+        .await
+        .body);
+    let names = [
+        "Grace Hopper",
+        "Alan Turing",
+        "Edsger Dijkstra",
+        "Barbara Liskov",
+        "Frances Allen",
+    ];
+    for (index, name) in names.iter().enumerate() {
+        let organization = if index % 2 == 0 {
+            organization_id
+        } else {
+            other_organization
+        };
+        let created = api
+            .post(
+                "/api/users",
+                json!({ "name": name, "job_id": job_id, "organization_id": organization }),
+            )
+            .await;
+        assert_eq!(created.status, StatusCode::CREATED);
+    }
+
+    let mut seen = Vec::new();
+    let mut uri = "/api/users?limit=2".to_string();
+    loop {
+        let page = api.get(&uri).await;
+        assert_eq!(page.status, StatusCode::OK);
+        assert_eq!(page.body["total"], 5);
+        seen.extend(
+            page.body["items"]
+                .as_array()
+                .expect("items")
+                .iter()
+                .map(|user| user["name"].as_str().expect("name").to_string()),
+        );
+        match page.body["next_cursor"].as_str() {
+            Some(cursor) => uri = format!("/api/users?limit=2&cursor={}", cursor),
+            None => break,
+        }
+    }
+    let mut sorted = names.map(str::to_string).to_vec();
+    sorted.sort();
+    assert_eq!(seen, sorted);
+
+    let filtered = api
+        .get(&format!(
+            "/api/users?organization_id={}&sort=name&direction=desc",
+            other_organization
+        ))
+        .await;
+    assert_eq!(filtered.body["total"], 2);
+    assert_eq!(filtered.body["items"][0]["name"], "Barbara Liskov");
+    assert_eq!(filtered.body["items"][1]["name"], "Alan Turing");
+    assert!(filtered.body.get("next_cursor").is_none());
+
+    let searched = api.get("/api/users?q=turing").await;
+    assert_eq!(searched.body["total"], 1);
+
+    assert_error(
+        &api.get("/api/users?sort=age").await,
+        StatusCode::BAD_REQUEST,
+        "invalid_query",
+    );
+    assert_error(
+        &api.get("/api/users?limit=0").await,
+        StatusCode::BAD_REQUEST,
+        "invalid_query",
+    );
+    assert_error(
+        &api.get("/api/users?cursor=%21%21").await,
+        StatusCode::BAD_REQUEST,
+        "invalid_query",
+    );
+    assert_error(
+        &api.get("/api/users?page=2").await,
+        StatusCode::BAD_REQUEST,
+        "invalid_query",
+    );
+}
+
+#[tokio::test]
+async fn jobs_and_organizations_in_use_cannot_be_deleted() {
+    let api = TestApi::new().await;
+    let (job_id, organization_id) = api.references().await;
+    let user = api
+        .post(
+            "/api/users",
+            json!({ "name": "Ken Thompson", "job_id": job_id, "organization_id": organization_id }),
+        )
+        .await;
+
+    assert_error(
+        &api.delete(&format!("/api/jobs/{}", job_id)).await,
+        StatusCode::CONFLICT,
+        "constraint_violation",
+    );
+    assert_error(
+        &api.delete(&format!("/api/organizations/{}", organization_id))
+            .await,
+        StatusCode::CONFLICT,
+        "constraint_violation",
+    );
+
+    api.delete(&format!("/api/users/{}", id(&user.body))).await;
+    assert_eq!(
+        api.delete(&format!("/api/jobs/{}", job_id)).await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        api.delete(&format!("/api/organizations/{}", organization_id))
+            .await
+            .status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(api.get("/api/jobs").await.body["total"], 0);
+}
+
+#[tokio::test]
+async fn jobs_are_listed_by_name_and_searched() {
+    let api = TestApi::new().await;
+    for name in ["Team Lead", "Developer", "Support Engineer"] {
+        api.post("/api/jobs", json!({ "name": name })).await;
+    }
+
+    let all = api.get("/api/jobs").await;
+    let names: Vec<&str> = all.body["items"]
+        .as_array()
+        .expect("items")
+        .iter()
+        .map(|job| job["name"].as_str().expect("name"))
+        .collect();
+    assert_eq!(names, ["Developer", "Support Engineer", "Team Lead"]);
+
+    let searched = api.get("/api/jobs?q=sup").await;
+    assert_eq!(searched.body["total"], 1);
+    assert_eq!(searched.body["items"][0]["name"], "Support Engineer");
+}
+
+#[tokio::test]
+async fn unknown_routes_and_ids_are_not_found() {
+    let api = TestApi::new().await;
+    assert_error(
+        &api.get("/api/widgets").await,
+        StatusCode::NOT_FOUND,
+        "not_found",
+    );
+    assert_error(
+        &api.get("/api/users/abc").await,
+        StatusCode::NOT_FOUND,
+        "not_found",
+    );
+    assert_error(
+        &api.get("/api/organizations/42").await,
+        StatusCode::NOT_FOUND,
+        "not_found",
+    );
+    assert_error(
+        &api.send(Method::PATCH, "/api/jobs/1", Some(json!({})))
+            .await,
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method_not_allowed",
+    );
+}
+
+#[tokio::test]
+async fn a_read_only_database_refuses_changes() {
+    let api = TestApi::open(true).await;
+    assert_eq!(api.get("/api/users").await.status, StatusCode::OK);
+    assert_error(
+        &api.post("/api/jobs", json!({ "name": "Developer" })).await,
+        StatusCode::FORBIDDEN,
+        "read_only",
+    );
+}
+
+#[tokio::test]
+async fn the_openapi_document_describes_every_route() {
+    let api = TestApi::new().await;
+    let document = api.get("/api/openapi.json").await;
+    assert_eq!(document.status, StatusCode::OK);
+    assert_eq!(document.body["openapi"], "3.0.3");
+
+    let paths = document.body["paths"].as_object().expect("paths");
+    for (path, methods) in [
+        ("/users", vec!["get", "post"]),
+        ("/users/{id}", vec!["get", "put", "delete"]),
+        ("/jobs", vec!["get", "post"]),
+        ("/jobs/{id}", vec!["get", "put", "delete"]),
+        ("/organizations", vec!["get", "post"]),
+        ("/organizations/{id}", vec!["get", "put", "delete"]),
+    ] {
+        for method in methods {
+            assert!(
+                paths[path].get(method).is_some(),
+                "{} {} is not documented",
+                method,
+                path
+            );
+        }
+    }
+
+    // Every schema reference resolves.
+    let schemas = document.body["components"]["schemas"]
+        .as_object()
+        .expect("schemas");
+    let text = document.body.to_string();
+    for reference in text.split("#/components/schemas/").skip(1) {
+        let name = reference.split('"').next().expect("a name");
+        assert!(schemas.contains_key(name), "{} is not defined", name);
+    }
+}
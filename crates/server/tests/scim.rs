@@ -0,0 +1,609 @@
+//! Drives the SCIM endpoints against an in-memory database.
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use usermgmt_core::infrastructure::MemoryDatabase;
+use usermgmt_server::scim::{self, ENTERPRISE_SCHEMA, GROUP_SCHEMA, USER_SCHEMA};
+
+const TOKEN: &str = "secret-token";
+const BASE_URL: &str = "http://localhost/scim/v2";
+const PATCH_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+
+struct TestScim {
+    router: Router,
+}
+
+struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Value,
+}
+
+impl TestScim {
+    fn new() -> Self {
+        Self::open(false)
+    }
+
+    fn open(read_only: bool) -> Self {
+        let database = MemoryDatabase::new();
+        Self {
+            router: scim::router(
+                database.services(),
+                TOKEN.to_string(),
+                read_only,
+                BASE_URL.to_string(),
+            ),
+        }
+    }
+
+    async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("router answers");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body reads")
+            .to_bytes();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).expect("JSON body")
+        };
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    /// Sends an authorized request, with `If-Match` when a version is given.
+    async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        if_match: Option<&str>,
+    ) -> TestResponse {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN));
+        if let Some(version) = if_match {
+            request = request.header(header::IF_MATCH, version);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, scim::SCIM_CONTENT_TYPE)
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("valid request");
+        self.send(request).await
+    }
+
+    async fn get(&self, uri: &str) -> TestResponse {
+        self.request(Method::GET, uri, None, None).await
+    }
+
+    async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::POST, uri, Some(body), None).await
+    }
+
+    async fn patch(&self, uri: &str, operations: Value) -> TestResponse {
+        self.request(Method::PATCH, uri, Some(patch_request(operations)), None)
+            .await
+    }
+
+    async fn create_user(&self, name: &str, title: &str, organization: &str) -> Value {
+        let created = self
+            .post(
+                "/scim/v2/Users",
+                json!({
+                    "schemas": [USER_SCHEMA, ENTERPRISE_SCHEMA],
+                    "userName": name,
+                    "title": title,
+                    ENTERPRISE_SCHEMA: { "organization": organization },
+                }),
+            )
+            .await;
+        assert_eq!(created.status, StatusCode::CREATED, "{}", created.body);
+        created.body
+    }
+
+    async fn create_group(&self, name: &str) -> Value {
+        let created = self
+            .post(
+                "/scim/v2/Groups",
+                json!({ "schemas": [GROUP_SCHEMA], "displayName": name }),
+            )
+            .await;
+        assert_eq!(created.status, StatusCode::CREATED, "{}", created.body);
+        created.body
+    }
+
+    /// The user names a filter selects, in id order.
+    async fn filtered(&self, filter: &str) -> Vec<String> {
+        let listed = self
+            .get(&format!("/scim/v2/Users?filter={}", encode(filter)))
+            .await;
+        assert_eq!(listed.status, StatusCode::OK, "{}: {}", filter, listed.body);
+        listed.body["Resources"]
+            .as_array()
+            .expect("a list of resources")
+            .iter()
+            .map(|user| user["userName"].as_str().expect("a user name").to_string())
+            .collect()
+    }
+}
+
+fn patch_request(operations: Value) -> Value {
+    json!({ "schemas": [PATCH_SCHEMA], "Operations": operations })
+}
+
+/// Percent-encodes the characters filters use that cannot go in a query.
+fn encode(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            ' ' => "%20".to_string(),
+            '"' => "%22".to_string(),
+            '[' => "%5B".to_string(),
+            ']' => "%5D".to_string(),
+            '(' => "%28".to_string(),
+            ')' => "%29".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn id(resource: &Value) -> &str {
+    resource["id"].as_str().expect("an id")
+}
+
+fn assert_error(response: &TestResponse, status: StatusCode, scim_type: Option<&str>) {
+    assert_eq!(response.status, status, "{}", response.body);
+    assert_eq!(response.body["status"], status.as_u16().to_string());
+    assert!(response.body["detail"].is_string());
+    match scim_type {
+        Some(scim_type) => assert_eq!(response.body["scimType"], scim_type),
+        None => assert!(response.body["scimType"].is_null()),
+    }
+}
+
+#[tokio::test]
+async fn users_are_filtered() {
+    let scim = TestScim::new();
+    let ada = scim
+        .create_user("Ada Lovelace", "Developer", "Engineering")
+        .await;
+    scim.patch(
+        &format!("/scim/v2/Users/{}", id(&ada)),
+        json!([{
+            "op": "add",
+            "path": "emails",
+            "value": [{ "value": "ada@example.com", "type": "work" }],
+        }]),
+    )
+    .await;
+    scim.create_user("Grace Hopper", "Admiral", "Navy").await;
+    scim.create_user("Alan Turing", "Developer", "Engineering")
+        .await;
+
+    assert_eq!(
+        scim.filtered(r#"userName eq "ada lovelace""#).await,
+        ["Ada Lovelace"]
+    );
+    assert_eq!(
+        scim.filtered(r#"userName co "hop""#).await,
+        ["Grace Hopper"]
+    );
+    assert_eq!(
+        scim.filtered(r#"userName sw "A""#).await,
+        ["Ada Lovelace", "Alan Turing"]
+    );
+    assert_eq!(
+        scim.filtered(r#"title eq "Developer" and userName sw "Al""#)
+            .await,
+        ["Alan Turing"]
+    );
+    assert_eq!(
+        scim.filtered(r#"title eq "Admiral" or userName eq "Alan Turing""#)
+            .await,
+        ["Grace Hopper", "Alan Turing"]
+    );
+    assert_eq!(
+        scim.filtered(r#"not (title eq "Developer")"#).await,
+        ["Grace Hopper"]
+    );
+    assert_eq!(
+        scim.filtered(r#"emails[type eq "work" and value co "example"]"#)
+            .await,
+        ["Ada Lovelace"]
+    );
+    assert_eq!(
+        scim.filtered(&format!(r#"{}:organization eq "Navy""#, ENTERPRISE_SCHEMA))
+            .await,
+        ["Grace Hopper"]
+    );
+    assert!(scim
+        .filtered(r#"userName eq "Charles Babbage""#)
+        .await
+        .is_empty());
+
+    assert_error(
+        &scim
+            .get(&format!("/scim/v2/Users?filter={}", encode("userName eq")))
+            .await,
+        StatusCode::BAD_REQUEST,
+        Some("invalidFilter"),
+    );
+}
+
+#[tokio::test]
+async fn users_are_patched() {
+    let scim = TestScim::new();
+    let created = scim
+        .create_user("Ada Lovelace", "Developer", "Engineering")
+        .await;
+    let uri = format!("/scim/v2/Users/{}", id(&created));
+    assert_eq!(created["meta"]["version"], r#"W/"1""#);
+
+    let added = scim
+        .patch(
+            &uri,
+            json!([
+                {
+                    "op": "add",
+                    "path": "emails",
+                    "value": [{ "value": "ada@example.com", "type": "work" }],
+                },
+                {
+                    "op": "add",
+                    "value": { "phoneNumbers": [{ "value": "+44 20 7946 0000" }] },
+                },
+            ]),
+        )
+        .await;
+    assert_eq!(added.status, StatusCode::OK, "{}", added.body);
+    assert_eq!(added.body["emails"][0]["value"], "ada@example.com");
+    assert_eq!(added.body["phoneNumbers"][0]["value"], "+44 20 7946 0000");
+    assert_eq!(added.body["meta"]["version"], r#"W/"2""#);
+    assert_eq!(added.headers[header::ETAG], r#"W/"2""#);
+
+    let replaced = scim
+        .patch(
+            &uri,
+            json!([
+                { "op": "replace", "path": "title", "value": "Analyst" },
+                {
+                    "op": "replace",
+                    "path": "emails[type eq \"work\"].value",
+                    "value": "countess@example.com",
+                },
+                {
+                    "op": "replace",
+                    "value": { ENTERPRISE_SCHEMA: { "organization": "Research" } },
+                },
+            ]),
+        )
+        .await;
+    assert_eq!(replaced.status, StatusCode::OK, "{}", replaced.body);
+    assert_eq!(replaced.body["title"], "Analyst");
+    assert_eq!(replaced.body["emails"][0]["value"], "countess@example.com");
+    assert_eq!(replaced.body[ENTERPRISE_SCHEMA]["organization"], "Research");
+    assert_eq!(replaced.body["groups"][0]["display"], "Research");
+
+    let removed = scim
+        .patch(
+            &uri,
+            json!([
+                { "op": "remove", "path": "emails" },
+                { "op": "remove", "path": "phoneNumbers[value sw \"+44\"]" },
+            ]),
+        )
+        .await;
+    assert_eq!(removed.status, StatusCode::OK, "{}", removed.body);
+    assert!(removed.body["emails"].is_null());
+    assert!(removed.body["phoneNumbers"].is_null());
+    assert_eq!(removed.body["meta"]["version"], r#"W/"4""#);
+
+    assert_error(
+        &scim
+            .patch(&uri, json!([{ "op": "remove", "path": "userName" }]))
+            .await,
+        StatusCode::BAD_REQUEST,
+        Some("invalidValue"),
+    );
+    assert_error(
+        &scim.patch(&uri, json!([{ "op": "remove" }])).await,
+        StatusCode::BAD_REQUEST,
+        Some("noTarget"),
+    );
+    assert_eq!(scim.get(&uri).await.body, removed.body);
+}
+
+#[tokio::test]
+async fn group_members_are_patched() {
+    let scim = TestScim::new();
+    let engineering = scim.create_group("Engineering").await;
+    let research = scim.create_group("Research").await;
+    let ada = scim
+        .create_user("Ada Lovelace", "Developer", "Engineering")
+        .await;
+    let alan = scim
+        .create_user("Alan Turing", "Developer", "Engineering")
+        .await;
+    let research_uri = format!("/scim/v2/Groups/{}", id(&research));
+    let engineering_uri = format!("/scim/v2/Groups/{}", id(&engineering));
+
+    let added = scim
+        .patch(
+            &research_uri,
+            json!([{ "op": "add", "path": "members", "value": [{ "value": id(&ada) }] }]),
+        )
+        .await;
+    assert_eq!(added.status, StatusCode::OK, "{}", added.body);
+    assert_eq!(added.body["members"][0]["value"], id(&ada));
+    assert_eq!(added.body["members"].as_array().map(Vec::len), Some(1));
+    let moved = scim.get(&format!("/scim/v2/Users/{}", id(&ada))).await;
+    assert_eq!(moved.body["groups"][0]["value"], id(&research));
+    assert_eq!(
+        scim.get(&engineering_uri).await.body["members"]
+            .as_array()
+            .map(Vec::len),
+        Some(1)
+    );
+
+    // Every user needs an organization, so members leave a group only by
+    // joining another one.
+    assert_error(
+        &scim
+            .patch(
+                &research_uri,
+                json!([{
+                    "op": "remove",
+                    "path": format!("members[value eq \"{}\"]", id(&ada)),
+                }]),
+            )
+            .await,
+        StatusCode::BAD_REQUEST,
+        Some("mutability"),
+    );
+    assert_error(
+        &scim
+            .patch(
+                &engineering_uri,
+                json!([{ "op": "remove", "path": "members" }]),
+            )
+            .await,
+        StatusCode::BAD_REQUEST,
+        Some("mutability"),
+    );
+
+    let replaced = scim
+        .patch(
+            &research_uri,
+            json!([
+                { "op": "replace", "path": "displayName", "value": "Analysis" },
+                {
+                    "op": "replace",
+                    "path": "members",
+                    "value": [{ "value": id(&ada) }, { "value": id(&alan) }],
+                },
+            ]),
+        )
+        .await;
+    assert_eq!(replaced.status, StatusCode::OK, "{}", replaced.body);
+    assert_eq!(replaced.body["displayName"], "Analysis");
+    assert_eq!(replaced.body["members"].as_array().map(Vec::len), Some(2));
+
+    // Engineering is empty now, so its last member can be "removed".
+    let emptied = scim
+        .patch(
+            &engineering_uri,
+            json!([{ "op": "remove", "path": "members" }]),
+        )
+        .await;
+    assert_eq!(emptied.status, StatusCode::OK, "{}", emptied.body);
+    assert_eq!(emptied.body["members"], json!([]));
+
+    assert_error(
+        &scim
+            .patch(
+                &research_uri,
+                json!([{ "op": "add", "path": "members", "value": [{ "value": "999" }] }]),
+            )
+            .await,
+        StatusCode::BAD_REQUEST,
+        Some("invalidValue"),
+    );
+}
+
+#[tokio::test]
+async fn requests_need_the_token() {
+    let scim = TestScim::new();
+    let send = |authorization: Option<&str>| {
+        let mut request = Request::builder().uri("/scim/v2/Users");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        request.body(Body::empty()).expect("valid request")
+    };
+
+    for authorization in [
+        None,
+        Some("Bearer wrong-token"),
+        Some("Bearer secret-tokens"),
+        Some("Basic secret-token"),
+    ] {
+        let response = scim.send(send(authorization)).await;
+        assert_error(&response, StatusCode::UNAUTHORIZED, None);
+        assert_eq!(response.headers[header::WWW_AUTHENTICATE], "Bearer");
+    }
+    let response = scim.send(send(Some("bearer secret-token"))).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["totalResults"], 0);
+}
+
+#[tokio::test]
+async fn a_read_only_database_refuses_changes() {
+    let scim = TestScim::open(true);
+
+    let listed = scim.get("/scim/v2/Users").await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_error(
+        &scim
+            .post(
+                "/scim/v2/Groups",
+                json!({ "schemas": [GROUP_SCHEMA], "displayName": "Engineering" }),
+            )
+            .await,
+        StatusCode::FORBIDDEN,
+        None,
+    );
+    assert_error(
+        &scim
+            .request(Method::DELETE, "/scim/v2/Users/1", None, None)
+            .await,
+        StatusCode::FORBIDDEN,
+        None,
+    );
+}
+
+#[tokio::test]
+async fn a_stale_if_match_fails_the_precondition() {
+    let scim = TestScim::new();
+    let created = scim
+        .create_user("Ada Lovelace", "Developer", "Engineering")
+        .await;
+    let uri = format!("/scim/v2/Users/{}", id(&created));
+    let rename = |name: &str| {
+        json!({
+            "schemas": [USER_SCHEMA],
+            "userName": name,
+            "title": "Developer",
+        })
+    };
+
+    let renamed = scim
+        .request(
+            Method::PUT,
+            &uri,
+            Some(rename("Ada King")),
+            Some(r#"W/"1""#),
+        )
+        .await;
+    assert_eq!(renamed.status, StatusCode::OK, "{}", renamed.body);
+    assert_eq!(renamed.headers[header::ETAG], r#"W/"2""#);
+
+    let stale = Some(r#"W/"1""#);
+    assert_error(
+        &scim
+            .request(Method::PUT, &uri, Some(rename("Ada Byron")), stale)
+            .await,
+        StatusCode::PRECONDITION_FAILED,
+        None,
+    );
+    assert_error(
+        &scim
+            .request(
+                Method::PATCH,
+                &uri,
+                Some(patch_request(
+                    json!([{ "op": "replace", "path": "userName", "value": "Ada Byron" }]),
+                )),
+                stale,
+            )
+            .await,
+        StatusCode::PRECONDITION_FAILED,
+        None,
+    );
+    assert_error(
+        &scim.request(Method::DELETE, &uri, None, stale).await,
+        StatusCode::PRECONDITION_FAILED,
+        None,
+    );
+    assert_eq!(scim.get(&uri).await.body["userName"], "Ada King");
+
+    let any = scim
+        .request(Method::PUT, &uri, Some(rename("Ada Byron")), Some("*"))
+        .await;
+    assert_eq!(any.status, StatusCode::OK, "{}", any.body);
+    let deleted = scim
+        .request(Method::DELETE, &uri, None, Some(r#"W/"3""#))
+        .await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn deactivated_users_are_deleted() {
+    let scim = TestScim::new();
+    let ada = scim
+        .create_user("Ada Lovelace", "Developer", "Engineering")
+        .await;
+    let grace = scim.create_user("Grace Hopper", "Admiral", "Navy").await;
+
+    let uri = format!("/scim/v2/Users/{}", id(&ada));
+    let deactivated = scim
+        .patch(
+            &uri,
+            json!([{ "op": "replace", "path": "active", "value": false }]),
+        )
+        .await;
+    assert_eq!(deactivated.status, StatusCode::OK, "{}", deactivated.body);
+    assert_eq!(deactivated.body["userName"], "Ada Lovelace");
+    assert_eq!(deactivated.body["active"], false);
+    assert_error(&scim.get(&uri).await, StatusCode::NOT_FOUND, None);
+
+    let uri = format!("/scim/v2/Users/{}", id(&grace));
+    let replaced = scim
+        .request(
+            Method::PUT,
+            &uri,
+            Some(json!({
+                "schemas": [USER_SCHEMA],
+                "userName": "Grace Hopper",
+                "active": "False",
+            })),
+            None,
+        )
+        .await;
+    assert_eq!(replaced.status, StatusCode::OK, "{}", replaced.body);
+    assert_eq!(replaced.body["active"], false);
+    assert_error(&scim.get(&uri).await, StatusCode::NOT_FOUND, None);
+
+    assert_error(
+        &scim
+            .post(
+                "/scim/v2/Users",
+                json!({ "schemas": [USER_SCHEMA], "userName": "Alan Turing", "active": false }),
+            )
+            .await,
+        StatusCode::BAD_REQUEST,
+        Some("invalidValue"),
+    );
+    // The name is free again once its user is gone.
+    scim.create_user("Ada Lovelace", "Developer", "Engineering")
+        .await;
+    assert_error(
+        &scim
+            .post(
+                "/scim/v2/Users",
+                json!({ "schemas": [USER_SCHEMA], "userName": "ada lovelace" }),
+            )
+            .await,
+        StatusCode::CONFLICT,
+        Some("uniqueness"),
+    );
+}
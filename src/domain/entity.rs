@@ -1,19 +0,0 @@
-use std::collections::HashMap;
-
-pub trait Entity: Clone + Default + std::fmt::Debug {
-    fn id(&self) -> i64;
-    fn set_id(&mut self, id: i64);
-    fn name(&self) -> &str;
-    fn set_name(&mut self, name: String);
-    fn errors(&self) -> &HashMap<&'static str, &'static str>;
-    fn validate(&mut self) -> Result<(), &HashMap<&'static str, &'static str>>;
-    fn validate_property(&mut self, propery: &str);
-    fn clear_errors(&mut self);
-}
-
-pub enum DomainEntity {
-    User,
-    Job,
-    Organization,
-    None,
-}
@@ -1,14 +0,0 @@
-mod entity;
-mod job;
-mod organization;
-pub mod repositories;
-pub mod services;
-mod user;
-
-pub use entity::DomainEntity;
-pub use entity::Entity;
-pub use job::Job;
-pub use organization::Organization;
-pub use repositories::{JobRepository, OrganizationRepository, RepositoryError, UserRepository};
-pub use services::{UserService, UserServiceError};
-pub use user::User;
@@ -1,7 +0,0 @@
-mod job_service;
-mod organization_service;
-mod user_service;
-
-pub use job_service::{JobService, JobServiceError};
-pub use organization_service::{OrganizationService, OrganizationServiceError};
-pub use user_service::{UserService, UserServiceError};
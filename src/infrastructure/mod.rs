@@ -1,8 +0,0 @@
-mod database;
-mod entity_state;
-
-pub use database::{get_database_path, Database};
-pub use entity_state::EntityState;
-pub mod job_repository;
-pub mod organization_repository;
-pub mod user_repository;